@@ -87,6 +87,7 @@ impl AuthUser {
                             id: uid,
                             user_token: "".to_string(),
                             created_at: "".to_string(),
+                            paused_until: None,
                         }
                     }
                 };
@@ -7,6 +7,55 @@ const MS_PER_WEEK: u64 = 7 * MS_PER_DAY;
 
 pub const AUTH_TOKEN_SIGNIN_EXPIRES: u64 = MS_PER_WEEK * 2;
 
+// overridden by the ENV_KEY_TELEGRAM_API_BASE_URL var, if set
+pub const TELEGRAM_API_DEFAULT_BASE_URL: &str = "https://api.telegram.org";
+
+// Telegram API retries, for 429s (honoring retry_after, up to the max) and 5xx on idempotent methods (exponential backoff)
+// kept short since it all happens within a single worker request
+pub const TELEGRAM_MAX_ATTEMPTS: u32 = 3;
+pub const TELEGRAM_BACKOFF_BASE_MS: u64 = 500;
+pub const TELEGRAM_MAX_RETRY_DELAY_MS: u64 = 10_000;
+
+// dev-only getUpdates polling (instead of the webhook), how long Telegram may hold the request open
+pub const TELEGRAM_POLL_TIMEOUT_SECS: u64 = 20;
+
+// Mini App initData older than this is rejected, even if the hash checks out
+pub const TELEGRAM_MINI_APP_INIT_DATA_MAX_AGE_SECS: u64 = 60 * 60 * 24;
+
+// one-time t.me deep links (registration, contact invites, groups)
+// long enough to send an invite to someone who might not be around right away
+pub const DEEP_LINK_EXPIRES_SECS: u64 = 60 * 60 * 24;
+// /linkchannel codes, they're posted right away from the same device
+pub const CHANNEL_LINK_CODE_EXPIRES_SECS: u64 = 60 * 60;
+
+// delivery history, older entries are deleted by the cleanup cron
+// overridden by the ENV_KEY_DELIVERY_RETENTION_DAYS var, if set
+pub const DELIVERY_RETENTION_DEFAULT_DAYS: u32 = 30;
+pub const DELIVERY_PAGE_SIZE: u32 = 50;
+// must match a cron trigger in wrangler.toml, any other trigger is the dev poller
+pub const DELIVERY_CLEANUP_CRON: &str = "0 3 * * *";
+
+// actions and destinations, per page
+pub const LIST_PAGE_SIZE_DEFAULT: u32 = 50;
+pub const LIST_PAGE_SIZE_MAX: u32 = 100;
+
+// test sends (actions and destination pings) per user, so the button can't be used to spam a chat
+pub const TEST_SEND_MAX_PER_WINDOW: u32 = 5;
+pub const TEST_SEND_WINDOW_SECS: u64 = 60 * 10;
+
+// saying this (anywhere in a transcript) pauses everything, nothing in that transcript is sent
+pub const PAUSE_PHRASE: &str = "omi assist pause";
+// for the phrase, and /pause without a number of hours
+pub const PAUSE_DEFAULT_SECS: u64 = 60 * 60 * 2;
+// the longest pause, so it can't be forgotten about
+pub const PAUSE_MAX_SECS: u64 = 60 * 60 * 24 * 7;
+
+// actions in one config import, they're all written in a single batch
+pub const CONFIG_IMPORT_MAX_ACTIONS: usize = 200;
+
+// abandoned bot conversations (e.g. /addaction) are forgotten after this
+pub const CONVERSATION_EXPIRES_SECS: u64 = 60 * 60;
+
 // the key is never used in isolation, rather it's used in conjunction with the id
 // 16 bytes of randomness is more than enough
 pub const AUTH_TOKEN_KEY_LENGTH: usize = 16;
@@ -16,6 +65,9 @@ cfg_if::cfg_if! {
         pub const ENV_KEY_TELEGRAM_BOT_TOKEN:&'static str = "TELEGRAM_BOT_TOKEN_DEV";
         pub const ENV_KEY_TELEGRAM_AUTH_TOKEN:&'static str = "TELEGRAM_BOT_TOKEN"; // always prod
         pub const ENV_KEY_TELEGRAM_WEBHOOK_SECRET:&'static str = "TELEGRAM_WEBHOOK_SECRET";
+        pub const ENV_KEY_TELEGRAM_API_BASE_URL:&'static str = "TELEGRAM_API_BASE_URL";
+        pub const ENV_KEY_TELEGRAM_GROUP_LINK_ALLOW_MEMBERS:&'static str = "TELEGRAM_GROUP_LINK_ALLOW_MEMBERS";
+        pub const ENV_KEY_DELIVERY_RETENTION_DAYS:&'static str = "DELIVERY_RETENTION_DAYS";
         pub const ENV_KEY_ADMIN_CODE:&'static str = "ADMIN_CODE";
         pub const API_DOMAIN:&'static str = "https://terrier-direct-openly.ngrok-free.app";
         pub const API_ROOT_PATH: &'static str = "";
@@ -23,11 +75,18 @@ cfg_if::cfg_if! {
         pub const ALLOWED_ORIGINS: &[&'static str] = &["http://localhost:8080", "http://127.0.0.1:8080"];
         pub const DB_BINDING:&'static str = "DB-omi-assist";
         pub const KV_BINDING_AUTH_TOKEN_SIGNIN:&'static str = "KV-omi-auth-token-signin";
+        pub const KV_BINDING_CONVERSATION:&'static str = "KV-omi-auth-token-signin"; // shared, keys are prefixed
+        pub const KV_BINDING_TELEGRAM_POLL:&'static str = "KV-omi-auth-token-signin"; // shared, keys are prefixed
+        pub const KV_BINDING_RATE_LIMIT:&'static str = "KV-omi-auth-token-signin"; // shared, keys are prefixed
         pub const FRONTEND_URL:&'static str = "http://localhost::8080";
+        pub const TELEGRAM_POLLING_ENABLED:bool = true;
     } else {
         pub const ENV_KEY_TELEGRAM_BOT_TOKEN:&'static str = "TELEGRAM_BOT_TOKEN";
         pub const ENV_KEY_TELEGRAM_AUTH_TOKEN:&'static str = "TELEGRAM_BOT_TOKEN";
         pub const ENV_KEY_TELEGRAM_WEBHOOK_SECRET:&'static str = "TELEGRAM_WEBHOOK_SECRET";
+        pub const ENV_KEY_TELEGRAM_API_BASE_URL:&'static str = "TELEGRAM_API_BASE_URL";
+        pub const ENV_KEY_TELEGRAM_GROUP_LINK_ALLOW_MEMBERS:&'static str = "TELEGRAM_GROUP_LINK_ALLOW_MEMBERS";
+        pub const ENV_KEY_DELIVERY_RETENTION_DAYS:&'static str = "DELIVERY_RETENTION_DAYS";
         pub const ENV_KEY_ADMIN_CODE:&'static str = "ADMIN_CODE";
         pub const API_DOMAIN:&'static str = "https://omi-assist-api-prod.dakom.workers.dev";
         pub const API_ROOT_PATH: &'static str = "";
@@ -35,7 +94,11 @@ cfg_if::cfg_if! {
         pub const ALLOWED_ORIGINS: &[&'static str] = &["https://omi-assist.pages.dev"];
         pub const DB_BINDING:&'static str = "DB-omi-assist";
         pub const KV_BINDING_AUTH_TOKEN_SIGNIN:&'static str = "KV-omi-auth-token-signin";
+        pub const KV_BINDING_CONVERSATION:&'static str = "KV-omi-auth-token-signin"; // shared, keys are prefixed
+        pub const KV_BINDING_TELEGRAM_POLL:&'static str = "KV-omi-auth-token-signin"; // shared, keys are prefixed
+        pub const KV_BINDING_RATE_LIMIT:&'static str = "KV-omi-auth-token-signin"; // shared, keys are prefixed
         pub const FRONTEND_URL:&'static str = "https://omi-assist.pages.dev";
+        pub const TELEGRAM_POLLING_ENABLED:bool = false; // prod always uses the webhook
     }
 }
 
@@ -43,14 +106,18 @@ pub const DB_TABLE: DbTable = DbTable {
     user_account: "user_account",
     omi_account: "omi_account",
     telegram_account: "telegram_account",
-    telegram_destination: "telegram_destination",
+    destination: "destination",
     telegram_action: "telegram_action",
+    invitation: "invitation",
+    delivery: "delivery",
 };
 
 pub struct DbTable {
     pub user_account: &'static str,
     pub omi_account: &'static str,
     pub telegram_account: &'static str,
-    pub telegram_destination: &'static str,
+    pub destination: &'static str,
     pub telegram_action: &'static str,
+    pub invitation: &'static str,
+    pub delivery: &'static str,
 }
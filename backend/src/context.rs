@@ -6,7 +6,8 @@ use worker::{Context, Env};
 pub struct ApiContext<R> {
     pub req: R,
     pub env: Env,
-    pub cf_ctx: Context,
+    // None when there's no incoming http request, e.g. polled telegram updates
+    pub cf_ctx: Option<Context>,
     pub user: Option<AuthUser>,
     pub lang: ContentLanguage,
 }
@@ -32,7 +33,7 @@ impl ApiContext<HttpRequest> {
         Self {
             req,
             env,
-            cf_ctx,
+            cf_ctx: Some(cf_ctx),
             user,
             lang,
         }
@@ -52,6 +53,17 @@ impl ApiContext<HttpRequest> {
 }
 
 impl<R> ApiContext<R> {
+    // for requests that didn't come in over http, so there's no user or language to go by
+    pub fn new_internal(req: R, env: Env) -> Self {
+        Self {
+            req,
+            env,
+            cf_ctx: None,
+            user: None,
+            lang: DEFAULT_CONTENT_LANG,
+        }
+    }
+
     pub fn uid_unchecked(&self) -> UserId {
         self.user.as_ref().unwrap().account.id.clone()
     }
@@ -71,10 +83,18 @@ impl ContentLanguage {
         }
     }
 
-    const fn all() -> [Self; 2] {
+    pub const fn all() -> [Self; 2] {
         [Self::English, Self::Hebrew]
     }
 
+    // for the bot's commands and descriptions, English is the fallback for everyone else
+    pub fn telegram_language_code(&self) -> Option<&'static str> {
+        match self {
+            Self::English => None,
+            Self::Hebrew => Some("he"),
+        }
+    }
+
     pub fn try_from_lang_id(lang_id: LanguageIdentifier) -> Option<Self> {
         for lang in Self::all().iter() {
             if lang.to_lang_id().matches(&lang_id, true, true) {
@@ -1,11 +1,21 @@
-use crate::{config::DB_TABLE, prelude::*};
+use crate::{
+    config::DB_TABLE,
+    db::{
+        page::Page,
+        query::{
+            and, count_where, delete_where, from_d1_row, id_in, insert_into, select_join_where,
+            update_where, Conditions, Join, Query,
+        },
+    },
+    destination::{lookup, DestinationKind},
+    prelude::*,
+};
 use serde::{Deserialize, Serialize};
 use shared::{
-    api::action::{
-        Action, ActionDestination, ActionDestinationId, ActionDestinationKind, ActionId,
-    },
+    api::action::{Action, ActionDestination, ActionDestinationId, ActionId, ParseMode},
     user::UserId,
 };
+use worker::D1PreparedStatement;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TelegramActionDb {
@@ -13,113 +23,345 @@ pub struct TelegramActionDb {
     pub destination_id: ActionDestinationId,
     pub prompt: String,
     pub msg: String,
+    pub parse_mode: ParseMode,
     pub created_at: String,
+    // never edited if None
+    pub updated_at: Option<String>,
+    pub enabled: DbBool,
 }
 
-impl TelegramActionDb {
-    pub async fn _load(env: &Env, id: &ActionId) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE id = ?1",
-                DB_TABLE.telegram_action
-            ))
-            .bind(&[id.into()])?
-            .first::<Self>(None)
-            .await?
-            .map(Self::from)
-            .ok_or(format!("no such action with id {id}").into())
+// an action along with everything needed to deliver it
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ActionWithDestinationDb {
+    pub id: ActionId,
+    pub destination_id: ActionDestinationId,
+    pub prompt: String,
+    pub msg: String,
+    pub parse_mode: ParseMode,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    pub enabled: DbBool,
+    pub name: String,
+    pub kind: DestinationKind,
+    pub config: String,
+    pub active: DbBool,
+    pub consented: DbBool,
+}
+
+from_d1_row!(TelegramActionDb, ActionWithDestinationDb);
+
+impl ActionWithDestinationDb {
+    pub fn config(&self) -> ApiResult<serde_json::Value> {
+        serde_json::from_str(&self.config).map_err(|err| ApiError::Db(err.to_string()))
     }
+}
+
+impl TryFrom<ActionWithDestinationDb> for Action {
+    type Error = ApiError;
+
+    fn try_from(r: ActionWithDestinationDb) -> ApiResult<Self> {
+        let kind = lookup(r.kind).api_kind(&r.config()?)?;
 
+        Ok(Action {
+            id: r.id,
+            destination: ActionDestination {
+                id: r.destination_id,
+                name: r.name,
+                kind,
+                active: r.active.into(),
+                consented: r.consented.into(),
+            },
+            prompt: r.prompt,
+            message: r.msg,
+            parse_mode: r.parse_mode,
+            enabled: r.enabled.into(),
+        })
+    }
+}
+
+impl TelegramActionDb {
     pub async fn insert(
         env: &Env,
         id: &ActionId,
         destination_id: &ActionDestinationId,
         prompt: &str,
         msg: &str,
+        parse_mode: ParseMode,
+        enabled: bool,
     ) -> ApiResult<()> {
-        get_d1(env)?
-            .prepare(format!(
-                "INSERT INTO {} (id, destination_id, prompt, msg) VALUES (?1, ?2, ?3, ?4)",
-                DB_TABLE.telegram_action
-            ))
-            .bind(&[id.into(), destination_id.into(), prompt.into(), msg.into()])?
+        Self::insert_query(env, id, destination_id, prompt, msg, parse_mode, enabled)?
             .run()
-            .await?
-            .into_result()
+            .await
+    }
+
+    // for writes that go in a batch, see run_batch
+    pub fn insert_statement(
+        env: &Env,
+        id: &ActionId,
+        destination_id: &ActionDestinationId,
+        prompt: &str,
+        msg: &str,
+        parse_mode: ParseMode,
+        enabled: bool,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(
+            Self::insert_query(env, id, destination_id, prompt, msg, parse_mode, enabled)?
+                .into_statement(),
+        )
+    }
+
+    fn insert_query(
+        env: &Env,
+        id: &ActionId,
+        destination_id: &ActionDestinationId,
+        prompt: &str,
+        msg: &str,
+        parse_mode: ParseMode,
+        enabled: bool,
+    ) -> ApiResult<Query> {
+        Query::new(
+            env,
+            "telegram_action.insert",
+            insert_into(
+                DB_TABLE.telegram_action,
+                &[
+                    "id",
+                    "destination_id",
+                    "prompt",
+                    "msg",
+                    "parse_mode",
+                    "enabled",
+                ],
+            ),
+            &[
+                id.into(),
+                destination_id.into(),
+                prompt.into(),
+                msg.into(),
+                parse_mode.as_str().into(),
+                DbBool::from(enabled).into(),
+            ],
+        )
+    }
+
+    // every field is set, the handler merges in whatever wasn't changed
+    // the new destination must already be checked to belong to the user
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionId,
+        destination_id: &ActionDestinationId,
+        prompt: &str,
+        msg: &str,
+        parse_mode: ParseMode,
+        enabled: bool,
+    ) -> ApiResult<()> {
+        Self::update_query(
+            env,
+            user_id,
+            id,
+            destination_id,
+            prompt,
+            msg,
+            parse_mode,
+            enabled,
+        )?
+        .run()
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_statement(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionId,
+        destination_id: &ActionDestinationId,
+        prompt: &str,
+        msg: &str,
+        parse_mode: ParseMode,
+        enabled: bool,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Self::update_query(
+            env,
+            user_id,
+            id,
+            destination_id,
+            prompt,
+            msg,
+            parse_mode,
+            enabled,
+        )?
+        .into_statement())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update_query(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionId,
+        destination_id: &ActionDestinationId,
+        prompt: &str,
+        msg: &str,
+        parse_mode: ParseMode,
+        enabled: bool,
+    ) -> ApiResult<Query> {
+        Query::new(
+            env,
+            "telegram_action.update",
+            update_where(
+                DB_TABLE.telegram_action,
+                "destination_id = ?1, prompt = ?2, msg = ?3, parse_mode = ?4, enabled = ?5, updated_at = CURRENT_TIMESTAMP",
+                &and(&[
+                    "id = ?6",
+                    &id_in("destination_id", DB_TABLE.destination, "user_id = ?7"),
+                ]),
+            ),
+            &[
+                destination_id.into(),
+                prompt.into(),
+                msg.into(),
+                parse_mode.as_str().into(),
+                DbBool::from(enabled).into(),
+                id.into(),
+                user_id.into(),
+            ],
+        )
     }
 
     pub async fn delete(env: &Env, user_id: &UserId, id: &ActionId) -> ApiResult<()> {
-        let stmt = format!(
-            r#"
-            DELETE FROM {} 
-            WHERE id = ?1 
-            AND destination_id IN (
-                SELECT id FROM {} WHERE user_id = ?2 
-            )
-        "#,
-            DB_TABLE.telegram_action, DB_TABLE.telegram_destination
-        );
+        Self::delete_query(env, user_id, id)?.run().await
+    }
 
-        get_d1(env)?
-            .prepare(stmt)
-            .bind(&[id.into(), user_id.into()])?
-            .run()
-            .await?
-            .into_result()
+    pub fn delete_statement(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionId,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Self::delete_query(env, user_id, id)?.into_statement())
     }
 
-    pub async fn list(env: &Env, user_id: &UserId) -> ApiResult<Vec<Action>> {
-        #[derive(Deserialize, Serialize, Debug)]
-        pub struct JoinedRecord {
-            pub id: ActionId,
-            pub destination_id: ActionDestinationId,
-            pub prompt: String,
-            pub msg: String,
-            pub name: String,
-            pub created_at: String,
-            pub chat_id: i64,
-            pub kind: u8,
-        }
-
-        let stmt = format!(
-            r#"
-            SELECT ta.*, td.chat_id, td.kind, td.name
-            FROM {} AS ta
-            JOIN {} AS td ON ta.destination_id = td.id
-            WHERE td.user_id = ?1
-        "#,
-            DB_TABLE.telegram_action, DB_TABLE.telegram_destination
+    fn delete_query(env: &Env, user_id: &UserId, id: &ActionId) -> ApiResult<Query> {
+        Query::new(
+            env,
+            "telegram_action.delete",
+            delete_where(
+                DB_TABLE.telegram_action,
+                &and(&[
+                    "id = ?1",
+                    &id_in("destination_id", DB_TABLE.destination, "user_id = ?2"),
+                ]),
+            ),
+            &[id.into(), user_id.into()],
+        )
+    }
+
+    pub async fn count_by_destination(
+        env: &Env,
+        destination_id: &ActionDestinationId,
+    ) -> ApiResult<u32> {
+        Query::new(
+            env,
+            "telegram_action.count_by_destination",
+            count_where(DB_TABLE.telegram_action, "destination_id = ?1"),
+            &[destination_id.into()],
+        )?
+        .count()
+        .await
+    }
+
+    pub async fn list_with_destinations(
+        env: &Env,
+        user_id: &UserId,
+    ) -> ApiResult<Vec<ActionWithDestinationDb>> {
+        Query::new(
+            env,
+            "telegram_action.list_with_destinations",
+            select_with_destination("d.user_id = ?1 ORDER BY ta.created_at, ta.id"),
+            &[user_id.into()],
+        )?
+        .all()
+        .await
+    }
+
+    // scoped to the user, like delete
+    pub async fn load_with_destination(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionId,
+    ) -> ApiResult<Option<ActionWithDestinationDb>> {
+        Query::new(
+            env,
+            "telegram_action.load_with_destination",
+            select_with_destination("ta.id = ?1 AND d.user_id = ?2"),
+            &[id.into(), user_id.into()],
+        )?
+        .first()
+        .await
+    }
+
+    // for the dashboard, see db::page
+    pub async fn list_page(
+        env: &Env,
+        user_id: &UserId,
+        page: &Page,
+    ) -> ApiResult<(Vec<Action>, Option<String>)> {
+        let (condition, params) = page.where_clause(
+            "ta.id",
+            Conditions::default().eq("d.user_id", user_id.into()),
         );
 
-        Ok(get_d1(env)?
-            .prepare(stmt)
-            .bind(&[user_id.into()])?
-            .all()
+        let rows = Query::new(
+            env,
+            "telegram_action.list_page",
+            select_with_destination(&condition),
+            &params,
+        )?
+        .all::<ActionWithDestinationDb>()
+        .await?;
+
+        let (rows, next_cursor) = page.finish(rows, |row| row.id.to_string());
+
+        Ok((
+            rows.into_iter()
+                .map(Action::try_from)
+                .collect::<ApiResult<_>>()?,
+            next_cursor,
+        ))
+    }
+
+    // everything, for the bot and the webhook
+    pub async fn list(env: &Env, user_id: &UserId) -> ApiResult<Vec<Action>> {
+        Self::list_with_destinations(env, user_id)
             .await?
-            .results::<JoinedRecord>()?
             .into_iter()
-            .map(|r| Action {
-                id: r.id,
-                destination: ActionDestination {
-                    id: r.destination_id,
-                    name: r.name,
-                    kind: match r.kind {
-                        1 => ActionDestinationKind::TelegramDm { chat_id: r.chat_id },
-                        2 => ActionDestinationKind::TelegramGroup { chat_id: r.chat_id },
-                        _ => unreachable!(),
-                    },
-                },
-                prompt: r.prompt,
-                message: r.msg,
-            })
-            .collect())
+            .map(Action::try_from)
+            .collect()
     }
 }
 
+// the rows for ActionWithDestinationDb, "ta" is the action and "d" its destination
+fn select_with_destination(condition: &str) -> String {
+    select_join_where(
+        "ta.*, d.name, d.kind, d.config, d.active, d.consented",
+        DB_TABLE.telegram_action,
+        "ta",
+        &[Join::Inner(
+            DB_TABLE.destination,
+            "d",
+            "ta.destination_id = d.id",
+        )],
+        condition,
+    )
+}
+
 // CREATE TABLE telegram_action (
 //     id TEXT PRIMARY KEY,
-//     destination_id TEXT NOT NULL,
+//     destination_id TEXT NOT NULL REFERENCES destination(id) ON DELETE CASCADE,
 //     prompt TEXT NOT NULL,
 //     msg TEXT NOT NULL,
-//     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+//     parse_mode TEXT NOT NULL DEFAULT 'plain',
+//     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+//     updated_at DATETIME,
+//     enabled INTEGER NOT NULL DEFAULT 1
 // ) WITHOUT ROWID;
@@ -0,0 +1,178 @@
+use crate::{
+    config::{DB_TABLE, DELIVERY_PAGE_SIZE},
+    db::{
+        page::Page,
+        query::{
+            delete_where, from_d1_row, insert_into, select_join_where, Conditions, Join, Query,
+        },
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use shared::{
+    api::action::{
+        ActionDelivery, ActionDestinationId, ActionId, DeliveryId, DeliveryStatus,
+        ListDeliveriesRequest, ListSort,
+    },
+    user::UserId,
+};
+
+// joined with the action and destination, which may be gone by now
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DeliveryDb {
+    pub id: DeliveryId,
+    pub user_id: UserId,
+    pub action_id: ActionId,
+    pub destination_id: ActionDestinationId,
+    pub text: String,
+    pub message_id: Option<i64>,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub prompt: Option<String>,
+    pub destination_name: Option<String>,
+}
+
+from_d1_row!(DeliveryDb);
+
+impl From<DeliveryDb> for ActionDelivery {
+    fn from(d: DeliveryDb) -> Self {
+        ActionDelivery {
+            id: d.id,
+            action_id: d.action_id,
+            destination_id: d.destination_id,
+            prompt: d.prompt,
+            destination_name: d.destination_name,
+            text: d.text,
+            message_id: d.message_id,
+            status: d.status,
+            error: d.error,
+            created_at: d.created_at,
+        }
+    }
+}
+
+impl DeliveryDb {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert(
+        env: &Env,
+        user_id: &UserId,
+        action_id: &ActionId,
+        destination_id: &ActionDestinationId,
+        text: &str,
+        message_id: Option<i64>,
+        status: DeliveryStatus,
+        error: Option<&str>,
+    ) -> ApiResult<()> {
+        Query::new(
+            env,
+            "delivery.insert",
+            insert_into(
+                DB_TABLE.delivery,
+                &[
+                    "id",
+                    "user_id",
+                    "action_id",
+                    "destination_id",
+                    "text",
+                    "message_id",
+                    "status",
+                    "error",
+                ],
+            ),
+            &[
+                DeliveryId::new(uuid::Uuid::now_v7()).into(),
+                user_id.into(),
+                action_id.into(),
+                destination_id.into(),
+                text.into(),
+                match message_id {
+                    Some(message_id) => JsValue::from_f64(message_id as f64),
+                    None => JsValue::NULL,
+                },
+                status.as_str().into(),
+                match error {
+                    Some(error) => error.into(),
+                    None => JsValue::NULL,
+                },
+            ],
+        )?
+        .run()
+        .await
+    }
+
+    // newest first, see db::page
+    pub async fn list(
+        env: &Env,
+        user_id: &UserId,
+        req: &ListDeliveriesRequest,
+    ) -> ApiResult<(Vec<ActionDelivery>, Option<String>)> {
+        let page = Page::new(req.cursor.as_deref(), DELIVERY_PAGE_SIZE, ListSort::Newest)?;
+
+        let mut conditions = Conditions::default().eq("d.user_id", user_id.into());
+
+        if let Some(action_id) = req.action_id.as_ref() {
+            conditions.push("d.action_id", "=", action_id.into());
+        }
+
+        if let Some(destination_id) = req.destination_id.as_ref() {
+            conditions.push("d.destination_id", "=", destination_id.into());
+        }
+
+        if let Some(status) = req.status {
+            conditions.push("d.status", "=", status.as_str().into());
+        }
+
+        let (condition, params) = page.where_clause("d.id", conditions);
+
+        // left joins, the action or destination may have been deleted since
+        let deliveries = Query::new(
+            env,
+            "delivery.list",
+            select_join_where(
+                "d.*, a.prompt AS prompt, dest.name AS destination_name",
+                DB_TABLE.delivery,
+                "d",
+                &[
+                    Join::Left(DB_TABLE.telegram_action, "a", "a.id = d.action_id"),
+                    Join::Left(DB_TABLE.destination, "dest", "dest.id = d.destination_id"),
+                ],
+                &condition,
+            ),
+            &params,
+        )?
+        .all::<Self>()
+        .await?;
+
+        let (deliveries, next_cursor) = page.finish(deliveries, |delivery| delivery.id.to_string());
+
+        Ok((
+            deliveries.into_iter().map(ActionDelivery::from).collect(),
+            next_cursor,
+        ))
+    }
+
+    // returns how many were deleted
+    pub async fn delete_older_than(env: &Env, days: u32) -> ApiResult<usize> {
+        Query::new(
+            env,
+            "delivery.delete_older_than",
+            delete_where(DB_TABLE.delivery, "created_at < datetime('now', ?1)"),
+            &[format!("-{days} days").into()],
+        )?
+        .changes()
+        .await
+    }
+}
+
+// CREATE TABLE delivery (
+//     id TEXT PRIMARY KEY,
+//     user_id TEXT NOT NULL REFERENCES user_account(id) ON DELETE CASCADE,
+//     action_id TEXT NOT NULL,
+//     destination_id TEXT NOT NULL,
+//     text TEXT NOT NULL,
+//     message_id INTEGER,
+//     status TEXT NOT NULL,
+//     error TEXT,
+//     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+// ) WITHOUT ROWID;
@@ -1,48 +1,73 @@
-use crate::{config::DB_TABLE, prelude::*};
+use crate::{
+    config::DB_TABLE,
+    db::{
+        action::TelegramActionDb,
+        page::Page,
+        query::{
+            delete_where, from_d1_row, insert_into, select_where, update_where, Conditions, Query,
+        },
+    },
+    destination::{lookup, Destination, DestinationKind},
+    prelude::*,
+};
 use serde::{Deserialize, Serialize};
 use shared::{
-    api::action::{ActionDestination, ActionDestinationId, ActionDestinationKind},
+    api::action::{ActionDestination, ActionDestinationId},
     user::UserId,
 };
+use worker::D1PreparedStatement;
 
 #[derive(Deserialize, Serialize, Debug)]
-pub struct TelegramDestinationDb {
+pub struct DestinationDb {
     pub id: ActionDestinationId,
-    pub name: String,
     pub user_id: UserId,
-    pub chat_id: i64,
-    pub kind: u8,
+    pub name: String,
+    // see destination::lookup
+    pub kind: DestinationKind,
+    // json-encoded, the shape depends on the kind
+    pub config: String,
     pub created_at: String,
+    pub active: DbBool,
+    pub consented: DbBool,
 }
 
-impl From<TelegramDestinationDb> for ActionDestination {
-    fn from(u: TelegramDestinationDb) -> Self {
-        let kind = match u.kind {
-            1 => ActionDestinationKind::TelegramDm { chat_id: u.chat_id },
-            2 => ActionDestinationKind::TelegramGroup { chat_id: u.chat_id },
-            _ => unreachable!(),
-        };
+from_d1_row!(DestinationDb);
 
-        ActionDestination {
+impl TryFrom<DestinationDb> for ActionDestination {
+    type Error = ApiError;
+
+    fn try_from(u: DestinationDb) -> ApiResult<Self> {
+        let kind = u.destination().api_kind(&u.config()?)?;
+
+        Ok(ActionDestination {
             id: u.id,
             kind,
             name: u.name,
-        }
+            active: u.active.into(),
+            consented: u.consented.into(),
+        })
     }
 }
 
-impl TelegramDestinationDb {
+impl DestinationDb {
+    pub fn destination(&self) -> &'static dyn Destination {
+        lookup(self.kind)
+    }
+
+    pub fn config(&self) -> ApiResult<serde_json::Value> {
+        serde_json::from_str(&self.config).map_err(|err| ApiError::Db(err.to_string()))
+    }
+
     pub async fn load(env: &Env, id: &ActionDestinationId) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE id = ?1",
-                DB_TABLE.telegram_destination
-            ))
-            .bind(&[id.into()])?
-            .first::<Self>(None)
-            .await?
-            .map(Self::from)
-            .ok_or(format!("no such destination with id {id}").into())
+        Query::new(
+            env,
+            "destination.load",
+            select_where(DB_TABLE.destination, "id = ?1"),
+            &[id.into()],
+        )?
+        .first()
+        .await?
+        .ok_or(format!("no such destination with id {id}").into())
     }
 
     pub async fn load_with_user_id(
@@ -50,35 +75,58 @@ impl TelegramDestinationDb {
         id: &ActionDestinationId,
         user_id: &UserId,
     ) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE id = ?1 AND user_id = ?2",
-                DB_TABLE.telegram_destination
-            ))
-            .bind(&[id.into(), user_id.into()])?
-            .first::<Self>(None)
-            .await?
-            .map(Self::from)
-            .ok_or(format!("no such destination with id {id} and user_id {user_id}").into())
+        Query::new(
+            env,
+            "destination.load_with_user_id",
+            select_where(DB_TABLE.destination, "id = ?1 AND user_id = ?2"),
+            &[id.into(), user_id.into()],
+        )?
+        .first()
+        .await?
+        .ok_or(format!("no such destination with id {id} and user_id {user_id}").into())
     }
 
-    pub async fn exists_by_user_chat_id(
+    // each forum topic is its own destination, None is the main thread
+    pub async fn exists_by_user_telegram_chat_id(
         env: &Env,
         user_id: &UserId,
         chat_id: i64,
+        message_thread_id: Option<i64>,
     ) -> ApiResult<bool> {
-        let res = get_d1(env)?
-            .prepare(format!(
-                "SELECT EXISTS(SELECT 1 FROM {} WHERE user_id = ?1 AND chat_id = ?2)",
-                DB_TABLE.telegram_destination
-            ))
-            .bind(&[user_id.into(), JsValue::from_f64(chat_id as f64)])?
-            .raw::<u32>()
-            .await?;
-
-        let exists = res[0][0] == 1;
+        Ok(
+            Self::find_by_user_telegram_chat_id(env, user_id, chat_id, message_thread_id)
+                .await?
+                .is_some(),
+        )
+    }
 
-        Ok(exists)
+    pub async fn find_by_user_telegram_chat_id(
+        env: &Env,
+        user_id: &UserId,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+    ) -> ApiResult<Option<Self>> {
+        Query::new(
+            env,
+            "destination.find_by_user_telegram_chat_id",
+            select_where(
+                DB_TABLE.destination,
+                "user_id = ?1 AND kind IN (?2, ?3, ?4) AND json_extract(config, '$.chat_id') = ?5 AND json_extract(config, '$.message_thread_id') IS ?6",
+            ),
+            &[
+                user_id.into(),
+                DestinationKind::TelegramDm.as_str().into(),
+                DestinationKind::TelegramGroup.as_str().into(),
+                DestinationKind::TelegramChannel.as_str().into(),
+                JsValue::from_f64(chat_id as f64),
+                match message_thread_id {
+                    Some(message_thread_id) => JsValue::from_f64(message_thread_id as f64),
+                    None => JsValue::NULL,
+                },
+            ],
+        )?
+        .first()
+        .await
     }
 
     pub async fn insert(
@@ -86,42 +134,243 @@ impl TelegramDestinationDb {
         id: &ActionDestinationId,
         user_id: &UserId,
         name: &str,
-        destination: ActionDestinationKind,
+        destination: &dyn Destination,
+        config: &serde_json::Value,
     ) -> ApiResult<()> {
-        let (kind, chat_id) = match destination {
-            ActionDestinationKind::TelegramDm { chat_id } => (1, chat_id),
-            ActionDestinationKind::TelegramGroup { chat_id } => (2, chat_id),
-        };
-
-        get_d1(env)?
-            .prepare(format!(
-                "INSERT INTO {} (id, user_id, chat_id, name, kind) VALUES (?1, ?2, ?3, ?4, ?5)",
-                DB_TABLE.telegram_destination
-            ))
-            .bind(&[
+        Self::insert_query(env, id, user_id, name, destination, config)?
+            .run()
+            .await
+    }
+
+    // for writes that go in a batch, see run_batch
+    pub fn insert_statement(
+        env: &Env,
+        id: &ActionDestinationId,
+        user_id: &UserId,
+        name: &str,
+        destination: &dyn Destination,
+        config: &serde_json::Value,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Self::insert_query(env, id, user_id, name, destination, config)?.into_statement())
+    }
+
+    fn insert_query(
+        env: &Env,
+        id: &ActionDestinationId,
+        user_id: &UserId,
+        name: &str,
+        destination: &dyn Destination,
+        config: &serde_json::Value,
+    ) -> ApiResult<Query> {
+        destination.validate(config)?;
+
+        Query::new(
+            env,
+            "destination.insert",
+            insert_into(
+                DB_TABLE.destination,
+                &["id", "user_id", "name", "kind", "config"],
+            ),
+            &[
                 id.into(),
                 user_id.into(),
-                JsValue::from_f64(chat_id as f64),
                 name.into(),
-                kind.into(),
-            ])?
-            .run()
-            .await?
-            .into_result()
+                destination.kind().as_str().into(),
+                config.to_string().into(),
+            ],
+        )
+    }
+
+    // every destination for the chat, across users and topics
+    pub async fn set_active_by_telegram_chat_id(
+        env: &Env,
+        chat_id: i64,
+        active: bool,
+    ) -> ApiResult<()> {
+        Query::new(
+            env,
+            "destination.set_active_by_telegram_chat_id",
+            update_where(
+                DB_TABLE.destination,
+                "active = ?1",
+                "kind IN (?2, ?3, ?4) AND json_extract(config, '$.chat_id') = ?5",
+            ),
+            &[
+                DbBool::from(active).into(),
+                DestinationKind::TelegramDm.as_str().into(),
+                DestinationKind::TelegramGroup.as_str().into(),
+                DestinationKind::TelegramChannel.as_str().into(),
+                JsValue::from_f64(chat_id as f64),
+            ],
+        )?
+        .run()
+        .await
+    }
+
+    // a group that was upgraded to a supergroup gets a new chat id
+    // numbers are bound as REAL, which json_set would keep (e.g. -100123.0) and then fail to load as an i64
+    pub async fn migrate_telegram_chat_id(
+        env: &Env,
+        chat_id: i64,
+        migrate_to_chat_id: i64,
+    ) -> ApiResult<()> {
+        Query::new(
+            env,
+            "destination.migrate_telegram_chat_id",
+            update_where(
+                DB_TABLE.destination,
+                "config = json_set(config, '$.chat_id', CAST(?1 AS INTEGER))",
+                "kind IN (?2, ?3, ?4) AND json_extract(config, '$.chat_id') = ?5",
+            ),
+            &[
+                JsValue::from_f64(migrate_to_chat_id as f64),
+                DestinationKind::TelegramDm.as_str().into(),
+                DestinationKind::TelegramGroup.as_str().into(),
+                DestinationKind::TelegramChannel.as_str().into(),
+                JsValue::from_f64(chat_id as f64),
+            ],
+        )?
+        .run()
+        .await
+    }
+
+    pub async fn set_active(env: &Env, id: &ActionDestinationId, active: bool) -> ApiResult<()> {
+        Self::set_active_query(env, id, active)?.run().await
+    }
+
+    pub fn set_active_statement(
+        env: &Env,
+        id: &ActionDestinationId,
+        active: bool,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Self::set_active_query(env, id, active)?.into_statement())
+    }
+
+    fn set_active_query(env: &Env, id: &ActionDestinationId, active: bool) -> ApiResult<Query> {
+        Query::new(
+            env,
+            "destination.set_active",
+            update_where(DB_TABLE.destination, "active = ?1", "id = ?2"),
+            &[DbBool::from(active).into(), id.into()],
+        )
+    }
+
+    // an invited contact accepting (again), see InvitationDb::revoke_by_chat_id for the opposite
+    pub fn set_consented_statement(
+        env: &Env,
+        id: &ActionDestinationId,
+        consented: bool,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Query::new(
+            env,
+            "destination.set_consented",
+            update_where(DB_TABLE.destination, "consented = ?1", "id = ?2"),
+            &[DbBool::from(consented).into(), id.into()],
+        )?
+        .into_statement())
+    }
+
+    pub async fn rename(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionDestinationId,
+        name: &str,
+    ) -> ApiResult<()> {
+        Query::new(
+            env,
+            "destination.rename",
+            update_where(
+                DB_TABLE.destination,
+                "name = ?1",
+                "id = ?2 AND user_id = ?3",
+            ),
+            &[name.into(), id.into(), user_id.into()],
+        )?
+        .run()
+        .await
+    }
+
+    // without cascade, fails with ApiError::DestinationInUse if any actions use it
+    // returns the number of actions that were deleted along with it
+    pub async fn delete_cascade(
+        env: &Env,
+        user_id: &UserId,
+        id: &ActionDestinationId,
+        cascade: bool,
+    ) -> ApiResult<u32> {
+        // make sure it's ours before counting anything
+        Self::load_with_user_id(env, id, user_id).await?;
+
+        let count = TelegramActionDb::count_by_destination(env, id).await?;
+
+        if count > 0 && !cascade {
+            return Err(ApiError::DestinationInUse(count));
+        }
+
+        // the actions go along with it, ON DELETE CASCADE
+        Self::delete(env, user_id, id).await?;
+
+        Ok(count)
     }
 
+    // the actions are deleted too, see delete_cascade to check for them first
+    pub async fn delete(env: &Env, user_id: &UserId, id: &ActionDestinationId) -> ApiResult<()> {
+        Query::new(
+            env,
+            "destination.delete",
+            delete_where(DB_TABLE.destination, "id = ?1 AND user_id = ?2"),
+            &[id.into(), user_id.into()],
+        )?
+        .run()
+        .await
+    }
+
+    // for the dashboard, see db::page
+    pub async fn list_page(
+        env: &Env,
+        user_id: &UserId,
+        page: &Page,
+    ) -> ApiResult<(Vec<ActionDestination>, Option<String>)> {
+        let (condition, params) =
+            page.where_clause("id", Conditions::default().eq("user_id", user_id.into()));
+
+        let rows = Query::new(
+            env,
+            "destination.list_page",
+            select_where(DB_TABLE.destination, &condition),
+            &params,
+        )?
+        .all::<Self>()
+        .await?;
+
+        let (rows, next_cursor) = page.finish(rows, |row| row.id.to_string());
+
+        Ok((
+            rows.into_iter()
+                .map(ActionDestination::try_from)
+                .collect::<ApiResult<_>>()?,
+            next_cursor,
+        ))
+    }
+
+    // everything, for the bot
     pub async fn list(env: &Env, user_id: &UserId) -> ApiResult<Vec<ActionDestination>> {
-        Ok(get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE user_id = ?1",
-                DB_TABLE.telegram_destination
-            ))
-            .bind(&[user_id.into()])?
-            .all()
+        Self::list_rows(env, user_id)
             .await?
-            .results::<Self>()?
             .into_iter()
-            .map(ActionDestination::from)
-            .collect())
+            .map(ActionDestination::try_from)
+            .collect()
+    }
+
+    // like list, but keeping the kind and config
+    pub async fn list_rows(env: &Env, user_id: &UserId) -> ApiResult<Vec<Self>> {
+        Query::new(
+            env,
+            "destination.list_rows",
+            select_where(DB_TABLE.destination, "user_id = ?1 ORDER BY created_at, id"),
+            &[user_id.into()],
+        )?
+        .all()
+        .await
     }
 }
@@ -0,0 +1,269 @@
+use crate::{
+    config::DB_TABLE,
+    db::{
+        destination::DestinationDb,
+        query::{
+            from_d1_row, insert_into, insert_into_if_exists, select_where, update_where, Query,
+        },
+    },
+    destination::Destination,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use shared::{
+    api::action::{ActionDestinationId, Invitation, InvitationId, InvitationStatus},
+    user::UserId,
+};
+use worker::D1PreparedStatement;
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct InvitationDb {
+    pub id: InvitationId,
+    // the inviter
+    pub user_id: UserId,
+    pub reason: String,
+    pub status: InvitationStatus,
+    pub tg_uid: Option<i64>,
+    pub chat_id: Option<i64>,
+    pub contact_name: Option<String>,
+    pub destination_id: Option<ActionDestinationId>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+from_d1_row!(InvitationDb);
+
+impl From<InvitationDb> for Invitation {
+    fn from(i: InvitationDb) -> Self {
+        Invitation {
+            id: i.id,
+            reason: i.reason,
+            status: i.status,
+            contact_name: i.contact_name,
+            destination_id: i.destination_id,
+            created_at: i.created_at,
+        }
+    }
+}
+
+impl InvitationDb {
+    pub async fn insert(
+        env: &Env,
+        id: &InvitationId,
+        user_id: &UserId,
+        reason: &str,
+    ) -> ApiResult<()> {
+        Query::new(
+            env,
+            "invitation.insert",
+            insert_into(DB_TABLE.invitation, &["id", "user_id", "reason", "status"]),
+            &[
+                id.into(),
+                user_id.into(),
+                reason.into(),
+                InvitationStatus::Pending.as_str().into(),
+            ],
+        )?
+        .run()
+        .await
+    }
+
+    pub async fn load(env: &Env, id: &InvitationId) -> ApiResult<Option<Self>> {
+        Query::new(
+            env,
+            "invitation.load",
+            select_where(DB_TABLE.invitation, "id = ?1"),
+            &[id.into()],
+        )?
+        .first()
+        .await
+    }
+
+    // the contact opened the link, but hasn't decided yet
+    // only that contact can accept or decline from here on
+    pub async fn set_contact(
+        env: &Env,
+        id: &InvitationId,
+        tg_uid: i64,
+        chat_id: i64,
+        contact_name: &str,
+    ) -> ApiResult<()> {
+        Query::new(
+            env,
+            "invitation.set_contact",
+            update_where(
+                DB_TABLE.invitation,
+                "tg_uid = ?1, chat_id = ?2, contact_name = ?3, updated_at = CURRENT_TIMESTAMP",
+                "id = ?4 AND status = ?5",
+            ),
+            &[
+                JsValue::from_f64(tg_uid as f64),
+                JsValue::from_f64(chat_id as f64),
+                contact_name.into(),
+                id.into(),
+                InvitationStatus::Pending.as_str().into(),
+            ],
+        )?
+        .run()
+        .await
+    }
+
+    // run along with creating or re-enabling the destination
+    // only applies while the invitation is still pending, so a second accept changes nothing
+    pub fn accept_statement(
+        env: &Env,
+        id: &InvitationId,
+        destination_id: &ActionDestinationId,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Query::new(
+            env,
+            "invitation.accept",
+            update_where(
+                DB_TABLE.invitation,
+                "status = ?1, destination_id = ?2, updated_at = CURRENT_TIMESTAMP",
+                "id = ?3 AND status = ?4",
+            ),
+            &[
+                InvitationStatus::Accepted.as_str().into(),
+                destination_id.into(),
+                id.into(),
+                InvitationStatus::Pending.as_str().into(),
+            ],
+        )?
+        .into_statement())
+    }
+
+    // false if it was no longer pending, e.g. already answered
+    pub async fn decline(env: &Env, id: &InvitationId) -> ApiResult<bool> {
+        Query::new(
+            env,
+            "invitation.decline",
+            update_where(
+                DB_TABLE.invitation,
+                "status = ?1, updated_at = CURRENT_TIMESTAMP",
+                "id = ?2 AND status = ?3",
+            ),
+            &[
+                InvitationStatus::Declined.as_str().into(),
+                id.into(),
+                InvitationStatus::Pending.as_str().into(),
+            ],
+        )?
+        .changes()
+        .await
+        .map(|changes| changes > 0)
+    }
+
+    // the contact's new destination, only created while the invitation is still pending
+    // goes in the same batch as accept_statement, which comes after it
+    pub fn insert_destination_statement(
+        env: &Env,
+        id: &InvitationId,
+        destination_id: &ActionDestinationId,
+        user_id: &UserId,
+        name: &str,
+        destination: &dyn Destination,
+        config: &serde_json::Value,
+    ) -> ApiResult<D1PreparedStatement> {
+        destination.validate(config)?;
+
+        Ok(Query::new(
+            env,
+            "invitation.insert_destination",
+            insert_into_if_exists(
+                DB_TABLE.destination,
+                &["id", "user_id", "name", "kind", "config"],
+                DB_TABLE.invitation,
+                "id = ?6 AND status = ?7",
+            ),
+            &[
+                destination_id.into(),
+                user_id.into(),
+                name.into(),
+                destination.kind().as_str().into(),
+                config.to_string().into(),
+                id.into(),
+                InvitationStatus::Pending.as_str().into(),
+            ],
+        )?
+        .into_statement())
+    }
+
+    // /stop from the contact, every inviter at once
+    // the destinations are kept (so the inviter sees what happened), but no longer delivered to
+    // returns how many invitations were revoked
+    pub async fn revoke_by_chat_id(env: &Env, chat_id: i64) -> ApiResult<usize> {
+        let accepted = Query::new(
+            env,
+            "invitation.list_accepted_by_chat_id",
+            select_where(DB_TABLE.invitation, "chat_id = ?1 AND status = ?2"),
+            &[
+                JsValue::from_f64(chat_id as f64),
+                InvitationStatus::Accepted.as_str().into(),
+            ],
+        )?
+        .all::<Self>()
+        .await?;
+
+        // the destinations and the invitations change together
+        let mut statements = accepted
+            .iter()
+            .filter_map(|invitation| invitation.destination_id.as_ref())
+            .map(|destination_id| {
+                DestinationDb::set_consented_statement(env, destination_id, false)
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+
+        statements.push(
+            Query::new(
+                env,
+                "invitation.revoke_by_chat_id",
+                update_where(
+                    DB_TABLE.invitation,
+                    "status = ?1, updated_at = CURRENT_TIMESTAMP",
+                    "chat_id = ?2 AND status = ?3",
+                ),
+                &[
+                    InvitationStatus::Revoked.as_str().into(),
+                    JsValue::from_f64(chat_id as f64),
+                    InvitationStatus::Accepted.as_str().into(),
+                ],
+            )?
+            .into_statement(),
+        );
+
+        run_batch(env, statements).await?;
+
+        Ok(accepted.len())
+    }
+
+    pub async fn list(env: &Env, user_id: &UserId) -> ApiResult<Vec<Invitation>> {
+        Ok(Query::new(
+            env,
+            "invitation.list",
+            select_where(
+                DB_TABLE.invitation,
+                "user_id = ?1 ORDER BY created_at DESC, id",
+            ),
+            &[user_id.into()],
+        )?
+        .all::<Self>()
+        .await?
+        .into_iter()
+        .map(Invitation::from)
+        .collect())
+    }
+}
+
+// CREATE TABLE invitation (
+//     id TEXT PRIMARY KEY,
+//     user_id TEXT NOT NULL REFERENCES user_account(id) ON DELETE CASCADE,
+//     reason TEXT NOT NULL,
+//     status TEXT NOT NULL DEFAULT 'pending',
+//     tg_uid INTEGER,
+//     chat_id INTEGER,
+//     contact_name TEXT,
+//     destination_id TEXT REFERENCES destination(id) ON DELETE SET NULL,
+//     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+//     updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+// ) WITHOUT ROWID;
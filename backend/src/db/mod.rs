@@ -1,3 +1,7 @@
 pub mod action;
+pub mod delivery;
 pub mod destination;
+pub mod invitation;
+pub mod page;
+pub mod query;
 pub mod user;
@@ -0,0 +1,186 @@
+// Keyset pagination over UUIDv7 ids, which are stored as plain hex and so sort by creation time
+// the cursor is opaque to clients: the sort and the last id of the previous page, base64'd together
+use base64::Engine;
+use shared::api::action::ListSort;
+
+use crate::{db::query::Conditions, prelude::*};
+
+pub struct Page {
+    pub limit: u32,
+    pub sort: ListSort,
+    after: Option<String>,
+}
+
+impl Page {
+    pub fn new(cursor: Option<&str>, limit: u32, sort: ListSort) -> ApiResult<Self> {
+        let after = match cursor {
+            None => None,
+            Some(cursor) => Some(decode_cursor(cursor, sort)?),
+        };
+
+        Ok(Self {
+            limit: limit.max(1),
+            sort,
+            after,
+        })
+    }
+
+    // the keyset condition (if past the first page) is added to the others
+    // then the order and limit, so this is the rest of the WHERE clause
+    pub fn where_clause(&self, column: &str, mut conditions: Conditions) -> (String, Vec<JsValue>) {
+        if let Some(after) = self.after.as_ref() {
+            let op = match self.sort {
+                ListSort::Oldest => ">",
+                ListSort::Newest => "<",
+            };
+            conditions.push(column, op, after.into());
+        }
+
+        let (condition, params) = conditions.finish();
+
+        (
+            format!("{condition} {}", self.order_and_limit(column)),
+            params,
+        )
+    }
+
+    // one extra row is fetched, to know if there's another page
+    fn order_and_limit(&self, column: &str) -> String {
+        format!(
+            "ORDER BY {column} {} LIMIT {}",
+            match self.sort {
+                ListSort::Oldest => "ASC",
+                ListSort::Newest => "DESC",
+            },
+            self.limit + 1
+        )
+    }
+
+    // drops the extra row and makes the cursor for the next page
+    pub fn finish<T>(
+        &self,
+        mut rows: Vec<T>,
+        id: impl Fn(&T) -> String,
+    ) -> (Vec<T>, Option<String>) {
+        let next_cursor = if rows.len() > self.limit as usize {
+            rows.truncate(self.limit as usize);
+            rows.last().map(|row| encode_cursor(&id(row), self.sort))
+        } else {
+            None
+        };
+
+        (rows, next_cursor)
+    }
+}
+
+fn encode_cursor(id: &str, sort: ListSort) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("{}:{id}", sort.as_str()))
+}
+
+fn decode_cursor(cursor: &str, sort: ListSort) -> ApiResult<String> {
+    let invalid = || ApiError::Validation("invalid cursor".to_string());
+
+    let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+
+    match decoded.split_once(':') {
+        Some((cursor_sort, id))
+            if cursor_sort == sort.as_str() && uuid::Uuid::parse_str(id).is_ok() =>
+        {
+            Ok(id.to_string())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SORTS: [ListSort; 2] = [ListSort::Oldest, ListSort::Newest];
+
+    fn id() -> String {
+        uuid::Uuid::now_v7().to_string()
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        for sort in SORTS {
+            let id = id();
+            assert_eq!(decode_cursor(&encode_cursor(&id, sort), sort).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn cursor_is_tied_to_its_sort() {
+        let id = id();
+        let cursor = encode_cursor(&id, ListSort::Oldest);
+        assert!(matches!(
+            decode_cursor(&cursor, ListSort::Newest),
+            Err(ApiError::Validation(_))
+        ));
+
+        let cursor = encode_cursor(&id, ListSort::Newest);
+        assert!(decode_cursor(&cursor, ListSort::Oldest).is_err());
+    }
+
+    #[test]
+    fn cursor_rejects_bad_input() {
+        // not base64
+        assert!(decode_cursor("not a cursor!", ListSort::Oldest).is_err());
+        // padded, which the encoder never produces
+        assert!(decode_cursor("b2xkZXN0Og==", ListSort::Oldest).is_err());
+        // not utf8
+        let cursor = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode([0xff, 0xfe]);
+        assert!(decode_cursor(&cursor, ListSort::Oldest).is_err());
+        // no id, or not a uuid
+        assert!(decode_cursor(&encode_cursor("", ListSort::Oldest), ListSort::Oldest).is_err());
+        assert!(decode_cursor(
+            &encode_cursor("not-a-uuid", ListSort::Oldest),
+            ListSort::Oldest
+        )
+        .is_err());
+        // no separator
+        let cursor = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(id());
+        assert!(decode_cursor(&cursor, ListSort::Oldest).is_err());
+    }
+
+    #[test]
+    fn page_from_cursor() {
+        let id = id();
+        let cursor = encode_cursor(&id, ListSort::Newest);
+
+        let page = Page::new(Some(&cursor), 0, ListSort::Newest).unwrap();
+        assert_eq!(page.after.as_deref(), Some(id.as_str()));
+        assert_eq!(page.limit, 1);
+        assert!(Page::new(Some(&cursor), 10, ListSort::Oldest).is_err());
+    }
+
+    #[test]
+    fn order_and_limit_fetches_one_more() {
+        let page = Page::new(None, 20, ListSort::Newest).unwrap();
+        assert_eq!(page.order_and_limit("d.id"), "ORDER BY d.id DESC LIMIT 21");
+
+        let page = Page::new(None, 5, ListSort::Oldest).unwrap();
+        assert_eq!(page.order_and_limit("id"), "ORDER BY id ASC LIMIT 6");
+    }
+
+    #[test]
+    fn finish_drops_the_extra_row() {
+        let page = Page::new(None, 2, ListSort::Oldest).unwrap();
+        let ids = [id(), id(), id()];
+
+        let (rows, next_cursor) = page.finish(ids.to_vec(), |id| id.clone());
+        assert_eq!(rows, ids[..2]);
+        assert_eq!(
+            decode_cursor(&next_cursor.unwrap(), ListSort::Oldest).unwrap(),
+            ids[1]
+        );
+
+        let (rows, next_cursor) = page.finish(ids[..2].to_vec(), |id| id.clone());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(next_cursor, None);
+    }
+}
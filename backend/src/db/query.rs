@@ -0,0 +1,301 @@
+// Typed access to D1
+//
+// Every statement is a named Query built from the helpers below, so an error says which one failed, and rows are read
+// through FromD1Row rather than straight into structs
+// anything that doesn't fit (an unknown enum value, a missing column) is an ApiError::Db, never a panic
+//
+// Enum-backed columns (parse_mode, status, etc.) are stored as their as_str() value
+// and read back through serde, so an unknown value fails the row instead of defaulting
+use serde_json::Value;
+use worker::{D1PreparedStatement, D1Result};
+
+use crate::prelude::*;
+
+pub trait FromD1Row: Sized {
+    // for errors
+    const NAME: &'static str;
+
+    fn from_row(row: Value) -> ApiResult<Self>;
+}
+
+// for row structs whose fields all deserialize from their columns
+macro_rules! from_d1_row {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl $crate::db::query::FromD1Row for $ty {
+                const NAME: &'static str = stringify!($ty);
+
+                fn from_row(row: ::serde_json::Value) -> ApiResult<Self> {
+                    ::serde_json::from_value(row)
+                        .map_err(|err| ApiError::Db(format!("{}: {err}", Self::NAME)))
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use from_d1_row;
+
+pub struct Query {
+    name: &'static str,
+    statement: D1PreparedStatement,
+}
+
+impl Query {
+    // the name is "table.what", e.g. "destination.list"
+    pub fn new(
+        env: &Env,
+        name: &'static str,
+        sql: impl Into<String>,
+        params: &[JsValue],
+    ) -> ApiResult<Self> {
+        let statement = get_d1(env)?
+            .prepare(sql)
+            .bind(params)
+            .map_err(|err| db_error(name, err))?;
+
+        Ok(Self { name, statement })
+    }
+
+    pub async fn first<T: FromD1Row>(self) -> ApiResult<Option<T>> {
+        self.statement
+            .first::<Value>(None)
+            .await
+            .map_err(|err| db_error(self.name, err))?
+            .map(T::from_row)
+            .transpose()
+    }
+
+    pub async fn all<T: FromD1Row>(self) -> ApiResult<Vec<T>> {
+        self.result()
+            .await?
+            .results::<Value>()
+            .map_err(|err| db_error(self.name, err))?
+            .into_iter()
+            .map(T::from_row)
+            .collect()
+    }
+
+    pub async fn run(self) -> ApiResult<()> {
+        self.result().await.map(|_| ())
+    }
+
+    // how many rows were changed
+    pub async fn changes(self) -> ApiResult<usize> {
+        let name = self.name;
+
+        Ok(self
+            .result()
+            .await?
+            .meta()
+            .map_err(|err| db_error(name, err))?
+            .and_then(|meta| meta.changes)
+            .unwrap_or_default())
+    }
+
+    // for SELECT COUNT(*) and SELECT EXISTS(...)
+    pub async fn count(self) -> ApiResult<u32> {
+        self.statement
+            .raw::<u32>()
+            .await
+            .map_err(|err| db_error(self.name, err))?
+            .first()
+            .and_then(|row| row.first())
+            .copied()
+            .ok_or_else(|| ApiError::Db(format!("{}: no rows", self.name)))
+    }
+
+    pub async fn exists(self) -> ApiResult<bool> {
+        Ok(self.count().await? == 1)
+    }
+
+    // for writes that go in a batch, see run_batch
+    pub fn into_statement(self) -> D1PreparedStatement {
+        self.statement
+    }
+
+    async fn result(&self) -> ApiResult<D1Result> {
+        let result = self
+            .statement
+            .run()
+            .await
+            .map_err(|err| db_error(self.name, err))?;
+
+        match result.error() {
+            Some(err) => Err(with_name(self.name, ApiError::from_db_message(&err))),
+            None => Ok(result),
+        }
+    }
+}
+
+// the statement shapes, so no query is built from a format! of its own
+// the table and column names are always constants, anything from a user is a bound param
+
+pub fn select_where(table: &str, condition: &str) -> String {
+    format!("SELECT * FROM {table} WHERE {condition}")
+}
+
+pub fn exists_where(table: &str, condition: &str) -> String {
+    format!("SELECT EXISTS(SELECT 1 FROM {table} WHERE {condition})")
+}
+
+pub fn count_where(table: &str, condition: &str) -> String {
+    format!("SELECT COUNT(*) FROM {table} WHERE {condition}")
+}
+
+// the columns of the main table can be "alias.*", the joined ones need their own names
+pub fn select_join_where(
+    columns: &str,
+    table: &str,
+    alias: &str,
+    joins: &[Join],
+    condition: &str,
+) -> String {
+    let joins = joins
+        .iter()
+        .map(|join| {
+            let (kind, table, alias, on) = match join {
+                Join::Inner(table, alias, on) => ("JOIN", table, alias, on),
+                Join::Left(table, alias, on) => ("LEFT JOIN", table, alias, on),
+            };
+            format!(" {kind} {table} AS {alias} ON {on}")
+        })
+        .collect::<String>();
+
+    format!("SELECT {columns} FROM {table} AS {alias}{joins} WHERE {condition}")
+}
+
+// the joined table, its alias, and the ON condition
+pub enum Join<'a> {
+    Inner(&'a str, &'a str, &'a str),
+    Left(&'a str, &'a str, &'a str),
+}
+
+// the values are bound in the same order as the columns
+pub fn insert_into(table: &str, columns: &[&str]) -> String {
+    format!(
+        "INSERT INTO {table} ({}) VALUES ({})",
+        columns.join(", "),
+        placeholders(columns.len())
+    )
+}
+
+// like insert_into, but only if a row in the other table matches
+// the condition's params are numbered after the values
+pub fn insert_into_if_exists(
+    table: &str,
+    columns: &[&str],
+    exists_table: &str,
+    condition: &str,
+) -> String {
+    format!(
+        "INSERT INTO {table} ({}) SELECT {} WHERE EXISTS (SELECT 1 FROM {exists_table} WHERE {condition})",
+        columns.join(", "),
+        placeholders(columns.len())
+    )
+}
+
+pub fn update_where(table: &str, set: &str, condition: &str) -> String {
+    format!("UPDATE {table} SET {set} WHERE {condition}")
+}
+
+pub fn delete_where(table: &str, condition: &str) -> String {
+    format!("DELETE FROM {table} WHERE {condition}")
+}
+
+pub fn and(conditions: &[&str]) -> String {
+    conditions.join(" AND ")
+}
+
+// e.g. scoping actions to a user through their destination
+pub fn id_in(column: &str, table: &str, condition: &str) -> String {
+    format!("{column} IN (SELECT id FROM {table} WHERE {condition})")
+}
+
+fn placeholders(count: usize) -> String {
+    (1..=count)
+        .map(|n| format!("?{n}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// a condition built up from optional parts, e.g. list filters
+// the params are numbered in the order they're pushed
+#[derive(Default)]
+pub struct Conditions {
+    conditions: Vec<String>,
+    params: Vec<JsValue>,
+}
+
+impl Conditions {
+    // "column op ?n", op is e.g. "=" or "<"
+    pub fn push(&mut self, column: &str, op: &str, param: JsValue) {
+        self.params.push(param);
+        self.conditions
+            .push(format!("{column} {op} ?{}", self.params.len()));
+    }
+
+    pub fn eq(mut self, column: &str, param: JsValue) -> Self {
+        self.push(column, "=", param);
+        self
+    }
+
+    // the condition and its params, to go in a Query
+    pub fn finish(self) -> (String, Vec<JsValue>) {
+        let conditions = self
+            .conditions
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        (and(&conditions), self.params)
+    }
+}
+
+// constraint violations keep their own variants, everything else is a Db error naming the query
+fn db_error(name: &str, err: worker::Error) -> ApiError {
+    with_name(name, err.into())
+}
+
+fn with_name(name: &str, err: ApiError) -> ApiError {
+    match err {
+        ApiError::Db(message) | ApiError::Unknown(message) => {
+            ApiError::Db(format!("{name}: {message}"))
+        }
+        err => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_placeholders_follow_the_columns() {
+        assert_eq!(
+            insert_into("t", &["a", "b"]),
+            "INSERT INTO t (a, b) VALUES (?1, ?2)"
+        );
+        assert_eq!(
+            insert_into_if_exists("t", &["a", "b"], "u", "id = ?3"),
+            "INSERT INTO t (a, b) SELECT ?1, ?2 WHERE EXISTS (SELECT 1 FROM u WHERE id = ?3)"
+        );
+    }
+
+    #[test]
+    fn joins_keep_their_order() {
+        assert_eq!(
+            select_join_where(
+                "t.*, u.name",
+                "t",
+                "t",
+                &[
+                    Join::Inner("u", "u", "u.id = t.u_id"),
+                    Join::Left("v", "v", "v.id = t.v_id"),
+                ],
+                "t.id = ?1",
+            ),
+            "SELECT t.*, u.name FROM t AS t JOIN u AS u ON u.id = t.u_id LEFT JOIN v AS v ON v.id = t.v_id WHERE t.id = ?1"
+        );
+    }
+}
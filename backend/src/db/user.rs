@@ -1,65 +1,119 @@
-use crate::{config::DB_TABLE, prelude::*};
+use crate::{
+    config::{DB_TABLE, PAUSE_MAX_SECS},
+    db::query::{exists_where, from_d1_row, insert_into, select_where, update_where, Query},
+    prelude::*,
+};
 use serde::{Deserialize, Serialize};
 use shared::user::UserId;
+use worker::D1PreparedStatement;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct UserAccount {
     pub id: UserId,
     pub user_token: String,
     pub created_at: String,
+    // every action is skipped until then, may be in the past
+    pub paused_until: Option<String>,
 }
 
+from_d1_row!(UserAccount, OmiAccount, TelegramAccount);
+
 impl UserAccount {
     pub async fn load(env: &Env, id: &UserId) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE id = ?1",
-                DB_TABLE.user_account
-            ))
-            .bind(&[id.into()])?
-            .first::<UserAccount>(None)
-            .await?
-            .map(UserAccount::from)
-            .ok_or(format!("Need to register (id {id})").into())
+        Query::new(
+            env,
+            "user_account.load",
+            select_where(DB_TABLE.user_account, "id = ?1"),
+            &[id.into()],
+        )?
+        .first()
+        .await?
+        .ok_or(format!("Need to register (id {id})").into())
     }
 
     pub async fn _exists(env: &Env, id: &UserId) -> ApiResult<bool> {
-        let res = get_d1(env)?
-            .prepare(format!(
-                "SELECT EXISTS(SELECT 1 FROM {} WHERE omi_id = ?1)",
-                DB_TABLE.user_account
-            ))
-            .bind(&[id.into()])?
-            .raw::<u32>()
-            .await?;
-
-        let exists = res[0][0] == 1;
-
-        Ok(exists)
+        Query::new(
+            env,
+            "user_account.exists",
+            exists_where(DB_TABLE.user_account, "id = ?1"),
+            &[id.into()],
+        )?
+        .exists()
+        .await
+    }
+
+    // run in a batch along with the linked accounts, see create_accounts
+    pub fn insert_statement(
+        env: &Env,
+        id: &UserId,
+        user_token: &str,
+    ) -> ApiResult<D1PreparedStatement> {
+        Ok(Query::new(
+            env,
+            "user_account.insert",
+            insert_into(DB_TABLE.user_account, &["id", "user_token"]),
+            &[id.into(), user_token.into()],
+        )?
+        .into_statement())
+    }
+
+    // only if it's still in the future
+    pub async fn paused_until(env: &Env, id: &UserId) -> ApiResult<Option<String>> {
+        Ok(Query::new(
+            env,
+            "user_account.paused_until",
+            select_where(
+                DB_TABLE.user_account,
+                "id = ?1 AND paused_until > CURRENT_TIMESTAMP",
+            ),
+            &[id.into()],
+        )?
+        .first::<UserAccount>()
+        .await?
+        .and_then(|account| account.paused_until))
+    }
+
+    // capped at PAUSE_MAX_SECS, returns when it ends
+    pub async fn pause(env: &Env, id: &UserId, duration_secs: u64) -> ApiResult<Option<String>> {
+        Query::new(
+            env,
+            "user_account.pause",
+            update_where(
+                DB_TABLE.user_account,
+                "paused_until = datetime('now', ?1)",
+                "id = ?2",
+            ),
+            &[
+                format!("+{} seconds", duration_secs.min(PAUSE_MAX_SECS)).into(),
+                id.into(),
+            ],
+        )?
+        .run()
+        .await?;
+
+        Self::paused_until(env, id).await
     }
 
-    pub async fn insert(env: &Env, id: &UserId, user_token: &str) -> ApiResult<()> {
-        get_d1(env)?
-            .prepare(format!(
-                "INSERT INTO {} (id, user_token) VALUES (?1, ?2)",
-                DB_TABLE.user_account
-            ))
-            .bind(&[id.into(), user_token.into()])?
-            .run()
-            .await?
-            .into_result()
+    pub async fn resume(env: &Env, id: &UserId) -> ApiResult<()> {
+        Query::new(
+            env,
+            "user_account.resume",
+            update_where(DB_TABLE.user_account, "paused_until = NULL", "id = ?1"),
+            &[id.into()],
+        )?
+        .run()
+        .await
     }
 
     pub async fn update_user_token(env: &Env, id: &UserId, user_token: &str) -> ApiResult<()> {
-        get_d1(env)?
-            .prepare(format!(
-                "UPDATE {} SET user_token = ?1 WHERE id = ?2",
-                DB_TABLE.user_account
-            ))
-            .bind(&[user_token.into(), id.into()])?
-            .run()
-            .await?
-            .into_result()
+        Query::new(
+            env,
+            "user_account.update_user_token",
+            update_where(DB_TABLE.user_account, "user_token = ?1", "id = ?2"),
+            &[user_token.into(), id.into()],
+        )?
+        .run()
+        .await
     }
 }
 
@@ -72,43 +126,36 @@ pub struct OmiAccount {
 
 impl OmiAccount {
     pub async fn load(env: &Env, id: &str) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE id = ?1",
-                DB_TABLE.omi_account
-            ))
-            .bind(&[id.into()])?
-            .first::<OmiAccount>(None)
-            .await?
-            .map(OmiAccount::from)
-            .ok_or(format!("Need to register (omi id {id})").into())
+        Query::new(
+            env,
+            "omi_account.load",
+            select_where(DB_TABLE.omi_account, "id = ?1"),
+            &[id.into()],
+        )?
+        .first()
+        .await?
+        .ok_or(format!("Need to register (omi id {id})").into())
     }
 
     pub async fn exists(env: &Env, id: &str) -> ApiResult<bool> {
-        let res = get_d1(env)?
-            .prepare(format!(
-                "SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?1)",
-                DB_TABLE.omi_account
-            ))
-            .bind(&[id.into()])?
-            .raw::<u32>()
-            .await?;
-
-        let exists = res[0][0] == 1;
-
-        Ok(exists)
+        Query::new(
+            env,
+            "omi_account.exists",
+            exists_where(DB_TABLE.omi_account, "id = ?1"),
+            &[id.into()],
+        )?
+        .exists()
+        .await
     }
 
-    pub async fn insert(env: &Env, id: &str, uid: &UserId) -> ApiResult<()> {
-        get_d1(env)?
-            .prepare(format!(
-                "INSERT INTO {} (id, user_id) VALUES (?1, ?2)",
-                DB_TABLE.omi_account
-            ))
-            .bind(&[id.into(), uid.into()])?
-            .run()
-            .await?
-            .into_result()
+    pub fn insert_statement(env: &Env, id: &str, uid: &UserId) -> ApiResult<D1PreparedStatement> {
+        Ok(Query::new(
+            env,
+            "omi_account.insert",
+            insert_into(DB_TABLE.omi_account, &["id", "user_id"]),
+            &[id.into(), uid.into()],
+        )?
+        .into_statement())
     }
 }
 
@@ -123,29 +170,27 @@ pub struct TelegramAccount {
 
 impl TelegramAccount {
     pub async fn load(env: &Env, id: i64) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE id = ?1",
-                DB_TABLE.telegram_account
-            ))
-            .bind(&[JsValue::from_f64(id as f64)])?
-            .first::<TelegramAccount>(None)
-            .await?
-            .map(TelegramAccount::from)
-            .ok_or(format!("Need to register (tg {id})").into())
+        Query::new(
+            env,
+            "telegram_account.load",
+            select_where(DB_TABLE.telegram_account, "id = ?1"),
+            &[JsValue::from_f64(id as f64)],
+        )?
+        .first()
+        .await?
+        .ok_or(format!("Need to register (tg {id})").into())
     }
 
     pub async fn load_by_user_id(env: &Env, user_id: &UserId) -> ApiResult<Self> {
-        get_d1(env)?
-            .prepare(format!(
-                "SELECT * FROM {} WHERE user_id = ?1",
-                DB_TABLE.telegram_account
-            ))
-            .bind(&[user_id.into()])?
-            .first::<TelegramAccount>(None)
-            .await?
-            .map(TelegramAccount::from)
-            .ok_or(format!("Need to register (user_id {user_id})").into())
+        Query::new(
+            env,
+            "telegram_account.load_by_user_id",
+            select_where(DB_TABLE.telegram_account, "user_id = ?1"),
+            &[user_id.into()],
+        )?
+        .first()
+        .await?
+        .ok_or(format!("Need to register (user_id {user_id})").into())
     }
 
     pub async fn update_name(
@@ -155,60 +200,54 @@ impl TelegramAccount {
         username: Option<&str>,
     ) -> ApiResult<()> {
         match username {
-            Some(username) => get_d1(env)?
-                .prepare(format!(
-                    "UPDATE {} SET first_name = ?1, username = ?2 WHERE id = ?3",
-                    DB_TABLE.telegram_account
-                ))
-                .bind(&[
+            Some(username) => Query::new(
+                env,
+                "telegram_account.update_name",
+                update_where(
+                    DB_TABLE.telegram_account,
+                    "first_name = ?1, username = ?2",
+                    "id = ?3",
+                ),
+                &[
                     first_name.into(),
                     username.into(),
                     JsValue::from_f64(id as f64),
-                ])?
-                .run()
-                .await?
-                .into_result(),
-            None => get_d1(env)?
-                .prepare(format!(
-                    "UPDATE {} SET first_name = ?1 WHERE id = ?2",
-                    DB_TABLE.telegram_account
-                ))
-                .bind(&[first_name.into(), JsValue::from_f64(id as f64)])?
-                .run()
-                .await?
-                .into_result(),
+                ],
+            )?,
+            None => Query::new(
+                env,
+                "telegram_account.update_first_name",
+                update_where(DB_TABLE.telegram_account, "first_name = ?1", "id = ?2"),
+                &[first_name.into(), JsValue::from_f64(id as f64)],
+            )?,
         }
+        .run()
+        .await
     }
 
     pub async fn exists(env: &Env, id: i64) -> ApiResult<bool> {
-        let res = get_d1(env)?
-            .prepare(format!(
-                "SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?1)",
-                DB_TABLE.telegram_account
-            ))
-            .bind(&[JsValue::from_f64(id as f64)])?
-            .raw::<u32>()
-            .await?;
-
-        let exists = res[0][0] == 1;
-
-        Ok(exists)
+        Query::new(
+            env,
+            "telegram_account.exists",
+            exists_where(DB_TABLE.telegram_account, "id = ?1"),
+            &[JsValue::from_f64(id as f64)],
+        )?
+        .exists()
+        .await
     }
 
-    pub async fn insert(env: &Env, id: i64, uid: &UserId) -> ApiResult<()> {
+    pub fn insert_statement(env: &Env, id: i64, uid: &UserId) -> ApiResult<D1PreparedStatement> {
         // the real name and username will be updated later via messages
-        get_d1(env)?
-            .prepare(format!(
-                "INSERT INTO {} (id, user_id, first_name) VALUES (?1, ?2, ?3)",
-                DB_TABLE.telegram_account
-            ))
-            .bind(&[
+        Ok(Query::new(
+            env,
+            "telegram_account.insert",
+            insert_into(DB_TABLE.telegram_account, &["id", "user_id", "first_name"]),
+            &[
                 JsValue::from_f64(id as f64),
                 uid.into(),
                 format!("user {id}").into(),
-            ])?
-            .run()
-            .await?
-            .into_result()
+            ],
+        )?
+        .into_statement())
     }
 }
@@ -0,0 +1,102 @@
+// Each kind of destination (telegram dm, home assistant, etc.) implements the Destination trait
+// and gets a DestinationKind, which lookup maps back to it
+//
+// The database only knows about a generic `destination` table with a `kind` column
+// and a JSON `config` column, lookup is what gives those meaning
+mod home_assistant;
+mod telegram;
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use shared::api::action::{ActionDestinationKind, ParseMode};
+
+use crate::{db::user::TelegramAccount, prelude::*};
+
+pub use home_assistant::{HomeAssistantConfig, HomeAssistantDestination};
+pub use telegram::{
+    TelegramChannelDestination, TelegramConfig, TelegramDmDestination, TelegramGroupDestination,
+};
+
+// stored in the `kind` column, so the names must never change once deployed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DestinationKind {
+    TelegramDm,
+    TelegramGroup,
+    TelegramChannel,
+    HomeAssistant,
+}
+
+impl DestinationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TelegramDm => "telegram_dm",
+            Self::TelegramGroup => "telegram_group",
+            Self::TelegramChannel => "telegram_channel",
+            Self::HomeAssistant => "home_assistant",
+        }
+    }
+}
+
+pub fn lookup(kind: DestinationKind) -> &'static dyn Destination {
+    match kind {
+        DestinationKind::TelegramDm => &TelegramDmDestination,
+        DestinationKind::TelegramGroup => &TelegramGroupDestination,
+        DestinationKind::TelegramChannel => &TelegramChannelDestination,
+        DestinationKind::HomeAssistant => &HomeAssistantDestination,
+    }
+}
+
+// Everything a destination might need to deliver a triggered action
+pub struct Delivery<'a> {
+    pub env: &'a Env,
+    pub prompt: &'a str,
+    pub message: &'a str,
+    pub parse_mode: ParseMode,
+    pub transcript: &'a str,
+    pub sender: &'a TelegramAccount,
+    // sent from the dashboard rather than triggered, and marked as such
+    pub test: bool,
+}
+
+#[async_trait(?Send)]
+pub trait Destination {
+    fn kind(&self) -> DestinationKind;
+
+    // checked before the config is stored
+    fn validate(&self, config: &Value) -> ApiResult<()>;
+
+    // checked before an action's message is stored
+    fn validate_message(&self, _message: &str, _parse_mode: ParseMode) -> ApiResult<()> {
+        Ok(())
+    }
+
+    // what a destination ping sends as the message, rendered like any other
+    fn ping_message(&self) -> &'static str {
+        "This is a test message from Omi Assist"
+    }
+
+    // what the client sees, secrets must not leak through here
+    fn api_kind(&self, config: &Value) -> ApiResult<ActionDestinationKind>;
+
+    // what gets sent, kept in the delivery history whether or not it goes through
+    fn render(&self, delivery: &Delivery<'_>) -> ApiResult<String>;
+
+    // sends what render() returned
+    // returns the sent message id, for destinations that have one
+    async fn deliver(
+        &self,
+        config: &Value,
+        delivery: &Delivery<'_>,
+        rendered: &str,
+    ) -> ApiResult<Option<i64>>;
+}
+
+pub fn serialize_config<T: Serialize>(config: &T) -> ApiResult<Value> {
+    serde_json::to_value(config).map_err(|err| ApiError::Db(err.to_string()))
+}
+
+pub fn deserialize_config<T: DeserializeOwned>(config: &Value) -> ApiResult<T> {
+    serde_json::from_value(config.clone()).map_err(|err| ApiError::Db(err.to_string()))
+}
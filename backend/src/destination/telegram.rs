@@ -0,0 +1,237 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shared::api::{
+    action::{ActionDestinationKind, ParseMode},
+    telegram::TelegramBotError,
+};
+
+use super::{deserialize_config, Delivery, Destination, DestinationKind};
+use crate::{db::destination::DestinationDb, formatter, prelude::*, telegram::TelegramBot};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TelegramConfig {
+    pub chat_id: i64,
+    // forum topic, only for groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+}
+
+pub struct TelegramDmDestination;
+
+impl TelegramDmDestination {
+    pub const KIND: DestinationKind = DestinationKind::TelegramDm;
+}
+
+#[async_trait(?Send)]
+impl Destination for TelegramDmDestination {
+    fn kind(&self) -> DestinationKind {
+        Self::KIND
+    }
+
+    fn validate(&self, config: &Value) -> ApiResult<()> {
+        validate(config)
+    }
+
+    fn validate_message(&self, message: &str, parse_mode: ParseMode) -> ApiResult<()> {
+        formatter::validate(message, parse_mode)
+    }
+
+    fn api_kind(&self, config: &Value) -> ApiResult<ActionDestinationKind> {
+        let TelegramConfig { chat_id, .. } = deserialize_config(config)?;
+        Ok(ActionDestinationKind::TelegramDm { chat_id })
+    }
+
+    fn render(&self, delivery: &Delivery<'_>) -> ApiResult<String> {
+        Ok(render(delivery))
+    }
+
+    async fn deliver(
+        &self,
+        config: &Value,
+        delivery: &Delivery<'_>,
+        rendered: &str,
+    ) -> ApiResult<Option<i64>> {
+        deliver(deserialize_config(config)?, delivery, rendered)
+            .await
+            .map(Some)
+    }
+}
+
+pub struct TelegramGroupDestination;
+
+impl TelegramGroupDestination {
+    pub const KIND: DestinationKind = DestinationKind::TelegramGroup;
+}
+
+#[async_trait(?Send)]
+impl Destination for TelegramGroupDestination {
+    fn kind(&self) -> DestinationKind {
+        Self::KIND
+    }
+
+    fn validate(&self, config: &Value) -> ApiResult<()> {
+        validate(config)
+    }
+
+    fn validate_message(&self, message: &str, parse_mode: ParseMode) -> ApiResult<()> {
+        formatter::validate(message, parse_mode)
+    }
+
+    fn api_kind(&self, config: &Value) -> ApiResult<ActionDestinationKind> {
+        let TelegramConfig {
+            chat_id,
+            message_thread_id,
+        } = deserialize_config(config)?;
+        Ok(ActionDestinationKind::TelegramGroup {
+            chat_id,
+            message_thread_id,
+        })
+    }
+
+    fn render(&self, delivery: &Delivery<'_>) -> ApiResult<String> {
+        Ok(render(delivery))
+    }
+
+    async fn deliver(
+        &self,
+        config: &Value,
+        delivery: &Delivery<'_>,
+        rendered: &str,
+    ) -> ApiResult<Option<i64>> {
+        deliver(deserialize_config(config)?, delivery, rendered)
+            .await
+            .map(Some)
+    }
+}
+
+pub struct TelegramChannelDestination;
+
+impl TelegramChannelDestination {
+    pub const KIND: DestinationKind = DestinationKind::TelegramChannel;
+}
+
+#[async_trait(?Send)]
+impl Destination for TelegramChannelDestination {
+    fn kind(&self) -> DestinationKind {
+        Self::KIND
+    }
+
+    fn validate(&self, config: &Value) -> ApiResult<()> {
+        validate(config)
+    }
+
+    fn validate_message(&self, message: &str, parse_mode: ParseMode) -> ApiResult<()> {
+        formatter::validate(message, parse_mode)
+    }
+
+    fn api_kind(&self, config: &Value) -> ApiResult<ActionDestinationKind> {
+        let TelegramConfig { chat_id, .. } = deserialize_config(config)?;
+        Ok(ActionDestinationKind::TelegramChannel { chat_id })
+    }
+
+    fn render(&self, delivery: &Delivery<'_>) -> ApiResult<String> {
+        Ok(render(delivery))
+    }
+
+    async fn deliver(
+        &self,
+        config: &Value,
+        delivery: &Delivery<'_>,
+        rendered: &str,
+    ) -> ApiResult<Option<i64>> {
+        deliver(deserialize_config(config)?, delivery, rendered)
+            .await
+            .map(Some)
+    }
+}
+
+// dm, group and channel only differ in how they're presented, delivery is the same
+
+fn validate(config: &Value) -> ApiResult<()> {
+    deserialize_config::<TelegramConfig>(config)
+        .map(|_| ())
+        .map_err(|err| ApiError::Validation(err.to_string()))
+}
+
+fn render(delivery: &Delivery<'_>) -> String {
+    let message =
+        formatter::format_delivery(delivery.sender, delivery.message, delivery.parse_mode);
+
+    // brackets would need escaping in MarkdownV2
+    if delivery.test {
+        format!(
+            "{} {message}",
+            formatter::escape("[TEST]", delivery.parse_mode)
+        )
+    } else {
+        message
+    }
+}
+
+// returns the sent message id
+async fn deliver(config: TelegramConfig, delivery: &Delivery<'_>, message: &str) -> ApiResult<i64> {
+    tracing::info!("Sending message to chat {}: {}", config.chat_id, message);
+
+    let tg = TelegramBot::new(delivery.env);
+
+    match send(&tg, config.chat_id, &config, message, delivery).await {
+        Ok(message_id) => Ok(message_id),
+        Err(ApiError::Telegram(TelegramBotError::ChatMigrated { migrate_to_chat_id })) => {
+            tracing::info!(
+                "Chat {} migrated to {}, updating destinations",
+                config.chat_id,
+                migrate_to_chat_id
+            );
+
+            DestinationDb::migrate_telegram_chat_id(
+                delivery.env,
+                config.chat_id,
+                migrate_to_chat_id,
+            )
+            .await?;
+
+            send(&tg, migrate_to_chat_id, &config, message, delivery).await
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// messages are validated when they're stored, but if Telegram still can't parse them
+// it's better to send them as plain text than not at all
+async fn send(
+    tg: &TelegramBot,
+    chat_id: i64,
+    config: &TelegramConfig,
+    message: &str,
+    delivery: &Delivery<'_>,
+) -> ApiResult<i64> {
+    match tg
+        .send_formatted_message(
+            chat_id,
+            config.message_thread_id,
+            message,
+            delivery.parse_mode,
+        )
+        .await
+    {
+        Ok(sent) => Ok(sent.message_id),
+        Err(ApiError::Telegram(TelegramBotError::BadRequest(description)))
+            if delivery.parse_mode != ParseMode::Plain
+                && description.contains("can't parse entities") =>
+        {
+            tracing::warn!("Telegram couldn't parse message, sending as plain text: {description}");
+
+            let plain = formatter::format_delivery(
+                delivery.sender,
+                &formatter::strip(delivery.message, delivery.parse_mode),
+                ParseMode::Plain,
+            );
+
+            tg.send_thread_message(chat_id, config.message_thread_id, &plain)
+                .await
+                .map(|sent| sent.message_id)
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,118 @@
+// Builds the text that's sent to Telegram for a given parse_mode
+//
+// Action messages are written by the user in the chosen markup and validated before they're stored,
+// everything else that ends up in the message (sender names, transcripts) is escaped here
+use shared::{
+    api::action::ParseMode,
+    backend::result::{ApiError, ApiResult},
+    format::{parse, plain_text, MARKDOWN_V2_RESERVED},
+};
+
+use crate::db::user::TelegramAccount;
+
+pub fn escape(text: &str, parse_mode: ParseMode) -> String {
+    match parse_mode {
+        ParseMode::Plain => text.to_string(),
+        ParseMode::MarkdownV2 => {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                if c == '\\' || MARKDOWN_V2_RESERVED.contains(&c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+        ParseMode::Html => text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;"),
+    }
+}
+
+// checked before an action's message is stored
+pub fn validate(message: &str, parse_mode: ParseMode) -> ApiResult<()> {
+    parse(message, parse_mode)
+        .map(|_| ())
+        .map_err(|err| ApiError::Validation(format!("invalid formatting: {err}")))
+}
+
+// the message with all markup removed, for when Telegram rejects it anyway
+pub fn strip(message: &str, parse_mode: ParseMode) -> String {
+    match parse(message, parse_mode) {
+        Ok(spans) => plain_text(&spans),
+        Err(_) => message.to_string(),
+    }
+}
+
+pub fn format_delivery(sender: &TelegramAccount, message: &str, parse_mode: ParseMode) -> String {
+    let first_name = escape(&sender.first_name, parse_mode);
+
+    match &sender.username {
+        Some(username) => format!(
+            "message from {} {}: {}",
+            first_name,
+            escape(&format!("(@{username})"), parse_mode),
+            message
+        ),
+        None => format!("message from {}: {}", first_name, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [ParseMode; 3] = [ParseMode::Plain, ParseMode::MarkdownV2, ParseMode::Html];
+
+    // anything a sender name or transcript might contain
+    const SAMPLES: &[&str] = &[
+        "",
+        "plain words",
+        "_*[]()~`>#+-=|{}.!",
+        "back\\slash and \\*",
+        "<b>not bold</b> & \"quoted\" &amp;",
+        ">starts like a quote\n>and again",
+        "||spoiler|| __underline__ ```code```",
+        "émoji 🎉 and ünïcode",
+    ];
+
+    #[test]
+    fn escaped_text_is_valid_and_unchanged() {
+        for parse_mode in MODES {
+            for sample in SAMPLES {
+                let escaped = escape(sample, parse_mode);
+                assert!(
+                    validate(&escaped, parse_mode).is_ok(),
+                    "{parse_mode:?} rejected {escaped:?}"
+                );
+                assert_eq!(strip(&escaped, parse_mode), *sample);
+            }
+        }
+    }
+
+    #[test]
+    fn escaped_text_inside_markup() {
+        for sample in SAMPLES {
+            let message = format!("*{}*", escape(sample, ParseMode::MarkdownV2));
+            assert!(validate(&message, ParseMode::MarkdownV2).is_ok());
+
+            let message = format!("<b>{}</b>", escape(sample, ParseMode::Html));
+            assert!(validate(&message, ParseMode::Html).is_ok());
+        }
+    }
+
+    #[test]
+    fn invalid_markup_is_a_validation_error() {
+        assert!(matches!(
+            validate("*unclosed", ParseMode::MarkdownV2),
+            Err(ApiError::Validation(_))
+        ));
+        assert!(matches!(
+            validate("<b>unclosed", ParseMode::Html),
+            Err(ApiError::Validation(_))
+        ));
+        assert!(validate("*unclosed", ParseMode::Plain).is_ok());
+    }
+}
@@ -1,15 +1,51 @@
 use crate::{
     api_ext::*,
-    db::{action::TelegramActionDb, destination::TelegramDestinationDb},
+    config::{
+        CONFIG_IMPORT_MAX_ACTIONS, LIST_PAGE_SIZE_DEFAULT, LIST_PAGE_SIZE_MAX,
+        TEST_SEND_MAX_PER_WINDOW, TEST_SEND_WINDOW_SECS,
+    },
+    db::{
+        action::TelegramActionDb,
+        delivery::DeliveryDb,
+        destination::DestinationDb,
+        invitation::InvitationDb,
+        page::Page,
+        user::{TelegramAccount, UserAccount},
+    },
+    destination::{
+        lookup, serialize_config, Delivery, HomeAssistantConfig, HomeAssistantDestination,
+    },
+    handlers::{
+        omi::{deliver_action, has_pause_phrase, join_transcript, matching_segment, skip_reason},
+        telegram::create_deep_link,
+    },
+    helpers::run_batch,
+    kv::{auth::DeepLinkPayload, rate_limit::RateLimitKv},
     ApiContext,
 };
 use action::{
-    Action, ActionId, AddAction, AddActionRequest, AddActionResponse, DeleteAction,
-    DeleteActionRequest, ListActionDestinations, ListActionDestinationsRequest,
-    ListActionDestinationsResponse, ListActions, ListActionsRequest, ListActionsResponse,
+    Action, ActionDestinationId, ActionId, AddAction, AddActionRequest, AddActionResponse,
+    AddHomeAssistantDestination, AddHomeAssistantDestinationRequest,
+    AddHomeAssistantDestinationResponse, ConfigAction, ConfigDestination, ConfigFile, ConfigFormat,
+    CreateDeepLink, CreateDeepLinkRequest, CreateInvitation, CreateInvitationRequest,
+    CreateInvitationResponse, DeepLinkKind, DeleteAction, DeleteActionRequest, DeleteDestination,
+    DeleteDestinationRequest, DeliverySkipReason, DeliveryStatus, ExportConfig,
+    ExportConfigRequest, ExportConfigResponse, GetPause, ImportConfig, ImportConfigRequest,
+    ImportConfigResponse, ImportMode, InvitationId, ListActionDestinations,
+    ListActionDestinationsRequest, ListActionDestinationsResponse, ListActions, ListActionsRequest,
+    ListActionsResponse, ListDeliveries, ListDeliveriesRequest, ListDeliveriesResponse,
+    ListInvitations, ListInvitationsResponse, ParseMode, PauseResponse, PingDestination,
+    PingDestinationRequest, RenameDestination, RenameDestinationRequest, SetPause, SetPauseRequest,
+    SimulateTranscript, SimulateTranscriptRequest, SimulateTranscriptResponse, SimulatedAction,
+    TestAction, TestActionRequest, TestSendResponse, UpdateAction, UpdateActionRequest,
+    UpdateActionResponse, CONFIG_FILE_VERSION,
 };
 use async_trait::async_trait;
-use shared::{api::*, backend::result::ApiResult};
+use shared::{
+    api::{auth::DeepLinkResponse, telegram::TelegramBotError, *},
+    backend::result::{ApiError, ApiResult},
+};
+use worker::HttpRequest;
 
 #[async_trait(?Send)]
 impl ApiBothExt for ListActionDestinations {
@@ -21,14 +57,71 @@ impl ApiBothExt for ListActionDestinations {
     ) -> ApiResult<ListActionDestinationsResponse> {
         let uid = ctx.uid_unchecked();
 
-        let destinations = TelegramDestinationDb::list(&ctx.env, &uid).await?;
+        let page = Page::new(
+            ctx.req.cursor.as_deref(),
+            ctx.req
+                .limit
+                .unwrap_or(LIST_PAGE_SIZE_DEFAULT)
+                .min(LIST_PAGE_SIZE_MAX),
+            ctx.req.sort,
+        )?;
+
+        let (destinations, next_cursor) = DestinationDb::list_page(&ctx.env, &uid, &page).await?;
 
-        Ok(ListActionDestinationsResponse { destinations })
+        Ok(ListActionDestinationsResponse {
+            destinations,
+            next_cursor,
+        })
     }
 }
 
 impl FromHttpRequest for ListActionDestinationsRequest {}
 
+#[async_trait(?Send)]
+impl ApiBothExt for AddHomeAssistantDestination {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(
+        ctx: &ApiContext<AddHomeAssistantDestinationRequest>,
+    ) -> ApiResult<AddHomeAssistantDestinationResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let AddHomeAssistantDestinationRequest {
+            name,
+            base_url,
+            token,
+            target,
+        } = &ctx.req;
+
+        let config = serialize_config(&HomeAssistantConfig {
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+            token: token.trim().to_string(),
+            target: target.clone(),
+        })?;
+
+        let destination_id = ActionDestinationId::new(uuid::Uuid::now_v7());
+
+        DestinationDb::insert(
+            &ctx.env,
+            &destination_id,
+            &uid,
+            name,
+            &HomeAssistantDestination,
+            &config,
+        )
+        .await?;
+
+        let destination = DestinationDb::load(&ctx.env, &destination_id)
+            .await?
+            .try_into()?;
+
+        Ok(AddHomeAssistantDestinationResponse { destination })
+    }
+}
+
+impl FromHttpRequest for AddHomeAssistantDestinationRequest {}
+
 #[async_trait(?Send)]
 impl ApiBothExt for AddAction {
     type Res = <Self as ApiBoth>::Res;
@@ -38,8 +131,13 @@ impl ApiBothExt for AddAction {
         let uid = ctx.uid_unchecked();
 
         let destination =
-            TelegramDestinationDb::load_with_user_id(&ctx.env, &ctx.req.destination_id, &uid)
-                .await?;
+            DestinationDb::load_with_user_id(&ctx.env, &ctx.req.destination_id, &uid).await?;
+
+        destination
+            .destination()
+            .validate_message(&ctx.req.message, ctx.req.parse_mode)?;
+
+        let destination = destination.try_into()?;
 
         let action_id = ActionId::new(uuid::Uuid::now_v7());
 
@@ -49,14 +147,18 @@ impl ApiBothExt for AddAction {
             &ctx.req.destination_id,
             &ctx.req.prompt,
             &ctx.req.message,
+            ctx.req.parse_mode,
+            true,
         )
         .await?;
 
         let action = Action {
             id: action_id,
-            destination: destination.into(),
+            destination,
             prompt: ctx.req.prompt.clone(),
             message: ctx.req.message.clone(),
+            parse_mode: ctx.req.parse_mode,
+            enabled: true,
         };
 
         Ok(AddActionResponse { action })
@@ -65,6 +167,57 @@ impl ApiBothExt for AddAction {
 
 impl FromHttpRequest for AddActionRequest {}
 
+#[async_trait(?Send)]
+impl ApiBothExt for UpdateAction {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<UpdateActionRequest>) -> ApiResult<UpdateActionResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let existing = TelegramActionDb::load_with_destination(&ctx.env, &uid, &ctx.req.id)
+            .await?
+            .ok_or_else(|| ApiError::Validation("no such action".to_string()))?;
+
+        let destination_id = ctx
+            .req
+            .destination_id
+            .clone()
+            .unwrap_or_else(|| existing.destination_id.clone());
+        let prompt = ctx.req.prompt.as_ref().unwrap_or(&existing.prompt);
+        let message = ctx.req.message.as_ref().unwrap_or(&existing.msg);
+        let parse_mode = ctx.req.parse_mode.unwrap_or(existing.parse_mode);
+        let enabled = ctx.req.enabled.unwrap_or(existing.enabled.into());
+
+        // checked even if the message didn't change, the destination or parse mode may have
+        DestinationDb::load_with_user_id(&ctx.env, &destination_id, &uid)
+            .await?
+            .destination()
+            .validate_message(message, parse_mode)?;
+
+        TelegramActionDb::update(
+            &ctx.env,
+            &uid,
+            &ctx.req.id,
+            &destination_id,
+            prompt,
+            message,
+            parse_mode,
+            enabled,
+        )
+        .await?;
+
+        let action = TelegramActionDb::load_with_destination(&ctx.env, &uid, &ctx.req.id)
+            .await?
+            .ok_or_else(|| ApiError::Validation("no such action".to_string()))?
+            .try_into()?;
+
+        Ok(UpdateActionResponse { action })
+    }
+}
+
+impl FromHttpRequest for UpdateActionRequest {}
+
 #[async_trait(?Send)]
 impl ApiReqExt for DeleteAction {
     type Req = <Self as ApiReq>::Req;
@@ -88,10 +241,623 @@ impl ApiBothExt for ListActions {
     async fn handle(ctx: &ApiContext<ListActionsRequest>) -> ApiResult<ListActionsResponse> {
         let uid = ctx.uid_unchecked();
 
-        let actions = TelegramActionDb::list(&ctx.env, &uid).await?;
+        let page = Page::new(
+            ctx.req.cursor.as_deref(),
+            ctx.req
+                .limit
+                .unwrap_or(LIST_PAGE_SIZE_DEFAULT)
+                .min(LIST_PAGE_SIZE_MAX),
+            ctx.req.sort,
+        )?;
 
-        Ok(ListActionsResponse { actions })
+        let (actions, next_cursor) = TelegramActionDb::list_page(&ctx.env, &uid, &page).await?;
+
+        Ok(ListActionsResponse {
+            actions,
+            next_cursor,
+        })
     }
 }
 
 impl FromHttpRequest for ListActionsRequest {}
+
+#[async_trait(?Send)]
+impl ApiReqExt for DeleteDestination {
+    type Req = <Self as ApiReq>::Req;
+
+    async fn handle(ctx: &ApiContext<DeleteDestinationRequest>) -> ApiResult<()> {
+        let uid = ctx.uid_unchecked();
+
+        DestinationDb::delete_cascade(&ctx.env, &uid, &ctx.req.id, ctx.req.cascade).await?;
+
+        Ok(())
+    }
+}
+
+impl FromHttpRequest for DeleteDestinationRequest {}
+
+#[async_trait(?Send)]
+impl ApiReqExt for RenameDestination {
+    type Req = <Self as ApiReq>::Req;
+
+    async fn handle(ctx: &ApiContext<RenameDestinationRequest>) -> ApiResult<()> {
+        let uid = ctx.uid_unchecked();
+
+        let name = ctx.req.name.trim();
+
+        if name.is_empty() {
+            return Err(ApiError::Validation("name cannot be empty".to_string()));
+        }
+
+        // fails if it doesn't exist or isn't ours
+        DestinationDb::load_with_user_id(&ctx.env, &ctx.req.id, &uid).await?;
+
+        DestinationDb::rename(&ctx.env, &uid, &ctx.req.id, name).await?;
+
+        Ok(())
+    }
+}
+
+impl FromHttpRequest for RenameDestinationRequest {}
+
+#[async_trait(?Send)]
+impl ApiBothExt for CreateDeepLink {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<CreateDeepLinkRequest>) -> ApiResult<DeepLinkResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let payload = match ctx.req.kind {
+            DeepLinkKind::LinkGroup => DeepLinkPayload::LinkGroup { uid },
+        };
+
+        create_deep_link(&ctx.env, payload).await
+    }
+}
+
+impl FromHttpRequest for CreateDeepLinkRequest {}
+
+#[async_trait(?Send)]
+impl ApiBothExt for CreateInvitation {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(
+        ctx: &ApiContext<CreateInvitationRequest>,
+    ) -> ApiResult<CreateInvitationResponse> {
+        let uid = ctx.uid_unchecked();
+
+        // shown to the contact as-is, it's why they should say yes
+        let reason = ctx.req.reason.trim();
+
+        if reason.is_empty() {
+            return Err(ApiError::Validation("reason cannot be empty".to_string()));
+        }
+
+        let invitation_id = InvitationId::new(uuid::Uuid::now_v7());
+
+        InvitationDb::insert(&ctx.env, &invitation_id, &uid, reason).await?;
+
+        let deep_link = create_deep_link(
+            &ctx.env,
+            DeepLinkPayload::InviteContact {
+                invitation_id: invitation_id.clone(),
+            },
+        )
+        .await?;
+
+        let invitation = InvitationDb::load(&ctx.env, &invitation_id)
+            .await?
+            .ok_or_else(|| ApiError::Db("invitation not found after insert".to_string()))?;
+
+        Ok(CreateInvitationResponse {
+            invitation: invitation.into(),
+            deep_link,
+        })
+    }
+}
+
+impl FromHttpRequest for CreateInvitationRequest {}
+
+#[async_trait(?Send)]
+impl ApiResExt for ListInvitations {
+    type Res = <Self as ApiRes>::Res;
+
+    async fn handle(ctx: &ApiContext<HttpRequest>) -> ApiResult<ListInvitationsResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let invitations = InvitationDb::list(&ctx.env, &uid).await?;
+
+        Ok(ListInvitationsResponse { invitations })
+    }
+}
+
+#[async_trait(?Send)]
+impl ApiBothExt for ListDeliveries {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<ListDeliveriesRequest>) -> ApiResult<ListDeliveriesResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let (deliveries, next_cursor) = DeliveryDb::list(&ctx.env, &uid, &ctx.req).await?;
+
+        Ok(ListDeliveriesResponse {
+            deliveries,
+            next_cursor,
+        })
+    }
+}
+
+impl FromHttpRequest for ListDeliveriesRequest {}
+
+#[async_trait(?Send)]
+impl ApiBothExt for SimulateTranscript {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(
+        ctx: &ApiContext<SimulateTranscriptRequest>,
+    ) -> ApiResult<SimulateTranscriptResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let sender = TelegramAccount::load_by_user_id(&ctx.env, &uid).await?;
+        let transcript = join_transcript(&ctx.req.segments);
+
+        // saying the pause phrase counts too, nothing in that transcript would be sent
+        let paused = has_pause_phrase(&ctx.req.segments)
+            || UserAccount::paused_until(&ctx.env, &uid).await?.is_some();
+
+        let mut actions = Vec::new();
+
+        for action in TelegramActionDb::list_with_destinations(&ctx.env, &uid).await? {
+            let matched_segment = matching_segment(&action, &ctx.req.segments);
+
+            let (skipped, rendered, render_error) = match matched_segment {
+                None => (None, None, None),
+                Some(_) => {
+                    let delivery = Delivery {
+                        env: &ctx.env,
+                        prompt: &action.prompt,
+                        message: &action.msg,
+                        parse_mode: action.parse_mode,
+                        transcript: &transcript,
+                        sender: &sender,
+                        test: false,
+                    };
+
+                    match lookup(action.kind).render(&delivery) {
+                        Ok(rendered) => (skip_reason(&action, paused), Some(rendered), None),
+                        Err(err) => (skip_reason(&action, paused), None, Some(err.to_string())),
+                    }
+                }
+            };
+
+            actions.push(SimulatedAction {
+                would_send: matched_segment.is_some()
+                    && skipped.is_none()
+                    && render_error.is_none(),
+                matched_segment: matched_segment.map(|segment| segment.text.clone()),
+                action_id: action.id,
+                prompt: action.prompt,
+                destination_name: action.name,
+                skipped,
+                rendered,
+                render_error,
+            });
+        }
+
+        Ok(SimulateTranscriptResponse { actions })
+    }
+}
+
+impl FromHttpRequest for SimulateTranscriptRequest {}
+
+// test sends and pings share one limit, they both end up in someone's chat
+async fn check_test_send_limit(ctx: &ApiContext<impl Sized>) -> ApiResult<()> {
+    RateLimitKv::hit(
+        &ctx.env,
+        &format!("test-send-{}", ctx.uid_unchecked()),
+        TEST_SEND_MAX_PER_WINDOW,
+        TEST_SEND_WINDOW_SECS,
+    )
+    .await
+}
+
+#[async_trait(?Send)]
+impl ApiBothExt for TestAction {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<TestActionRequest>) -> ApiResult<TestSendResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let action = TelegramActionDb::load_with_destination(&ctx.env, &uid, &ctx.req.id)
+            .await?
+            .ok_or_else(|| ApiError::Validation("no such action".to_string()))?;
+
+        check_test_send_limit(ctx).await?;
+
+        let sender = TelegramAccount::load_by_user_id(&ctx.env, &uid).await?;
+
+        // there's no real transcript, so the prompt stands in for one
+        let delivery = Delivery {
+            env: &ctx.env,
+            prompt: &action.prompt,
+            message: &action.msg,
+            parse_mode: action.parse_mode,
+            transcript: &action.prompt,
+            sender: &sender,
+            test: true,
+        };
+
+        // recorded in the history like any other delivery
+        // a pause is for triggered actions, a test is explicitly asked for
+        let status = deliver_action(&ctx.env, &uid, &action, &delivery).await?;
+
+        let error = match status {
+            DeliveryStatus::Sent => None,
+            DeliveryStatus::Skipped => {
+                skip_reason(&action, false).map(|reason| reason.description().to_string())
+            }
+            DeliveryStatus::Failed => {
+                Some("destination is unreachable, it's been marked inactive".to_string())
+            }
+        };
+
+        Ok(TestSendResponse { status, error })
+    }
+}
+
+impl FromHttpRequest for TestActionRequest {}
+
+#[async_trait(?Send)]
+impl ApiBothExt for PingDestination {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<PingDestinationRequest>) -> ApiResult<TestSendResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let destination_db = DestinationDb::load_with_user_id(&ctx.env, &ctx.req.id, &uid).await?;
+
+        // an inactive destination is still pinged, that's how to find out it's reachable again
+        if !bool::from(destination_db.consented) {
+            return Ok(TestSendResponse {
+                status: DeliveryStatus::Skipped,
+                error: Some(DeliverySkipReason::NotConsented.description().to_string()),
+            });
+        }
+
+        check_test_send_limit(ctx).await?;
+
+        let sender = TelegramAccount::load_by_user_id(&ctx.env, &uid).await?;
+        let destination = destination_db.destination();
+
+        let delivery = Delivery {
+            env: &ctx.env,
+            prompt: "",
+            message: destination.ping_message(),
+            parse_mode: ParseMode::Plain,
+            transcript: "",
+            sender: &sender,
+            test: true,
+        };
+
+        let rendered = destination.render(&delivery)?;
+
+        // not recorded in the history, there's no action to record it against
+        match destination
+            .deliver(&destination_db.config()?, &delivery, &rendered)
+            .await
+        {
+            Ok(_) => {
+                if !bool::from(destination_db.active) {
+                    DestinationDb::set_active(&ctx.env, &destination_db.id, true).await?;
+                }
+
+                Ok(TestSendResponse {
+                    status: DeliveryStatus::Sent,
+                    error: None,
+                })
+            }
+            Err(ApiError::Telegram(TelegramBotError::Forbidden(reason))) => {
+                DestinationDb::set_active(&ctx.env, &destination_db.id, false).await?;
+
+                Ok(TestSendResponse {
+                    status: DeliveryStatus::Failed,
+                    error: Some(reason),
+                })
+            }
+            Err(err) => Ok(TestSendResponse {
+                status: DeliveryStatus::Failed,
+                error: Some(err.to_string()),
+            }),
+        }
+    }
+}
+
+impl FromHttpRequest for PingDestinationRequest {}
+
+#[async_trait(?Send)]
+impl ApiResExt for GetPause {
+    type Res = <Self as ApiRes>::Res;
+
+    async fn handle(ctx: &ApiContext<HttpRequest>) -> ApiResult<PauseResponse> {
+        let uid = ctx.uid_unchecked();
+
+        Ok(PauseResponse {
+            paused_until: UserAccount::paused_until(&ctx.env, &uid).await?,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl ApiBothExt for SetPause {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<SetPauseRequest>) -> ApiResult<PauseResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let paused_until = match ctx.req.duration_secs {
+            Some(duration_secs) => UserAccount::pause(&ctx.env, &uid, duration_secs).await?,
+            None => {
+                UserAccount::resume(&ctx.env, &uid).await?;
+                None
+            }
+        };
+
+        Ok(PauseResponse { paused_until })
+    }
+}
+
+impl FromHttpRequest for SetPauseRequest {}
+
+#[async_trait(?Send)]
+impl ApiBothExt for ExportConfig {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<ExportConfigRequest>) -> ApiResult<ExportConfigResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let destinations = DestinationDb::list_rows(&ctx.env, &uid).await?;
+        let actions = TelegramActionDb::list_with_destinations(&ctx.env, &uid).await?;
+
+        let file = ConfigFile {
+            version: CONFIG_FILE_VERSION,
+            destinations: destinations
+                .into_iter()
+                .map(|destination| ConfigDestination {
+                    name: destination.name,
+                    kind: destination.kind.as_str().to_string(),
+                })
+                .collect(),
+            actions: actions
+                .into_iter()
+                .map(|action| ConfigAction {
+                    prompt: action.prompt,
+                    destination: action.name,
+                    message: action.msg,
+                    parse_mode: action.parse_mode,
+                    enabled: action.enabled.into(),
+                })
+                .collect(),
+        };
+
+        Ok(ExportConfigResponse {
+            content: encode_config(&file, ctx.req.format)?,
+            filename: format!("omi-assist.{}", ctx.req.format.extension()),
+        })
+    }
+}
+
+impl FromHttpRequest for ExportConfigRequest {}
+
+#[async_trait(?Send)]
+impl ApiBothExt for ImportConfig {
+    type Res = <Self as ApiBoth>::Res;
+    type Req = <Self as ApiBoth>::Req;
+
+    async fn handle(ctx: &ApiContext<ImportConfigRequest>) -> ApiResult<ImportConfigResponse> {
+        let uid = ctx.uid_unchecked();
+
+        let ImportConfigRequest {
+            content,
+            format,
+            mode,
+            dry_run,
+        } = &ctx.req;
+
+        let file = decode_config(content, *format)?;
+
+        if file.version == 0 || file.version > CONFIG_FILE_VERSION {
+            return Err(ApiError::Validation(format!(
+                "unsupported config version {}, expected {CONFIG_FILE_VERSION} or lower",
+                file.version
+            )));
+        }
+
+        if file.actions.len() > CONFIG_IMPORT_MAX_ACTIONS {
+            return Err(ApiError::Validation(format!(
+                "too many actions, at most {CONFIG_IMPORT_MAX_ACTIONS} can be imported at once"
+            )));
+        }
+
+        let destinations = DestinationDb::list_rows(&ctx.env, &uid).await?;
+        let existing = TelegramActionDb::list_with_destinations(&ctx.env, &uid).await?;
+
+        let mut res = ImportConfigResponse {
+            applied: false,
+            added: 0,
+            updated: 0,
+            unchanged: 0,
+            removed: 0,
+            problems: Vec::new(),
+        };
+
+        // the existing action id if it's an update, and the destination it's for
+        let mut changes: Vec<(Option<ActionId>, ActionDestinationId, &ConfigAction)> = Vec::new();
+        // everything in the file so far, to catch duplicates
+        let mut seen: Vec<(&ActionDestinationId, &str)> = Vec::new();
+
+        for (index, action) in file.actions.iter().enumerate() {
+            let n = index + 1;
+            let prompt = action.prompt.trim();
+
+            if prompt.is_empty() {
+                res.problems
+                    .push(format!("action {n}: the prompt is empty"));
+                continue;
+            }
+
+            let mut named = destinations
+                .iter()
+                .filter(|destination| destination.name == action.destination);
+
+            let destination = match (named.next(), named.next()) {
+                (Some(destination), None) => destination,
+                (None, _) => {
+                    res.problems.push(format!(
+                        "action {n}: there's no destination named \"{}\"",
+                        action.destination
+                    ));
+                    continue;
+                }
+                (Some(_), Some(_)) => {
+                    res.problems.push(format!(
+                        "action {n}: more than one destination is named \"{}\"",
+                        action.destination
+                    ));
+                    continue;
+                }
+            };
+
+            if let Err(err) = destination
+                .destination()
+                .validate_message(&action.message, action.parse_mode)
+            {
+                res.problems.push(format!("action {n}: {err}"));
+                continue;
+            }
+
+            if seen
+                .iter()
+                .any(|(id, other)| *id == &destination.id && same_prompt(other, prompt))
+            {
+                res.problems.push(format!(
+                    "action {n}: \"{prompt}\" is already listed for \"{}\"",
+                    destination.name
+                ));
+                continue;
+            }
+            seen.push((&destination.id, prompt));
+
+            // matched the same way in both modes, so kept actions keep their id (and delivery history)
+            let current = existing.iter().find(|current| {
+                current.destination_id == destination.id && same_prompt(&current.prompt, prompt)
+            });
+
+            match current {
+                Some(current)
+                    if current.msg == action.message
+                        && current.parse_mode == action.parse_mode
+                        && bool::from(current.enabled) == action.enabled =>
+                {
+                    res.unchanged += 1;
+                }
+                Some(current) => {
+                    res.updated += 1;
+                    changes.push((Some(current.id.clone()), destination.id.clone(), action));
+                }
+                None => {
+                    res.added += 1;
+                    changes.push((None, destination.id.clone(), action));
+                }
+            }
+        }
+
+        // replace only removes what the file doesn't have
+        let removed = match mode {
+            ImportMode::Merge => Vec::new(),
+            ImportMode::Replace => existing
+                .iter()
+                .filter(|current| {
+                    !seen.iter().any(|(destination_id, prompt)| {
+                        *destination_id == &current.destination_id
+                            && same_prompt(&current.prompt, prompt)
+                    })
+                })
+                .collect(),
+        };
+        res.removed = removed.len() as u32;
+
+        if *dry_run || !res.problems.is_empty() {
+            return Ok(res);
+        }
+
+        let mut statements = removed
+            .iter()
+            .map(|current| TelegramActionDb::delete_statement(&ctx.env, &uid, &current.id))
+            .collect::<ApiResult<Vec<_>>>()?;
+
+        for (id, destination_id, action) in changes {
+            statements.push(match id {
+                Some(id) => TelegramActionDb::update_statement(
+                    &ctx.env,
+                    &uid,
+                    &id,
+                    &destination_id,
+                    action.prompt.trim(),
+                    &action.message,
+                    action.parse_mode,
+                    action.enabled,
+                )?,
+                None => TelegramActionDb::insert_statement(
+                    &ctx.env,
+                    &ActionId::new(uuid::Uuid::now_v7()),
+                    &destination_id,
+                    action.prompt.trim(),
+                    &action.message,
+                    action.parse_mode,
+                    action.enabled,
+                )?,
+            });
+        }
+
+        // all at once, so a replace never leaves the user with only some of their actions
+        run_batch(&ctx.env, statements).await?;
+
+        res.applied = true;
+
+        Ok(res)
+    }
+}
+
+impl FromHttpRequest for ImportConfigRequest {}
+
+// the webhook ignores case when matching, so these would always trigger together
+fn same_prompt(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+fn encode_config(file: &ConfigFile, format: ConfigFormat) -> ApiResult<String> {
+    match format {
+        ConfigFormat::Json => {
+            serde_json::to_string_pretty(file).map_err(|err| ApiError::Parse(err.to_string()))
+        }
+        ConfigFormat::Yaml => {
+            serde_yaml::to_string(file).map_err(|err| ApiError::Parse(err.to_string()))
+        }
+    }
+}
+
+fn decode_config(content: &str, format: ConfigFormat) -> ApiResult<ConfigFile> {
+    match format {
+        ConfigFormat::Json => serde_json::from_str(content).map_err(|err| err.to_string()),
+        ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|err| err.to_string()),
+    }
+    .map_err(|err| ApiError::Validation(format!("couldn't read the config file: {err}")))
+}
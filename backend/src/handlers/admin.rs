@@ -6,6 +6,8 @@ use crate::{
 };
 use admin::{
     AdminPopulateFakeUser, AdminPopulateFakeUserRequest, AdminPopulateFakeUserResponse,
+    AdminTelegramDeleteWebHook, AdminTelegramGetCommands, AdminTelegramGetCommandsResponse,
+    AdminTelegramPollUpdates, AdminTelegramPollUpdatesResponse, AdminTelegramSetCommands,
     AdminTelegramSetWebHook,
 };
 use async_trait::async_trait;
@@ -15,7 +17,10 @@ use shared::{
 };
 use worker::HttpRequest;
 
-use super::auth::register;
+use super::{
+    auth::register,
+    telegram::{get_bot_commands, poll_updates, set_bot_commands},
+};
 
 #[async_trait(?Send)]
 impl ApiEmptyExt for AdminTelegramSetWebHook {
@@ -29,6 +34,40 @@ impl ApiEmptyExt for AdminTelegramSetWebHook {
     }
 }
 
+#[async_trait(?Send)]
+impl ApiEmptyExt for AdminTelegramDeleteWebHook {
+    async fn handle(ctx: &ApiContext<HttpRequest>) -> ApiResult<()> {
+        TelegramBot::new(&ctx.env).delete_webhook().await
+    }
+}
+
+#[async_trait(?Send)]
+impl ApiEmptyExt for AdminTelegramSetCommands {
+    async fn handle(ctx: &ApiContext<HttpRequest>) -> ApiResult<()> {
+        set_bot_commands(&TelegramBot::new(&ctx.env)).await
+    }
+}
+
+#[async_trait(?Send)]
+impl ApiResExt for AdminTelegramGetCommands {
+    type Res = AdminTelegramGetCommandsResponse;
+
+    async fn handle(ctx: &ApiContext<HttpRequest>) -> ApiResult<AdminTelegramGetCommandsResponse> {
+        Ok(AdminTelegramGetCommandsResponse {
+            commands: get_bot_commands(&TelegramBot::new(&ctx.env)).await?,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl ApiResExt for AdminTelegramPollUpdates {
+    type Res = AdminTelegramPollUpdatesResponse;
+
+    async fn handle(ctx: &ApiContext<HttpRequest>) -> ApiResult<AdminTelegramPollUpdatesResponse> {
+        poll_updates(&ctx.env).await
+    }
+}
+
 #[async_trait(?Send)]
 impl ApiBothExt for AdminPopulateFakeUser {
     type Req = AdminPopulateFakeUserRequest;
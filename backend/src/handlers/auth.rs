@@ -1,17 +1,23 @@
 use crate::{
     any_to_json_response,
     api_ext::*,
-    config::{AUTH_TOKEN_SIGNIN_EXPIRES, ENV_KEY_TELEGRAM_AUTH_TOKEN},
+    config::{
+        AUTH_TOKEN_SIGNIN_EXPIRES, ENV_KEY_TELEGRAM_AUTH_TOKEN, ENV_KEY_TELEGRAM_BOT_TOKEN,
+        TELEGRAM_MINI_APP_INIT_DATA_MAX_AGE_SECS,
+    },
     db::user::{OmiAccount, TelegramAccount, UserAccount},
     empty_response,
-    kv::auth::AuthKv,
+    handlers::telegram::create_deep_link,
+    helpers::run_batch,
+    kv::auth::{AuthKv, DeepLinkPayload},
     ApiContext,
 };
 use async_trait::async_trait;
 use auth::{
-    AuthCheck, AuthCheckResponse, AuthRegister, AuthRegisterRequest, AuthRegisterResponse,
+    AuthCheck, AuthCheckResponse, AuthMiniAppSignin, AuthMiniAppSigninRequest, AuthRegister,
+    AuthRegisterDeepLink, AuthRegisterDeepLinkRequest, AuthRegisterRequest, AuthRegisterResponse,
     AuthSignin, AuthSigninRequest, AuthSigninResponse, AuthSignout, AuthSignoutRequest,
-    AuthTokenCreateResponse, AuthTokenKind,
+    AuthTokenCreateResponse, AuthTokenKind, DeepLinkResponse,
 };
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
@@ -21,7 +27,7 @@ use shared::{
     backend::result::{ApiError, ApiResult, AuthError},
     user::UserId,
 };
-use worker::{Env, HttpRequest, HttpResponse};
+use worker::{Date, Env, HttpRequest, HttpResponse, Url};
 
 // Register
 #[async_trait(?Send)]
@@ -75,12 +81,7 @@ pub async fn register(
     omi_uid: &str,
     tg_uid: i64,
 ) -> ApiResult<(AuthRegisterResponse, AuthTokenCreateResponse)> {
-    // Register in database
-    let uid = UserId::new(uuid::Uuid::now_v7());
-    let user_token = uuid::Uuid::now_v7().as_simple().to_string();
-    UserAccount::insert(&env, &uid, &user_token).await?;
-    OmiAccount::insert(&env, &omi_uid, &uid).await?;
-    TelegramAccount::insert(&env, tg_uid, &uid).await?;
+    let (uid, user_token) = create_accounts(env, omi_uid, tg_uid).await?;
 
     // Log user in
     let auth_token = AuthKv::create(
@@ -96,6 +97,65 @@ pub async fn register(
     Ok((AuthRegisterResponse { uid, auth_key }, auth_token))
 }
 
+// Register in database, without logging in (e.g. when it happens from the bot)
+// returns the new user id and user token
+pub async fn create_accounts(env: &Env, omi_uid: &str, tg_uid: i64) -> ApiResult<(UserId, String)> {
+    let uid = UserId::new(uuid::Uuid::now_v7());
+    let user_token = uuid::Uuid::now_v7().as_simple().to_string();
+
+    // all or nothing, a partial registration would block the omi id from registering again
+    run_batch(
+        env,
+        vec![
+            UserAccount::insert_statement(env, &uid, &user_token)?,
+            OmiAccount::insert_statement(env, omi_uid, &uid)?,
+            TelegramAccount::insert_statement(env, tg_uid, &uid)?,
+        ],
+    )
+    .await
+    .map_err(|err| match err {
+        // checked beforehand too, but it can still race
+        ApiError::AlreadyExists(column) if column == "omi_account.id" => {
+            AuthError::OmiIdAlreadyExists.into()
+        }
+        ApiError::AlreadyExists(column) if column == "telegram_account.id" => {
+            AuthError::TelegramIdAlreadyExists.into()
+        }
+        err => err,
+    })?;
+
+    Ok((uid, user_token))
+}
+
+// Register via deep link
+#[async_trait(?Send)]
+impl ApiBothExt for AuthRegisterDeepLink {
+    type Req = <Self as ApiBoth>::Req;
+    type Res = <Self as ApiBoth>::Res;
+
+    async fn handle(ctx: &ApiContext<AuthRegisterDeepLinkRequest>) -> ApiResult<DeepLinkResponse> {
+        let omi_uid = ctx.req.omi_uid.trim();
+
+        if omi_uid.is_empty() {
+            return Err(ApiError::Validation("omi id cannot be empty".to_string()));
+        }
+
+        if OmiAccount::exists(&ctx.env, omi_uid).await? {
+            return Err(AuthError::OmiIdAlreadyExists.into());
+        }
+
+        create_deep_link(
+            &ctx.env,
+            DeepLinkPayload::Register {
+                omi_uid: omi_uid.to_string(),
+            },
+        )
+        .await
+    }
+}
+
+impl FromHttpRequest for AuthRegisterDeepLinkRequest {}
+
 // Signin
 #[async_trait(?Send)]
 impl ApiBothWithExtraExt for AuthSignin {
@@ -114,27 +174,7 @@ impl ApiBothWithExtraExt for AuthSignin {
 
         validate_telegram_login(&ctx.env, data_check, data_check_hash)?;
 
-        let tg_account = TelegramAccount::load(&ctx.env, *tg_uid).await?;
-        let user = UserAccount::load(&ctx.env, &tg_account.user_id).await?;
-
-        // Log user in
-        let auth_token = AuthKv::create(
-            &ctx.env,
-            AuthTokenKind::Signin,
-            user.id.clone(),
-            user.user_token.clone(),
-            AUTH_TOKEN_SIGNIN_EXPIRES,
-        )
-        .await?;
-        let auth_key = auth_token.key.clone();
-
-        Ok((
-            AuthSigninResponse {
-                uid: user.id,
-                auth_key,
-            },
-            auth_token,
-        ))
+        signin(&ctx.env, *tg_uid).await
     }
 
     async fn response(
@@ -150,6 +190,61 @@ impl ApiBothWithExtraExt for AuthSignin {
 
 impl FromHttpRequest for AuthSigninRequest {}
 
+// Signin from the Mini App
+#[async_trait(?Send)]
+impl ApiBothWithExtraExt for AuthMiniAppSignin {
+    type Req = <Self as ApiBoth>::Req;
+    type Res = <Self as ApiBoth>::Res;
+    type Extra = AuthTokenCreateResponse;
+
+    async fn handle(
+        ctx: &ApiContext<AuthMiniAppSigninRequest>,
+    ) -> ApiResult<(AuthSigninResponse, AuthTokenCreateResponse)> {
+        let tg_uid = validate_telegram_web_app(&ctx.env, &ctx.req.init_data)?;
+
+        signin(&ctx.env, tg_uid).await
+    }
+
+    async fn response(
+        _ctx: &ApiContext<AuthMiniAppSigninRequest>,
+        data: AuthSigninResponse,
+        auth_token: AuthTokenCreateResponse,
+    ) -> HttpResponse {
+        let mut res = any_to_json_response(&data, None).await;
+        set_signin_cookie(&mut res, &auth_token.id);
+        res
+    }
+}
+
+impl FromHttpRequest for AuthMiniAppSigninRequest {}
+
+async fn signin(
+    env: &Env,
+    tg_uid: i64,
+) -> ApiResult<(AuthSigninResponse, AuthTokenCreateResponse)> {
+    let tg_account = TelegramAccount::load(env, tg_uid).await?;
+    let user = UserAccount::load(env, &tg_account.user_id).await?;
+
+    // Log user in
+    let auth_token = AuthKv::create(
+        env,
+        AuthTokenKind::Signin,
+        user.id.clone(),
+        user.user_token.clone(),
+        AUTH_TOKEN_SIGNIN_EXPIRES,
+    )
+    .await?;
+    let auth_key = auth_token.key.clone();
+
+    Ok((
+        AuthSigninResponse {
+            uid: user.id,
+            auth_key,
+        },
+        auth_token,
+    ))
+}
+
 // Signout
 #[async_trait(?Send)]
 impl ApiReqExt for AuthSignout {
@@ -214,6 +309,74 @@ fn validate_telegram_login(env: &Env, data_check: &str, data_check_hash: &str) -
     }
 }
 
+// returns the telegram user id that the Mini App was opened by
+fn validate_telegram_web_app(env: &Env, init_data: &str) -> ApiResult<i64> {
+    // unlike the login widget, the Mini App is opened from our bot itself (i.e. the dev bot in dev)
+    let bot_token = env.secret(ENV_KEY_TELEGRAM_BOT_TOKEN).unwrap().to_string();
+
+    // initData is a url-encoded query string, let Url do the decoding
+    let url = Url::parse(&format!("https://localhost/?{init_data}"))
+        .map_err(|err| ApiError::Parse(err.to_string()))?;
+
+    let mut hash = None;
+    let mut auth_date = None;
+    let mut user = None;
+    let mut pairs = Vec::new();
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "hash" => hash = Some(value.to_string()),
+            "auth_date" => auth_date = value.parse::<u64>().ok(),
+            "user" => user = Some(value.to_string()),
+            _ => {}
+        }
+
+        if key != "hash" {
+            pairs.push(format!("{key}={value}"));
+        }
+    }
+
+    let hash = hash.ok_or(ApiError::Auth(AuthError::InvalidSignin))?;
+
+    // Validate the hash (https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app)
+    pairs.sort();
+    let data_check = pairs.join("\n");
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(b"WebAppData").expect("HMAC can take key of any size");
+    mac.update(bot_token.as_bytes());
+    let secret_key = mac.finalize().into_bytes();
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(&secret_key).expect("HMAC can take key of any size");
+    mac.update(data_check.as_bytes());
+    let computed_hash = hex::encode(mac.finalize().into_bytes());
+
+    if computed_hash != hash {
+        tracing::warn!("mini app hash is invalid {computed_hash} vs. {hash}");
+        return Err(ApiError::Auth(AuthError::NotAuthorized));
+    }
+
+    // the hash never expires by itself, so a leaked initData would otherwise be good forever
+    let now_secs = Date::now().as_millis() / 1000;
+    match auth_date {
+        Some(auth_date)
+            if now_secs.saturating_sub(auth_date) <= TELEGRAM_MINI_APP_INIT_DATA_MAX_AGE_SECS => {}
+        _ => return Err(ApiError::Auth(AuthError::InvalidSignin)),
+    }
+
+    #[derive(serde::Deserialize)]
+    struct WebAppUser {
+        id: i64,
+    }
+
+    let user: WebAppUser =
+        serde_json::from_str(&user.ok_or(ApiError::Auth(AuthError::InvalidSignin))?)
+            .map_err(|err| ApiError::Parse(err.to_string()))?;
+
+    Ok(user.id)
+}
+
 #[cfg(debug_assertions)]
 pub fn set_signin_cookie(res: &mut HttpResponse, auth_token_id: &str) {
     let value = format!("{HEADER_AUTH_TOKEN_ID}={auth_token_id}; Path=/; HttpOnly; Secure; Partitioned; SameSite=None; Max-Age=2147483647");
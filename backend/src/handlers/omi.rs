@@ -1,17 +1,26 @@
 use std::{future::Future, pin::Pin};
 
 use async_trait::async_trait;
-use shared::api::{
-    omi::{OmiHookError, OmiPayload, OmiWebHook, OmiWebHookRequest},
-    ApiReq,
+use shared::{
+    api::{
+        action::{DeliverySkipReason, DeliveryStatus},
+        omi::{OmiHookError, OmiPayload, OmiSegment, OmiWebHook, OmiWebHookRequest},
+        telegram::TelegramBotError,
+        ApiReq,
+    },
+    user::UserId,
 };
 
 use crate::{
     api_ext::{ApiReqExt, FromHttpRequest},
+    config::{PAUSE_DEFAULT_SECS, PAUSE_PHRASE},
     db::{
-        action::TelegramActionDb,
-        user::{OmiAccount, TelegramAccount},
+        action::{ActionWithDestinationDb, TelegramActionDb},
+        delivery::DeliveryDb,
+        destination::DestinationDb,
+        user::{OmiAccount, TelegramAccount, UserAccount},
     },
+    destination::{lookup, Delivery},
     prelude::*,
     telegram::TelegramBot,
 };
@@ -25,33 +34,8 @@ impl ApiReqExt for OmiWebHook {
             return Ok(());
         }
 
-        let (actions_to_send, user_id) = match OmiAccount::load(&ctx.env, &ctx.req.omi_uid).await {
-            Ok(omi_account) => {
-                let user_id = omi_account.user_id.clone();
-                let mut actions_to_send = Vec::new();
-                match TelegramActionDb::list(&ctx.env, &omi_account.user_id).await {
-                    Ok(actions) => {
-                        for action in actions {
-                            let hit = ctx.req.payload.segments.iter().any(|segment| {
-                                segment
-                                    .text
-                                    .to_lowercase()
-                                    .contains(&action.prompt.to_lowercase())
-                            });
-                            if hit {
-                                actions_to_send.push(action);
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        return Err(ApiError::Omi(OmiHookError::NoActions(
-                            ctx.req.omi_uid.clone(),
-                        )));
-                    }
-                }
-
-                (actions_to_send, user_id)
-            }
+        let user_id = match OmiAccount::load(&ctx.env, &ctx.req.omi_uid).await {
+            Ok(omi_account) => omi_account.user_id,
             Err(_) => {
                 return Err(ApiError::Omi(OmiHookError::NoSuchUser(
                     ctx.req.omi_uid.clone(),
@@ -59,24 +43,65 @@ impl ApiReqExt for OmiWebHook {
             }
         };
 
+        // nothing else in a transcript that pauses is sent
+        if has_pause_phrase(&ctx.req.payload.segments) {
+            return pause_from_phrase(&ctx.env, &user_id).await;
+        }
+
+        // checked before matching, and not recorded in the delivery history
+        // a device keeps sending transcripts while paused, which would bury everything else there
+        if UserAccount::paused_until(&ctx.env, &user_id)
+            .await?
+            .is_some()
+        {
+            tracing::info!("User {} is paused, ignoring transcript", user_id);
+            return Ok(());
+        }
+
+        let actions = match TelegramActionDb::list_with_destinations(&ctx.env, &user_id).await {
+            Ok(actions) => actions,
+            Err(_) => {
+                return Err(ApiError::Omi(OmiHookError::NoActions(
+                    ctx.req.omi_uid.clone(),
+                )));
+            }
+        };
+
+        // disabled actions aren't recorded either, for the same reason
+        let actions_to_send = actions
+            .into_iter()
+            .filter(|action| {
+                bool::from(action.enabled)
+                    && matching_segment(action, &ctx.req.payload.segments).is_some()
+            })
+            .collect::<Vec<_>>();
+
         if !actions_to_send.is_empty() {
-            let tg_bot = TelegramBot::new(&ctx.env);
             let tg_user = TelegramAccount::load_by_user_id(&ctx.env, &user_id).await?;
 
+            let transcript = join_transcript(&ctx.req.payload.segments);
+
+            // one failing destination doesn't stop the rest, the first error is returned at the end
+            let mut first_err = None;
+
             for action in actions_to_send {
-                let message = match &tg_user.username {
-                    Some(username) => format!(
-                        "message from {} (@{}): {}",
-                        tg_user.first_name, username, action.message
-                    ),
-                    None => format!("message from {}: {}", tg_user.first_name, action.message),
+                let delivery = Delivery {
+                    env: &ctx.env,
+                    prompt: &action.prompt,
+                    message: &action.msg,
+                    parse_mode: action.parse_mode,
+                    transcript: &transcript,
+                    sender: &tg_user,
+                    test: false,
                 };
 
-                tracing::info!("Sending message to user {}: {}", ctx.req.omi_uid, message);
+                if let Err(err) = deliver_action(&ctx.env, &user_id, &action, &delivery).await {
+                    first_err.get_or_insert(err);
+                }
+            }
 
-                tg_bot
-                    .send_message(action.destination.kind.chat_id(), &message)
-                    .await?;
+            if let Some(err) = first_err {
+                return Err(err);
             }
         } else {
             tracing::info!("No actions to send, payload: {:?}", ctx.req.payload);
@@ -86,6 +111,152 @@ impl ApiReqExt for OmiWebHook {
     }
 }
 
+// also used by SimulateTranscript, so a dry run matches exactly what would happen
+pub fn matching_segment<'a>(
+    action: &ActionWithDestinationDb,
+    segments: &'a [OmiSegment],
+) -> Option<&'a OmiSegment> {
+    let prompt = action.prompt.to_lowercase();
+
+    segments
+        .iter()
+        .find(|segment| segment.text.to_lowercase().contains(&prompt))
+}
+
+pub fn join_transcript(segments: &[OmiSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn has_pause_phrase(segments: &[OmiSegment]) -> bool {
+    segments
+        .iter()
+        .any(|segment| segment.text.to_lowercase().contains(PAUSE_PHRASE))
+}
+
+// only if they aren't paused already, and then the user hears back in their private chat
+// the same phrase tends to arrive in several webhook calls as the transcript grows,
+// which shouldn't keep pushing the pause further out
+async fn pause_from_phrase(env: &Env, user_id: &UserId) -> ApiResult<()> {
+    if UserAccount::paused_until(env, user_id).await?.is_some() {
+        return Ok(());
+    }
+
+    let paused_until = UserAccount::pause(env, user_id, PAUSE_DEFAULT_SECS).await?;
+
+    tracing::info!("User {} paused by phrase", user_id);
+
+    let tg_user = TelegramAccount::load_by_user_id(env, user_id).await?;
+
+    if let Err(err) = TelegramBot::new(env)
+        .send_message(
+            tg_user.id,
+            &format!(
+                "All actions are paused until {} UTC. Send /resume to resume now",
+                paused_until.unwrap_or_default()
+            ),
+        )
+        .await
+    {
+        tracing::warn!("couldn't tell user {} about the pause: {:#?}", user_id, err);
+    }
+
+    Ok(())
+}
+
+// a matched action is still skipped if this is Some
+// the webhook never gets as far as Paused or ActionDisabled, see OmiWebHook::handle
+pub fn skip_reason(action: &ActionWithDestinationDb, paused: bool) -> Option<DeliverySkipReason> {
+    if paused {
+        Some(DeliverySkipReason::Paused)
+    } else if !bool::from(action.enabled) {
+        Some(DeliverySkipReason::ActionDisabled)
+    } else if !bool::from(action.active) {
+        Some(DeliverySkipReason::DestinationInactive)
+    } else if !bool::from(action.consented) {
+        Some(DeliverySkipReason::NotConsented)
+    } else {
+        None
+    }
+}
+
+// sends a triggered (or test) action and records it in the delivery history
+pub async fn deliver_action(
+    env: &Env,
+    user_id: &UserId,
+    action: &ActionWithDestinationDb,
+    delivery: &Delivery<'_>,
+) -> ApiResult<DeliveryStatus> {
+    let destination = lookup(action.kind);
+
+    // a pause is for triggered actions, which don't get here while paused
+    let skipped = skip_reason(action, false).map(|reason| reason.description());
+
+    let (text, result) = match (destination.render(delivery), skipped) {
+        (Err(err), _) => (String::new(), Err(err)),
+        (Ok(text), Some(reason)) => {
+            tracing::info!("Skipping action {}, {}", action.id, reason);
+            (text, Ok(None))
+        }
+        (Ok(text), None) => {
+            tracing::info!(
+                "Delivering action {} for user {} to {}",
+                action.id,
+                user_id,
+                action.kind.as_str()
+            );
+
+            let result = destination
+                .deliver(&action.config()?, delivery, &text)
+                .await;
+            (text, result)
+        }
+    };
+
+    let (message_id, status, error) = match (&result, skipped) {
+        (Ok(message_id), None) => (*message_id, DeliveryStatus::Sent, None),
+        (Ok(_), Some(reason)) => (None, DeliveryStatus::Skipped, Some(reason.to_string())),
+        (Err(err), _) => (None, DeliveryStatus::Failed, Some(err.to_string())),
+    };
+
+    if let Err(err) = DeliveryDb::insert(
+        env,
+        user_id,
+        &action.id,
+        &action.destination_id,
+        &text,
+        message_id,
+        status,
+        error.as_deref(),
+    )
+    .await
+    {
+        tracing::warn!(
+            "failed to record delivery of action {}: {:#?}",
+            action.id,
+            err
+        );
+    }
+
+    match result {
+        Ok(_) => Ok(status),
+        // same as a my_chat_member update telling us we were removed
+        Err(ApiError::Telegram(TelegramBotError::Forbidden(reason))) => {
+            tracing::warn!(
+                "Destination {} is unreachable, marking inactive: {}",
+                action.destination_id,
+                reason
+            );
+            DestinationDb::set_active(env, &action.destination_id, false).await?;
+            Ok(DeliveryStatus::Failed)
+        }
+        Err(err) => Err(err),
+    }
+}
+
 impl FromHttpRequest for OmiWebHookRequest {
     fn from_request(
         _env: worker::Env,
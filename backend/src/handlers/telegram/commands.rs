@@ -0,0 +1,149 @@
+// What the bot advertises in Telegram's command menu, its profile descriptions, and the Mini App button
+// keep in sync with the parsing in shared::api::telegram::TelegramBotCommand
+use shared::{
+    api::telegram::{TelegramCommandInfo, TelegramCommandScope, TelegramScopedCommands},
+    backend::result::ApiResult,
+};
+
+use crate::{config::FRONTEND_URL, context::ContentLanguage, telegram::TelegramBot};
+
+// channels don't have a command menu, "/omi link <code>" there is documented on the dashboard
+const SCOPES: [TelegramCommandScope; 2] = [
+    TelegramCommandScope::AllPrivateChats,
+    TelegramCommandScope::AllGroupChats,
+];
+
+pub async fn set_bot_commands(tg: &TelegramBot) -> ApiResult<()> {
+    for lang in ContentLanguage::all() {
+        let language_code = lang.telegram_language_code();
+
+        for scope in SCOPES {
+            tg.set_my_commands(&commands(scope, lang), scope, language_code)
+                .await?;
+        }
+
+        tg.set_my_description(&description(lang), language_code)
+            .await?;
+        tg.set_my_short_description(short_description(lang), language_code)
+            .await?;
+    }
+
+    // the menu button isn't per-language
+    // Mini Apps must be served over https, so this is skipped for the local dev frontend
+    if FRONTEND_URL.starts_with("https://") {
+        tg.set_chat_menu_button_web_app("Dashboard", &format!("{FRONTEND_URL}/dashboard"))
+            .await?;
+    }
+
+    Ok(())
+}
+
+// what's actually registered right now, which may differ from the above if it wasn't set yet
+pub async fn get_bot_commands(tg: &TelegramBot) -> ApiResult<Vec<TelegramScopedCommands>> {
+    let mut all = Vec::new();
+
+    for lang in ContentLanguage::all() {
+        let language_code = lang.telegram_language_code();
+
+        for scope in SCOPES {
+            all.push(TelegramScopedCommands {
+                scope,
+                language_code: language_code.map(|code| code.to_string()),
+                commands: tg.get_my_commands(scope, language_code).await?,
+            });
+        }
+    }
+
+    Ok(all)
+}
+
+fn commands(scope: TelegramCommandScope, lang: ContentLanguage) -> Vec<TelegramCommandInfo> {
+    let commands: &[(&str, &str, &str)] = match scope {
+        // (command, english, hebrew)
+        TelegramCommandScope::AllPrivateChats => &[
+            (
+                "start",
+                "Get started with Omi Assist",
+                "תחילת עבודה עם Omi Assist",
+            ),
+            (
+                "link",
+                "Link this chat to receive your actions",
+                "קישור הצ'אט הזה לקבלת הפעולות שלך",
+            ),
+            (
+                "linkchannel",
+                "Get a code for linking a channel you admin",
+                "קבלת קוד לקישור ערוץ שאתם מנהלים",
+            ),
+            (
+                "unlink",
+                "Unlink this chat and remove its actions",
+                "ניתוק הצ'אט הזה ומחיקת הפעולות שלו",
+            ),
+            ("actions", "List your actions", "הצגת הפעולות שלך"),
+            ("addaction", "Create a new action", "יצירת פעולה חדשה"),
+            (
+                "delete",
+                "Delete an action by its number, e.g. /delete 2",
+                "מחיקת פעולה לפי המספר שלה, למשל /delete 2",
+            ),
+            ("destinations", "List your destinations", "הצגת היעדים שלך"),
+            (
+                "cancel",
+                "Cancel the current command",
+                "ביטול הפקודה הנוכחית",
+            ),
+            (
+                "stop",
+                "Stop receiving alerts from people who invited you",
+                "הפסקת קבלת התראות מאנשים שהזמינו אותך",
+            ),
+            (
+                "pause",
+                "Pause all your actions, e.g. /pause 2 for two hours",
+                "השהיית כל הפעולות שלך, למשל /pause 2 לשעתיים",
+            ),
+            (
+                "resume",
+                "Resume your paused actions",
+                "חידוש הפעולות שהושהו",
+            ),
+        ],
+        // group commands can't have spaces, so "/omi link" and "/omi unlink" share an entry
+        _ => &[(
+            "omi",
+            "\"/omi link\" sends your actions here, \"/omi unlink\" stops them",
+            "\"/omi link\" שולח את הפעולות שלך לכאן, \"/omi unlink\" מפסיק אותן",
+        )],
+    };
+
+    commands
+        .iter()
+        .map(|(command, en, he)| TelegramCommandInfo {
+            command: command.to_string(),
+            description: match lang {
+                ContentLanguage::English => en.to_string(),
+                ContentLanguage::Hebrew => he.to_string(),
+            },
+        })
+        .collect()
+}
+
+fn description(lang: ContentLanguage) -> String {
+    match lang {
+        ContentLanguage::English => format!(
+            "Omi Assist sends messages here when your Omi conversations match a prompt.\n\nUse /link to connect this chat, then set up your actions at {FRONTEND_URL}"
+        ),
+        ContentLanguage::Hebrew => format!(
+            "Omi Assist שולח לכאן הודעות כשהשיחות שלך ב-Omi תואמות להנחיה.\n\nהשתמשו ב-/link כדי לחבר את הצ'אט הזה, ואז הגדירו את הפעולות שלכם ב-{FRONTEND_URL}"
+        ),
+    }
+}
+
+fn short_description(lang: ContentLanguage) -> &'static str {
+    match lang {
+        ContentLanguage::English => "Actions triggered by your Omi conversations",
+        ContentLanguage::Hebrew => "פעולות שמופעלות על ידי השיחות שלך ב-Omi",
+    }
+}
@@ -0,0 +1,182 @@
+// t.me/bot?start=<token> and t.me/bot?startgroup=<token> links
+// the token is one-time, see AuthKv::create_deep_link
+use shared::{
+    api::{
+        auth::{AuthTokenKind, DeepLinkResponse},
+        telegram::{self, TelegramBotError, TelegramMessage, TelegramWebHookRequest},
+    },
+    backend::result::{ApiError, ApiResult},
+};
+use worker::Env;
+
+use crate::{
+    config::{DEEP_LINK_EXPIRES_SECS, FRONTEND_URL},
+    db::user::{OmiAccount, TelegramAccount},
+    destination::TelegramGroupDestination,
+    handlers::auth::create_accounts,
+    kv::auth::{AuthKv, DeepLinkPayload},
+    telegram::TelegramBot,
+    ApiContext,
+};
+
+use super::{
+    invitation::handle_invitation_start,
+    link::{check_group_link, group_destination_name, link_destination},
+};
+
+pub async fn create_deep_link(env: &Env, payload: DeepLinkPayload) -> ApiResult<DeepLinkResponse> {
+    // the dev and prod bots have different usernames, so ask rather than configure it
+    let username = TelegramBot::new(env)
+        .get_me()
+        .await?
+        .username
+        .ok_or_else(|| {
+            ApiError::Telegram(TelegramBotError::Internal(
+                "bot has no username".to_string(),
+            ))
+        })?;
+
+    let start = match payload {
+        DeepLinkPayload::LinkGroup { .. } => "startgroup",
+        DeepLinkPayload::Register { .. } | DeepLinkPayload::InviteContact { .. } => "start",
+        DeepLinkPayload::LinkChannel { .. } => {
+            return Err("channel codes aren't links, see handle_link_channel_code".into())
+        }
+    };
+
+    let token = AuthKv::create_deep_link(env, payload, DEEP_LINK_EXPIRES_SECS).await?;
+
+    Ok(DeepLinkResponse {
+        url: format!("https://t.me/{username}?{start}={token}"),
+        expires_in_secs: DEEP_LINK_EXPIRES_SECS,
+    })
+}
+
+pub async fn handle_start_payload(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    start_param: &str,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let (token_id, payload) = match AuthKv::load_deep_link(&ctx.env, start_param).await? {
+        Some(token) => token,
+        None => {
+            tg.send_message(message.chat.id, "This link is invalid or has expired")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let from = match message.from.as_ref() {
+        Some(from) => from,
+        None => return Ok(()),
+    };
+
+    let is_private = message.chat.chat_type == telegram::TelegramChatType::Private;
+
+    match payload {
+        DeepLinkPayload::Register { omi_uid } => {
+            if !is_private {
+                tg.send_message(
+                    message.chat.id,
+                    "This link only works in a private chat with the bot",
+                )
+                .await?;
+                return Ok(());
+            }
+
+            if TelegramAccount::exists(&ctx.env, from.id).await? {
+                tg.send_message(
+                    message.chat.id,
+                    &format!(
+                        "This Telegram account is already registered, sign in at {FRONTEND_URL}"
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+
+            AuthKv::delete(&ctx.env, AuthTokenKind::DeepLink, &token_id).await?;
+
+            if OmiAccount::exists(&ctx.env, &omi_uid).await? {
+                tg.send_message(message.chat.id, "This Omi account is already registered")
+                    .await?;
+                return Ok(());
+            }
+
+            create_accounts(&ctx.env, &omi_uid, from.id).await?;
+
+            // we already know the real name, no need to wait for it
+            if let Err(err) = TelegramAccount::update_name(
+                &ctx.env,
+                from.id,
+                &from.first_name,
+                from.username.as_deref(),
+            )
+            .await
+            {
+                tracing::warn!("failed to update user name: {:#?}", err);
+            }
+
+            tg.send_message(
+                message.chat.id,
+                &format!("Registration complete! Send /link to receive your actions right here, and manage them at {FRONTEND_URL}"),
+            )
+            .await?;
+        }
+        DeepLinkPayload::InviteContact { invitation_id } => {
+            if !is_private {
+                tg.send_message(
+                    message.chat.id,
+                    "This link only works in a private chat with the bot",
+                )
+                .await?;
+                return Ok(());
+            }
+
+            AuthKv::delete(&ctx.env, AuthTokenKind::DeepLink, &token_id).await?;
+
+            handle_invitation_start(ctx, message, from, &invitation_id).await?;
+        }
+        DeepLinkPayload::LinkGroup { uid } => {
+            if is_private {
+                tg.send_message(
+                    message.chat.id,
+                    "This link is for adding the bot to a group",
+                )
+                .await?;
+                return Ok(());
+            }
+
+            // checked before the token is used up, so an admin can still use the same link
+            if let Some(reason) = check_group_link(ctx, &tg, message.chat.id, from.id).await {
+                tg.send_message(message.chat.id, &reason).await?;
+                return Ok(());
+            }
+
+            AuthKv::delete(&ctx.env, AuthTokenKind::DeepLink, &token_id).await?;
+
+            link_destination(
+                ctx,
+                &uid,
+                message,
+                None,
+                &TelegramGroupDestination,
+                &group_destination_name(message),
+                "This group is already linked",
+                "Group linked successfully",
+            )
+            .await?;
+        }
+        DeepLinkPayload::LinkChannel { .. } => {
+            tg.send_message(
+                message.chat.id,
+                "This code is for a channel, post \"/omi link <code>\" there",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
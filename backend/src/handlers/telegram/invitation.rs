@@ -0,0 +1,224 @@
+// a contact opens the invite link, sees who is asking and why, and accepts or declines
+// nothing is delivered to them until they accept, and /stop takes it back at any time
+use shared::{
+    api::{
+        action::{ActionDestinationId, InvitationId, InvitationStatus},
+        telegram::{
+            TelegramCallbackQuery, TelegramInlineKeyboardButton, TelegramMessage, TelegramUser,
+            TelegramWebHookRequest,
+        },
+    },
+    backend::result::ApiResult,
+};
+
+use crate::{
+    db::{destination::DestinationDb, invitation::InvitationDb, user::TelegramAccount},
+    destination::{serialize_config, TelegramConfig, TelegramDmDestination},
+    helpers::run_batch_changes,
+    telegram::TelegramBot,
+    ApiContext,
+};
+
+use super::link::dm_destination_name;
+
+const CALLBACK_ACCEPT: &str = "invitation_accept:";
+const CALLBACK_DECLINE: &str = "invitation_decline:";
+
+pub async fn handle_invitation_start(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    from: &TelegramUser,
+    invitation_id: &InvitationId,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let invitation = match InvitationDb::load(&ctx.env, invitation_id).await? {
+        Some(invitation) if invitation.status == InvitationStatus::Pending => invitation,
+        _ => {
+            tg.send_message(message.chat.id, "This invitation is no longer valid")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    InvitationDb::set_contact(
+        &ctx.env,
+        invitation_id,
+        from.id,
+        message.chat.id,
+        &dm_destination_name(from),
+    )
+    .await?;
+
+    let inviter = match TelegramAccount::load_by_user_id(&ctx.env, &invitation.user_id).await {
+        Ok(account) => match account.username {
+            Some(username) => format!("{} (@{username})", account.first_name),
+            None => account.first_name,
+        },
+        Err(_) => "An Omi Assist user".to_string(),
+    };
+
+    tg.send_message_with_buttons(
+        message.chat.id,
+        &format!(
+            "{inviter} would like to send you alerts from their Omi device.\n\nReason: {}\n\nYou can send /stop at any time to stop receiving them.",
+            invitation.reason
+        ),
+        &[
+            TelegramInlineKeyboardButton {
+                text: "Accept".to_string(),
+                callback_data: format!("{CALLBACK_ACCEPT}{invitation_id}"),
+            },
+            TelegramInlineKeyboardButton {
+                text: "Decline".to_string(),
+                callback_data: format!("{CALLBACK_DECLINE}{invitation_id}"),
+            },
+        ],
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn handle_callback_query(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    query: &TelegramCallbackQuery,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let data = query.data.as_deref().unwrap_or_default();
+
+    let (accept, invitation_id) = match (
+        data.strip_prefix(CALLBACK_ACCEPT),
+        data.strip_prefix(CALLBACK_DECLINE),
+    ) {
+        (Some(id), _) => (true, id),
+        (None, Some(id)) => (false, id),
+        (None, None) => {
+            tracing::info!("unknown callback query: {data}");
+            return tg.answer_callback_query(&query.id, None).await;
+        }
+    };
+
+    let invitation_id = match uuid::Uuid::parse_str(invitation_id) {
+        Ok(id) => InvitationId::new(id),
+        Err(_) => {
+            return tg
+                .answer_callback_query(&query.id, Some("This invitation is no longer valid"))
+                .await;
+        }
+    };
+
+    // only the contact who opened the link can answer, and only once
+    let invitation = match InvitationDb::load(&ctx.env, &invitation_id).await? {
+        Some(invitation)
+            if invitation.status == InvitationStatus::Pending
+                && invitation.tg_uid == Some(query.from.id) =>
+        {
+            invitation
+        }
+        _ => {
+            return tg
+                .answer_callback_query(&query.id, Some("This invitation is no longer valid"))
+                .await;
+        }
+    };
+
+    let chat_id = invitation.chat_id.unwrap_or(query.from.id);
+
+    let text = if accept {
+        // they may have been linked by this user before, and then /stop'd
+        let existing = DestinationDb::find_by_user_telegram_chat_id(
+            &ctx.env,
+            &invitation.user_id,
+            chat_id,
+            None,
+        )
+        .await?;
+
+        // the destination and the invitation change together, or not at all
+        let statements = match existing {
+            Some(existing) => vec![
+                DestinationDb::set_active_statement(&ctx.env, &existing.id, true)?,
+                DestinationDb::set_consented_statement(&ctx.env, &existing.id, true)?,
+                InvitationDb::accept_statement(&ctx.env, &invitation_id, &existing.id)?,
+            ],
+            None => {
+                let config = serialize_config(&TelegramConfig {
+                    chat_id,
+                    message_thread_id: None,
+                })?;
+
+                let destination_id = ActionDestinationId::new(uuid::Uuid::now_v7());
+                vec![
+                    InvitationDb::insert_destination_statement(
+                        &ctx.env,
+                        &invitation_id,
+                        &destination_id,
+                        &invitation.user_id,
+                        &invitation
+                            .contact_name
+                            .clone()
+                            .unwrap_or_else(|| dm_destination_name(&query.from)),
+                        &TelegramDmDestination,
+                        &config,
+                    )?,
+                    InvitationDb::accept_statement(&ctx.env, &invitation_id, &destination_id)?,
+                ]
+            }
+        };
+
+        // accept_statement is last, and changes nothing if this was answered in the meantime
+        // (e.g. a double tap), in which case the destination wasn't created a second time either
+        let changes = run_batch_changes(&ctx.env, statements).await?;
+
+        if changes.last().copied().unwrap_or_default() == 0 {
+            return tg
+                .answer_callback_query(&query.id, Some("This invitation is no longer valid"))
+                .await;
+        }
+
+        "Invitation accepted, you'll now receive their alerts here. Send /stop at any time to stop."
+    } else {
+        if !InvitationDb::decline(&ctx.env, &invitation_id).await? {
+            return tg
+                .answer_callback_query(&query.id, Some("This invitation is no longer valid"))
+                .await;
+        }
+
+        "Invitation declined, you won't receive any alerts from them."
+    };
+
+    tg.answer_callback_query(&query.id, None).await?;
+
+    match query.message.as_ref() {
+        Some(message) => {
+            tg.edit_message_text(message.chat.id, message.message_id, text)
+                .await?
+        }
+        None => {
+            tg.send_message(chat_id, text).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_stop(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let revoked = InvitationDb::revoke_by_chat_id(&ctx.env, message.chat.id).await?;
+
+    let text = match revoked {
+        0 => "You aren't receiving alerts from anyone who invited you. To stop your own actions here, send /unlink".to_string(),
+        1 => "Stopped, you won't receive alerts from the person who invited you anymore".to_string(),
+        n => format!("Stopped, you won't receive alerts from the {n} people who invited you anymore"),
+    };
+
+    tg.send_message(message.chat.id, &text).await?;
+
+    Ok(())
+}
@@ -0,0 +1,442 @@
+use crate::{
+    config::{
+        CHANNEL_LINK_CODE_EXPIRES_SECS, ENV_KEY_TELEGRAM_GROUP_LINK_ALLOW_MEMBERS, FRONTEND_URL,
+    },
+    db::{destination::DestinationDb, user::TelegramAccount},
+    destination::{
+        serialize_config, Destination, TelegramChannelDestination, TelegramConfig,
+        TelegramDmDestination, TelegramGroupDestination,
+    },
+    kv::auth::{AuthKv, DeepLinkPayload},
+    telegram::TelegramBot,
+    ApiContext,
+};
+use shared::{
+    api::{
+        action::ActionDestinationId,
+        auth::AuthTokenKind,
+        telegram::{self, TelegramMessage, TelegramUser, TelegramWebHookRequest},
+    },
+    backend::result::ApiResult,
+    user::UserId,
+};
+
+pub async fn handle_link(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let from = match message.from.as_ref() {
+        Some(from) => from,
+        None => {
+            tracing::info!("link without a sender");
+            return Ok(());
+        }
+    };
+
+    // replies go back to the topic the command was sent from
+    let message_thread_id = message.topic_thread_id();
+
+    let tg_user = match TelegramAccount::load(&ctx.env, from.id).await {
+        Ok(user) => user,
+        Err(_) => {
+            let _ = tg
+                .send_thread_message(
+                    message.chat.id,
+                    message_thread_id,
+                    &format!("you need to first register an account at {FRONTEND_URL}"),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if tg_user.first_name != from.first_name || tg_user.username != from.username {
+        if let Err(err) = TelegramAccount::update_name(
+            &ctx.env,
+            tg_user.id,
+            &from.first_name,
+            from.username.as_deref(),
+        )
+        .await
+        {
+            tracing::warn!(
+                "failed to update user name: {:#?}, but it was a side-effect optimization anyway",
+                err
+            );
+        }
+    }
+
+    if message.chat.chat_type != telegram::TelegramChatType::Private {
+        if let Some(reason) = check_group_link(ctx, &tg, message.chat.id, from.id).await {
+            tg.send_thread_message(message.chat.id, message_thread_id, &reason)
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let (destination, name): (&dyn Destination, String) = match message.chat.chat_type {
+        telegram::TelegramChatType::Private => (&TelegramDmDestination, dm_destination_name(from)),
+        _ => {
+            let name = group_destination_name(message);
+            (
+                &TelegramGroupDestination,
+                match message_thread_id {
+                    Some(message_thread_id) => format!("{name} (topic {message_thread_id})"),
+                    None => name,
+                },
+            )
+        }
+    };
+
+    let (already_linked_msg, linked_msg) = match (&message.chat.chat_type, message_thread_id) {
+        (telegram::TelegramChatType::Private, _) => (
+            "You have already linked this chat",
+            "Chat linked successfully",
+        ),
+        (_, Some(_)) => (
+            "You have already linked this topic",
+            "Topic linked successfully",
+        ),
+        (_, None) => (
+            "You have already linked this group",
+            "Group linked successfully",
+        ),
+    };
+
+    link_destination(
+        ctx,
+        &tg_user.user_id,
+        message,
+        message_thread_id,
+        destination,
+        &name,
+        already_linked_msg,
+        linked_msg,
+    )
+    .await
+}
+
+// see channel_code_owner for who the channel gets linked to
+pub async fn handle_link_channel(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    code: Option<&str>,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let user_id = match channel_code_owner(ctx, &tg, message.chat.id, code).await? {
+        Ok(user_id) => user_id,
+        Err(reason) => {
+            tg.send_message(message.chat.id, &reason).await?;
+            return Ok(());
+        }
+    };
+
+    let name = match message.chat.title.clone() {
+        None => format!("Channel {}", message.chat.id),
+        Some(title) => title,
+    };
+
+    link_destination(
+        ctx,
+        &user_id,
+        message,
+        None,
+        &TelegramChannelDestination,
+        &name,
+        "This channel is already linked",
+        "Channel linked successfully",
+    )
+    .await
+}
+
+// anyone in a group can send the command, but only an admin gets to decide alerts go there
+// (unless the TELEGRAM_GROUP_LINK_ALLOW_MEMBERS var is "true")
+// and either way, the bot needs to actually be able to post
+// returns why not, to be shown in the chat
+pub(super) async fn check_group_link(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    tg: &TelegramBot,
+    chat_id: i64,
+    user_id: i64,
+) -> Option<String> {
+    let allow_members = ctx
+        .env
+        .var(ENV_KEY_TELEGRAM_GROUP_LINK_ALLOW_MEMBERS)
+        .map(|var| var.to_string() == "true")
+        .unwrap_or(false);
+
+    if !allow_members {
+        match tg.get_chat_member(chat_id, user_id).await {
+            Ok(member) if member.status.is_admin() => {}
+            Ok(_) => {
+                return Some(
+                    "Only a group admin can link this group. Ask an admin to send the command, or to make you an admin first".to_string(),
+                )
+            }
+            Err(err) => {
+                tracing::warn!("couldn't check admin rights in chat {chat_id}: {:#?}", err);
+                return Some(
+                    "Couldn't check your admin rights in this group, please try again".to_string(),
+                );
+            }
+        }
+    }
+
+    let bot = match tg.get_me().await {
+        Ok(bot) => bot,
+        Err(err) => {
+            tracing::warn!("couldn't get the bot user: {:#?}", err);
+            return Some(
+                "Couldn't check my permissions in this group, please try again".to_string(),
+            );
+        }
+    };
+
+    match tg.get_chat_member(chat_id, bot.id).await {
+        Ok(member) if !member.status.is_present() || member.can_send_messages == Some(false) => {
+            Some("I'm not allowed to post in this group. Ask an admin to let me send messages, then link again".to_string())
+        }
+        Ok(_) => None,
+        Err(err) => {
+            tracing::warn!("couldn't check bot permissions in chat {chat_id}: {:#?}", err);
+            Some("Couldn't check my permissions in this group, please try again".to_string())
+        }
+    }
+}
+
+pub(super) fn dm_destination_name(from: &TelegramUser) -> String {
+    match from.username.clone() {
+        None => from.first_name.clone(),
+        Some(username) => format!("{} (@{})", from.first_name, username),
+    }
+}
+
+pub(super) fn group_destination_name(message: &TelegramMessage) -> String {
+    match message.chat.title.clone() {
+        None => format!("Group {}", message.chat.id),
+        Some(title) => title,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn link_destination(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    user_id: &UserId,
+    message: &TelegramMessage,
+    message_thread_id: Option<i64>,
+    destination: &dyn Destination,
+    name: &str,
+    already_linked_msg: &str,
+    linked_msg: &str,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    if let Some(existing) = DestinationDb::find_by_user_telegram_chat_id(
+        &ctx.env,
+        user_id,
+        message.chat.id,
+        message_thread_id,
+    )
+    .await?
+    {
+        // linking again is a way to bring back a destination that went inactive
+        let msg = if bool::from(existing.active) {
+            already_linked_msg
+        } else {
+            DestinationDb::set_active(&ctx.env, &existing.id, true).await?;
+            linked_msg
+        };
+
+        let _ = tg
+            .send_thread_message(message.chat.id, message_thread_id, msg)
+            .await?;
+        return Ok(());
+    }
+
+    let config = serialize_config(&TelegramConfig {
+        chat_id: message.chat.id,
+        message_thread_id,
+    })?;
+
+    let destination_id = ActionDestinationId::new(uuid::Uuid::now_v7());
+    DestinationDb::insert(
+        &ctx.env,
+        &destination_id,
+        user_id,
+        name,
+        destination,
+        &config,
+    )
+    .await?;
+
+    let _ = tg
+        .send_thread_message(message.chat.id, message_thread_id, linked_msg)
+        .await?;
+
+    Ok(())
+}
+
+// sent from a private chat, gives the code for handle_link_channel (and unlinking a channel)
+pub async fn handle_link_channel_code(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let from = match message.from.as_ref() {
+        Some(from) => from,
+        None => return Ok(()),
+    };
+
+    let tg_user = match TelegramAccount::load(&ctx.env, from.id).await {
+        Ok(user) => user,
+        Err(_) => {
+            tg.send_message(
+                message.chat.id,
+                &format!("you need to first register an account at {FRONTEND_URL}"),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let code = AuthKv::create_deep_link(
+        &ctx.env,
+        DeepLinkPayload::LinkChannel {
+            uid: tg_user.user_id,
+            telegram_user_id: tg_user.id,
+        },
+        CHANNEL_LINK_CODE_EXPIRES_SECS,
+    )
+    .await?;
+
+    tg.send_message(
+        message.chat.id,
+        &format!("Post this in a channel you're an admin of, within the hour, to link it:\n\n/omi link {code}\n\nTo unlink a channel, post \"/omi unlink <code>\" with a new code instead. Each code works once"),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn handle_unlink(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    code: Option<&str>,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let message_thread_id = message.topic_thread_id();
+
+    let user_id = match (&message.chat.chat_type, message.from.as_ref()) {
+        (telegram::TelegramChatType::Channel, _) => {
+            match channel_code_owner(ctx, &tg, message.chat.id, code).await? {
+                Ok(user_id) => Some(user_id),
+                Err(reason) => {
+                    tg.send_message(message.chat.id, &reason).await?;
+                    return Ok(());
+                }
+            }
+        }
+        (_, Some(from)) => TelegramAccount::load(&ctx.env, from.id)
+            .await
+            .ok()
+            .map(|tg_user| tg_user.user_id),
+        (_, None) => None,
+    };
+
+    let destination = match user_id {
+        Some(user_id) => {
+            DestinationDb::find_by_user_telegram_chat_id(
+                &ctx.env,
+                &user_id,
+                message.chat.id,
+                message_thread_id,
+            )
+            .await?
+        }
+        None => None,
+    };
+
+    let destination = match destination {
+        Some(destination) => destination,
+        None => {
+            tg.send_thread_message(message.chat.id, message_thread_id, "Nothing to unlink here")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    // there's no way to confirm from here, so actions go along with it
+    let deleted_actions =
+        DestinationDb::delete_cascade(&ctx.env, &destination.user_id, &destination.id, true)
+            .await?;
+
+    let msg = match deleted_actions {
+        0 => "Unlinked successfully".to_string(),
+        1 => "Unlinked successfully, 1 action was removed".to_string(),
+        n => format!("Unlinked successfully, {n} actions were removed"),
+    };
+
+    tg.send_thread_message(message.chat.id, message_thread_id, &msg)
+        .await?;
+
+    Ok(())
+}
+
+// channel posts don't have a sender, so whoever posts proves who they are with a code from /linkchannel
+// which only counts if the account that asked for it is an admin of this channel
+// the code is used up either way, since it's now been posted where others can see it
+// returns why not, to be shown in the channel
+async fn channel_code_owner(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    tg: &TelegramBot,
+    chat_id: i64,
+    code: Option<&str>,
+) -> ApiResult<Result<UserId, String>> {
+    let code = match code {
+        Some(code) => code,
+        None => {
+            return Ok(Err(
+                "Send /linkchannel to me in a private chat for a code, then post it here along with the command".to_string(),
+            ))
+        }
+    };
+
+    let (token_id, uid, telegram_user_id) = match AuthKv::load_deep_link(&ctx.env, code).await? {
+        Some((
+            token_id,
+            DeepLinkPayload::LinkChannel {
+                uid,
+                telegram_user_id,
+            },
+        )) => (token_id, uid, telegram_user_id),
+        _ => {
+            return Ok(Err(
+                "This code is invalid or has expired, send /linkchannel to me for a new one"
+                    .to_string(),
+            ))
+        }
+    };
+
+    AuthKv::delete(&ctx.env, AuthTokenKind::DeepLink, &token_id).await?;
+
+    match tg.get_chat_member(chat_id, telegram_user_id).await {
+        Ok(member) if member.status.is_admin() => Ok(Ok(uid)),
+        Ok(_) => Ok(Err(
+            "Only an admin of this channel can use this code".to_string()
+        )),
+        Err(err) => {
+            tracing::warn!(
+                "couldn't check admin rights in channel {chat_id}: {:#?}",
+                err
+            );
+            Ok(Err(
+                "Couldn't check admin rights in this channel, please send /linkchannel to me for a new code and try again".to_string(),
+            ))
+        }
+    }
+}
@@ -0,0 +1,393 @@
+// Managing actions from the private chat, for when there's no browser around
+use shared::{
+    api::{
+        action::{Action, ActionDestination, ActionDestinationKind, ActionId, ParseMode},
+        telegram::{TelegramMessage, TelegramWebHookRequest},
+    },
+    backend::result::ApiResult,
+};
+
+use crate::{
+    config::{FRONTEND_URL, PAUSE_DEFAULT_SECS},
+    db::{
+        action::TelegramActionDb,
+        destination::DestinationDb,
+        user::{TelegramAccount, UserAccount},
+    },
+    kv::conversation::{Conversation, ConversationKv},
+    telegram::TelegramBot,
+    ApiContext,
+};
+
+pub async fn handle_list_actions(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let tg_user = match load_user(ctx, &tg, message).await? {
+        Some(tg_user) => tg_user,
+        None => return Ok(()),
+    };
+
+    let actions = TelegramActionDb::list(&ctx.env, &tg_user.user_id).await?;
+
+    let text = if actions.is_empty() {
+        "You don't have any actions yet, send /addaction to create one".to_string()
+    } else {
+        let mut lines = vec!["Your actions:".to_string()];
+        lines.extend(
+            actions
+                .iter()
+                .enumerate()
+                .map(|(index, action)| format_action(index, action)),
+        );
+        lines.push("Send /delete <number> to remove one".to_string());
+        lines.join("\n")
+    };
+
+    tg.send_message(message.chat.id, &text).await?;
+
+    Ok(())
+}
+
+pub async fn handle_list_destinations(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let tg_user = match load_user(ctx, &tg, message).await? {
+        Some(tg_user) => tg_user,
+        None => return Ok(()),
+    };
+
+    let destinations = DestinationDb::list(&ctx.env, &tg_user.user_id).await?;
+
+    let text = if destinations.is_empty() {
+        "You don't have any destinations yet, send /link here or \"/omi link\" in a group"
+            .to_string()
+    } else {
+        let mut lines = vec!["Your destinations:".to_string()];
+        lines.extend(destinations.iter().enumerate().map(|(index, destination)| {
+            format!("{}. {}", index + 1, format_destination(destination))
+        }));
+        lines.join("\n")
+    };
+
+    tg.send_message(message.chat.id, &text).await?;
+
+    Ok(())
+}
+
+pub async fn handle_delete_action(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    n: Option<usize>,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let tg_user = match load_user(ctx, &tg, message).await? {
+        Some(tg_user) => tg_user,
+        None => return Ok(()),
+    };
+
+    let actions = TelegramActionDb::list(&ctx.env, &tg_user.user_id).await?;
+
+    // same numbering as /actions
+    let action = match n
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|index| actions.get(index))
+    {
+        Some(action) => action,
+        None => {
+            tg.send_message(
+                message.chat.id,
+                "Usage: /delete <number>, see /actions for the numbers",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    TelegramActionDb::delete(&ctx.env, &tg_user.user_id, &action.id).await?;
+
+    tg.send_message(
+        message.chat.id,
+        &format!("Deleted action \"{}\"", action.prompt),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn handle_add_action(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let tg_user = match load_user(ctx, &tg, message).await? {
+        Some(tg_user) => tg_user,
+        None => return Ok(()),
+    };
+
+    let destinations = DestinationDb::list(&ctx.env, &tg_user.user_id).await?;
+
+    if destinations.is_empty() {
+        tg.send_message(
+            message.chat.id,
+            "You need to link a destination first, send /link here or \"/omi link\" in a group",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut lines = vec!["Which destination? Reply with the number (or /cancel):".to_string()];
+    lines.extend(
+        destinations.iter().enumerate().map(|(index, destination)| {
+            format!("{}. {}", index + 1, format_destination(destination))
+        }),
+    );
+
+    ConversationKv::save(
+        &ctx.env,
+        message.chat.id,
+        &Conversation::Destination {
+            destination_ids: destinations.into_iter().map(|d| d.id).collect(),
+        },
+    )
+    .await?;
+
+    tg.send_message(message.chat.id, &lines.join("\n")).await?;
+
+    Ok(())
+}
+
+pub async fn handle_cancel(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    ConversationKv::clear(&ctx.env, message.chat.id).await?;
+
+    TelegramBot::new(&ctx.env)
+        .send_message(message.chat.id, "Cancelled")
+        .await?;
+
+    Ok(())
+}
+
+// plain text in the private chat, only meaningful mid-conversation
+// returns false if there was no conversation to continue
+pub async fn handle_conversation_reply(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    text: &str,
+) -> ApiResult<bool> {
+    let conversation = match ConversationKv::load(&ctx.env, message.chat.id).await? {
+        Some(conversation) => conversation,
+        None => return Ok(false),
+    };
+
+    let tg = TelegramBot::new(&ctx.env);
+    let text = text.trim();
+
+    match conversation {
+        Conversation::Destination { destination_ids } => {
+            let destination_id = match text
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|index| destination_ids.get(index))
+            {
+                Some(destination_id) => destination_id.clone(),
+                None => {
+                    tg.send_message(
+                        message.chat.id,
+                        &format!(
+                            "Please reply with a number from 1 to {} (or /cancel)",
+                            destination_ids.len()
+                        ),
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+
+            ConversationKv::save(
+                &ctx.env,
+                message.chat.id,
+                &Conversation::Prompt { destination_id },
+            )
+            .await?;
+
+            tg.send_message(message.chat.id, "What phrase should trigger the action?")
+                .await?;
+        }
+        Conversation::Prompt { destination_id } => {
+            ConversationKv::save(
+                &ctx.env,
+                message.chat.id,
+                &Conversation::Message {
+                    destination_id,
+                    prompt: text.to_string(),
+                },
+            )
+            .await?;
+
+            tg.send_message(message.chat.id, "What message should be sent?")
+                .await?;
+        }
+        Conversation::Message {
+            destination_id,
+            prompt,
+        } => {
+            let tg_user = match load_user(ctx, &tg, message).await? {
+                Some(tg_user) => tg_user,
+                None => return Ok(true),
+            };
+
+            let destination =
+                DestinationDb::load_with_user_id(&ctx.env, &destination_id, &tg_user.user_id)
+                    .await?;
+
+            if let Err(err) = destination
+                .destination()
+                .validate_message(text, ParseMode::Plain)
+            {
+                tg.send_message(
+                    message.chat.id,
+                    &format!("That message won't work: {err}\nPlease try again (or /cancel)"),
+                )
+                .await?;
+                return Ok(true);
+            }
+
+            TelegramActionDb::insert(
+                &ctx.env,
+                &ActionId::new(uuid::Uuid::now_v7()),
+                &destination_id,
+                &prompt,
+                text,
+                ParseMode::Plain,
+                true,
+            )
+            .await?;
+
+            ConversationKv::clear(&ctx.env, message.chat.id).await?;
+
+            tg.send_message(
+                message.chat.id,
+                &format!("Added action \"{prompt}\" to {}", destination.name),
+            )
+            .await?;
+        }
+    }
+
+    Ok(true)
+}
+
+pub async fn handle_pause(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+    hours: Option<String>,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let tg_user = match load_user(ctx, &tg, message).await? {
+        Some(tg_user) => tg_user,
+        None => return Ok(()),
+    };
+
+    let usage = "Send /pause <hours> to pause all actions, e.g. /pause 2";
+
+    // whole hours only, anything else is most likely a typo
+    let duration_secs = match hours.as_deref().map(str::parse::<u64>) {
+        None => PAUSE_DEFAULT_SECS,
+        Some(Ok(hours)) if hours > 0 => hours.saturating_mul(60 * 60),
+        Some(_) => {
+            tg.send_message(message.chat.id, usage).await?;
+            return Ok(());
+        }
+    };
+
+    let text = match UserAccount::pause(&ctx.env, &tg_user.user_id, duration_secs).await? {
+        Some(paused_until) => {
+            format!("All actions are paused until {paused_until} UTC. Send /resume to resume now")
+        }
+        None => usage.to_string(),
+    };
+
+    tg.send_message(message.chat.id, &text).await?;
+
+    Ok(())
+}
+
+pub async fn handle_resume(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let tg_user = match load_user(ctx, &tg, message).await? {
+        Some(tg_user) => tg_user,
+        None => return Ok(()),
+    };
+
+    let text = match UserAccount::paused_until(&ctx.env, &tg_user.user_id).await? {
+        Some(_) => {
+            UserAccount::resume(&ctx.env, &tg_user.user_id).await?;
+            "Resumed, your actions will be sent again"
+        }
+        None => "Your actions aren't paused",
+    };
+
+    tg.send_message(message.chat.id, text).await?;
+
+    Ok(())
+}
+
+async fn load_user(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    tg: &TelegramBot,
+    message: &TelegramMessage,
+) -> ApiResult<Option<TelegramAccount>> {
+    let from = match message.from.as_ref() {
+        Some(from) => from,
+        None => return Ok(None),
+    };
+
+    match TelegramAccount::load(&ctx.env, from.id).await {
+        Ok(tg_user) => Ok(Some(tg_user)),
+        Err(_) => {
+            tg.send_message(
+                message.chat.id,
+                &format!("you need to first register an account at {FRONTEND_URL}"),
+            )
+            .await?;
+            Ok(None)
+        }
+    }
+}
+
+fn format_action(index: usize, action: &Action) -> String {
+    format!(
+        "{}. \"{}\" -> {}{}\n    {}",
+        index + 1,
+        action.prompt,
+        format_destination(&action.destination),
+        if action.enabled { "" } else { " (disabled)" },
+        action.message
+    )
+}
+
+fn format_destination(destination: &ActionDestination) -> String {
+    let kind = match &destination.kind {
+        ActionDestinationKind::TelegramDm { .. } => "Telegram DM",
+        ActionDestinationKind::TelegramGroup { .. } => "Telegram Group",
+        ActionDestinationKind::TelegramChannel { .. } => "Telegram Channel",
+        ActionDestinationKind::HomeAssistant { .. } => "Home Assistant",
+    };
+
+    format!("{} ({kind})", destination.name)
+}
@@ -0,0 +1,183 @@
+use std::{future::Future, pin::Pin};
+
+mod commands;
+mod deep_link;
+mod invitation;
+mod link;
+mod manage;
+mod poll;
+
+use crate::config::FRONTEND_URL;
+use crate::{
+    api_ext::*, db::destination::DestinationDb, json_body_to_any, telegram::TelegramBot, ApiContext,
+};
+use async_trait::async_trait;
+pub use commands::{get_bot_commands, set_bot_commands};
+pub use deep_link::create_deep_link;
+use deep_link::handle_start_payload;
+use invitation::{handle_callback_query, handle_stop};
+use link::{handle_link, handle_link_channel, handle_link_channel_code, handle_unlink};
+use manage::{
+    handle_add_action, handle_cancel, handle_conversation_reply, handle_delete_action,
+    handle_list_actions, handle_list_destinations, handle_pause, handle_resume,
+};
+pub use poll::poll_updates;
+use shared::{
+    api::*,
+    backend::result::{ApiError, ApiResult},
+};
+use telegram::{
+    TelegramBotCommand, TelegramBotError, TelegramChatMemberUpdated, TelegramMessage,
+    TelegramOmiCommand, TelegramWebHook, TelegramWebHookRequest,
+};
+use worker::HttpRequest;
+
+#[async_trait(?Send)]
+impl ApiReqExt for TelegramWebHook {
+    type Req = <Self as ApiReq>::Req;
+
+    async fn handle(ctx: &ApiContext<TelegramWebHookRequest>) -> ApiResult<()> {
+        if let Some(update) = ctx.req.my_chat_member.as_ref() {
+            return handle_my_chat_member(ctx, update).await;
+        }
+
+        // inline button presses, e.g. accepting an invitation
+        if let Some(query) = ctx.req.callback_query.as_ref() {
+            return handle_callback_query(ctx, query).await;
+        }
+
+        // channels only send channel_post updates, everything else is a regular message
+        match (ctx.req.message.as_ref(), ctx.req.channel_post.as_ref()) {
+            (Some(msg), _) | (None, Some(msg)) => handle_message(ctx, msg).await,
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+// the bot was added/removed/blocked/unblocked
+// destinations for that chat are kept, but only delivered to while the bot is present
+async fn handle_my_chat_member(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    update: &TelegramChatMemberUpdated,
+) -> ApiResult<()> {
+    let was_present = update.old_chat_member.status.is_present();
+    let is_present = update.new_chat_member.status.is_present();
+
+    if was_present != is_present {
+        tracing::info!(
+            "bot membership changed in chat {}: {:?} -> {:?}",
+            update.chat.id,
+            update.old_chat_member.status,
+            update.new_chat_member.status
+        );
+
+        DestinationDb::set_active_by_telegram_chat_id(&ctx.env, update.chat.id, is_present).await?;
+    }
+
+    Ok(())
+}
+
+async fn handle_message(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    msg: &TelegramMessage,
+) -> ApiResult<()> {
+    if let Some(migrate_to_chat_id) = msg.migrate_to_chat_id {
+        tracing::info!("chat {} migrated to {}", msg.chat.id, migrate_to_chat_id);
+        return DestinationDb::migrate_telegram_chat_id(&ctx.env, msg.chat.id, migrate_to_chat_id)
+            .await;
+    }
+
+    match TelegramBotCommand::try_from(msg) {
+        Ok(command) => {
+            if msg.from.as_ref().is_some_and(|from| from.is_bot) {
+                tracing::info!("ignoring bot message");
+                return Ok(());
+            }
+
+            match command {
+                TelegramBotCommand::Start(payload) => match payload {
+                    Some(payload) => handle_start_payload(ctx, msg, &payload).await,
+                    None => handle_start(ctx, msg).await,
+                },
+                TelegramBotCommand::Omi(omi_command) => match omi_command {
+                    TelegramOmiCommand::LinkDm => handle_link(ctx, msg).await,
+                    TelegramOmiCommand::LinkGroup => handle_link(ctx, msg).await,
+                    TelegramOmiCommand::LinkChannel(code) => {
+                        handle_link_channel(ctx, msg, code.as_deref()).await
+                    }
+                    TelegramOmiCommand::LinkChannelCode => handle_link_channel_code(ctx, msg).await,
+                    TelegramOmiCommand::Unlink(code) => {
+                        handle_unlink(ctx, msg, code.as_deref()).await
+                    }
+                    TelegramOmiCommand::ListActions => handle_list_actions(ctx, msg).await,
+                    TelegramOmiCommand::AddAction => handle_add_action(ctx, msg).await,
+                    TelegramOmiCommand::DeleteAction(n) => handle_delete_action(ctx, msg, n).await,
+                    TelegramOmiCommand::ListDestinations => {
+                        handle_list_destinations(ctx, msg).await
+                    }
+                    TelegramOmiCommand::Cancel => handle_cancel(ctx, msg).await,
+                    TelegramOmiCommand::Stop => handle_stop(ctx, msg).await,
+                    TelegramOmiCommand::Pause(hours) => handle_pause(ctx, msg, hours).await,
+                    TelegramOmiCommand::Resume => handle_resume(ctx, msg).await,
+                },
+            }
+        }
+        Err(err) => match err {
+            TelegramBotError::UnknownCommand(text) => {
+                tracing::info!("unknown command: {:#?}", text);
+                Ok(())
+            }
+            TelegramBotError::OmiCommand(text) => {
+                if msg.chat.chat_type == telegram::TelegramChatType::Private
+                    && handle_conversation_reply(ctx, msg, &text).await?
+                {
+                    return Ok(());
+                }
+                worker::console_warn!("omi command error: {:#?}", text);
+                Ok(())
+            }
+            TelegramBotError::UnsupportedMessage => {
+                tracing::info!("unsupported message");
+                Ok(())
+            }
+            _ => Err(ApiError::Telegram(err)),
+        },
+    }
+}
+
+impl FromHttpRequest for TelegramWebHookRequest {
+    fn from_request(
+        env: worker::Env,
+        req: HttpRequest,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<Self>>>> {
+        Box::pin(async move {
+            match req.headers().get("X-Telegram-Bot-Api-Secret-Token") {
+                None => Err(ApiError::Telegram(TelegramBotError::Unauthorized)),
+                Some(value) => {
+                    let env_secret = env.secret("TELEGRAM_WEBHOOK_SECRET").unwrap().to_string();
+
+                    if env_secret.is_empty() || value.to_str().unwrap_or_default() != &env_secret {
+                        Err(ApiError::Telegram(TelegramBotError::Unauthorized))
+                    } else {
+                        json_body_to_any::<Self>(req.into_body()).await
+                    }
+                }
+            }
+        })
+    }
+}
+
+async fn handle_start(
+    ctx: &ApiContext<TelegramWebHookRequest>,
+    message: &TelegramMessage,
+) -> ApiResult<()> {
+    let tg = TelegramBot::new(&ctx.env);
+
+    let text = format!("Welcome to Omi Assist! You can manage your settings at {FRONTEND_URL}");
+
+    let res = tg.send_message(message.chat.id, &text).await?;
+
+    tracing::info!("response id: {}", res.message_id);
+
+    Ok(())
+}
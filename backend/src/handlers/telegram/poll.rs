@@ -0,0 +1,72 @@
+// Dev-only alternative to the webhook, so the bot works locally without a public tunnel
+//
+// Each polled update goes through the exact same handler as a webhook request
+use shared::{
+    api::{
+        admin::AdminTelegramPollUpdatesResponse,
+        telegram::{TelegramBotError, TelegramWebHook},
+    },
+    backend::result::{ApiError, ApiResult},
+};
+use worker::Env;
+
+use crate::{
+    api_ext::ApiReqExt,
+    config::{TELEGRAM_POLLING_ENABLED, TELEGRAM_POLL_TIMEOUT_SECS},
+    kv::telegram_poll::TelegramPollKv,
+    telegram::TelegramBot,
+    ApiContext,
+};
+
+pub async fn poll_updates(env: &Env) -> ApiResult<AdminTelegramPollUpdatesResponse> {
+    if !TELEGRAM_POLLING_ENABLED {
+        return Err(ApiError::Validation(
+            "telegram polling is only available in dev".to_string(),
+        ));
+    }
+
+    let tg = TelegramBot::new(env);
+
+    let mut offset = TelegramPollKv::load_offset(env).await?;
+
+    let updates = match tg.get_updates(offset, TELEGRAM_POLL_TIMEOUT_SECS).await {
+        Ok(updates) => updates,
+        // a webhook is still set, which might be a live one for this bot, so it's left alone
+        Err(ApiError::Telegram(TelegramBotError::Api {
+            error_code: 409, ..
+        })) => {
+            tracing::warn!("a telegram webhook is set, not polling");
+            return Err(ApiError::Validation(
+                "a telegram webhook is set, delete it with the admin tg/delete-web-hook route to poll instead".to_string(),
+            ));
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut handled = 0;
+    let mut failed = 0;
+
+    for update in updates {
+        let update_id = update.update_id;
+        let ctx = ApiContext::new_internal(update, env.clone());
+
+        match TelegramWebHook::handle(&ctx).await {
+            Ok(_) => handled += 1,
+            Err(err) => {
+                // skipped rather than retried, a bad update shouldn't hold up the rest
+                tracing::error!("failed to handle telegram update {update_id}: {err:?}");
+                failed += 1;
+            }
+        }
+
+        // saved as we go, so a crash part way through doesn't replay what was already handled
+        TelegramPollKv::save_offset(env, update_id + 1).await?;
+        offset = Some(update_id + 1);
+    }
+
+    Ok(AdminTelegramPollUpdatesResponse {
+        handled,
+        failed,
+        offset,
+    })
+}
@@ -1,9 +1,10 @@
 use crate::{config::DB_BINDING, prelude::*};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use worker::{kv::ToRawKvValue, D1Database, D1Result, Env, State};
+use worker::{kv::ToRawKvValue, D1Database, D1PreparedStatement, D1Result, Env, State};
 
 // sqllite uses integers for booleans
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(try_from = "u32")]
 pub struct DbBool(u32);
 
 impl From<bool> for DbBool {
@@ -22,12 +23,14 @@ impl From<DbBool> for bool {
     }
 }
 
-impl From<u32> for DbBool {
-    fn from(v: u32) -> Self {
+impl TryFrom<u32> for DbBool {
+    type Error = String;
+
+    fn try_from(v: u32) -> Result<Self, Self::Error> {
         match v {
-            0 => Self(0),
-            1 => Self(1),
-            _ => panic!("invalid value for DbBool: {}", v),
+            0 => Ok(Self(0)),
+            1 => Ok(Self(1)),
+            _ => Err(format!("invalid value for DbBool: {}", v)),
         }
     }
 }
@@ -49,12 +52,43 @@ pub trait D1ResultExt {
 impl D1ResultExt for D1Result {
     fn into_result(self) -> ApiResult<()> {
         match self.error() {
-            Some(err) => Err(err.into()),
+            Some(err) => Err(ApiError::from_db_message(&err)),
             None => Ok(()),
         }
     }
 }
 
+// D1 runs a batch as a single transaction, if any statement fails none of them are applied
+// so writes that must happen together are prepared first and sent through here
+pub async fn run_batch(env: &Env, statements: Vec<D1PreparedStatement>) -> ApiResult<()> {
+    run_batch_changes(env, statements).await.map(|_| ())
+}
+
+// like run_batch, but returns how many rows each statement changed
+// for statements guarded by a WHERE, to know whether they still applied
+pub async fn run_batch_changes(
+    env: &Env,
+    statements: Vec<D1PreparedStatement>,
+) -> ApiResult<Vec<usize>> {
+    if statements.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut changes = Vec::new();
+
+    for result in get_d1(env)?.batch(statements).await? {
+        changes.push(
+            result
+                .meta()?
+                .and_then(|meta| meta.changes)
+                .unwrap_or_default(),
+        );
+        result.into_result()?;
+    }
+
+    Ok(changes)
+}
+
 pub async fn put_kv(
     env: &Env,
     namespace: &str,
@@ -70,6 +104,24 @@ pub async fn put_kv(
         .map_err(|e| ApiError::Kv(e.to_string()))
 }
 
+// KV entries are deleted automatically after ttl_secs (minimum 60)
+pub async fn put_kv_expiring(
+    env: &Env,
+    namespace: &str,
+    key: &str,
+    value: impl ToRawKvValue,
+    ttl_secs: u64,
+) -> ApiResult<()> {
+    env.kv(namespace)
+        .map_err(|e| ApiError::Kv(e.to_string()))?
+        .put(key, value)
+        .map_err(|e| ApiError::Kv(e.to_string()))?
+        .expiration_ttl(ttl_secs)
+        .execute()
+        .await
+        .map_err(|e| ApiError::Kv(e.to_string()))
+}
+
 #[allow(dead_code)]
 pub async fn try_get_kv_string(env: &Env, namespace: &str, key: &str) -> ApiResult<Option<String>> {
     env.kv(namespace)
@@ -2,6 +2,7 @@ use base64::Engine;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use shared::{
+    api::action::InvitationId,
     api::auth::{
         AuthTokenAfterValidation, AuthTokenCreateResponse, AuthTokenKind, AuthTokenValidateResponse,
     },
@@ -13,7 +14,7 @@ use worker::{Date, Env};
 
 use crate::{
     config::{AUTH_TOKEN_KEY_LENGTH, KV_BINDING_AUTH_TOKEN_SIGNIN},
-    delete_kv, get_kv_json, put_kv,
+    delete_kv, get_kv_json, put_kv, put_kv_expiring, try_get_kv_json,
 };
 
 pub struct AuthKv {}
@@ -87,6 +88,84 @@ impl AuthKv {
     }
 }
 
+// Deep link tokens
+// the /start payload is limited to 64 chars of [A-Za-z0-9_-]
+// so it's the id (32 hex chars) directly followed by the key (22 url-safe base64 chars)
+impl AuthKv {
+    pub async fn create_deep_link(
+        env: &Env,
+        payload: DeepLinkPayload,
+        expires_secs: u64,
+    ) -> ApiResult<String> {
+        let id = Uuid::now_v7().as_simple().to_string();
+        let key = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(&rand::thread_rng().gen::<[u8; AUTH_TOKEN_KEY_LENGTH]>());
+
+        let data = AuthDeepLinkTokenData {
+            kind: AuthTokenKind::DeepLink,
+            key: key.clone(),
+            payload,
+        };
+
+        // kv expiration takes care of links that are never used
+        put_kv_expiring(env, KV_BINDING_AUTH_TOKEN_SIGNIN, &id, &data, expires_secs).await?;
+
+        Ok(format!("{id}{key}"))
+    }
+
+    // doesn't consume the token, call delete() once it's actually been used
+    // so that a link opened in the wrong chat can still be used in the right one
+    // returns the token id along with the payload
+    pub async fn load_deep_link(
+        env: &Env,
+        start_param: &str,
+    ) -> ApiResult<Option<(String, DeepLinkPayload)>> {
+        const ID_LENGTH: usize = 32;
+
+        if start_param.len() <= ID_LENGTH || !start_param.is_ascii() {
+            return Ok(None);
+        }
+
+        let (id, key) = start_param.split_at(ID_LENGTH);
+
+        // anything else in this namespace (e.g. signin tokens) won't parse as a deep link
+        let token =
+            match try_get_kv_json::<AuthDeepLinkTokenData>(env, KV_BINDING_AUTH_TOKEN_SIGNIN, id)
+                .await
+            {
+                Ok(Some(token)) => token,
+                _ => return Ok(None),
+            };
+
+        if token.kind != AuthTokenKind::DeepLink || token.key != key {
+            return Ok(None);
+        }
+
+        Ok(Some((id.to_string(), token.payload)))
+    }
+}
+
+// what the deep link does once someone presses Start
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeepLinkPayload {
+    // the Telegram account isn't known yet, it's whoever opens the link
+    Register { omi_uid: String },
+    // the inviter is on the invitation, the contact is whoever opens the link
+    InviteContact { invitation_id: InvitationId },
+    LinkGroup { uid: UserId },
+    // not a t.me link, the code is posted in the channel with "/omi link <code>"
+    // telegram_user_id is who asked for it, they must be an admin of that channel
+    LinkChannel { uid: UserId, telegram_user_id: i64 },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AuthDeepLinkTokenData {
+    kind: AuthTokenKind,
+    key: String,
+    payload: DeepLinkPayload,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct AuthSigninTokenData {
     uid: UserId,
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use shared::{api::action::ActionDestinationId, backend::result::ApiResult};
+use worker::Env;
+
+use crate::{
+    config::{CONVERSATION_EXPIRES_SECS, KV_BINDING_CONVERSATION},
+    delete_kv, put_kv_expiring, try_get_kv_json,
+};
+
+// Multi-step bot commands (e.g. /addaction) need to remember where they left off
+// between webhook calls, keyed by chat
+pub struct ConversationKv {}
+
+impl ConversationKv {
+    pub async fn load(env: &Env, chat_id: i64) -> ApiResult<Option<Conversation>> {
+        try_get_kv_json(env, KV_BINDING_CONVERSATION, &Self::key(chat_id)).await
+    }
+
+    pub async fn save(env: &Env, chat_id: i64, conversation: &Conversation) -> ApiResult<()> {
+        put_kv_expiring(
+            env,
+            KV_BINDING_CONVERSATION,
+            &Self::key(chat_id),
+            conversation,
+            CONVERSATION_EXPIRES_SECS,
+        )
+        .await
+    }
+
+    pub async fn clear(env: &Env, chat_id: i64) -> ApiResult<()> {
+        delete_kv(env, KV_BINDING_CONVERSATION, &Self::key(chat_id)).await
+    }
+
+    // shares the auth token namespace, so keep the keys distinct from token ids
+    fn key(chat_id: i64) -> String {
+        format!("conversation-{chat_id}")
+    }
+}
+
+// the /addaction steps, in order
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Conversation {
+    Destination {
+        // in the order they were listed, so replies can be a number
+        destination_ids: Vec<ActionDestinationId>,
+    },
+    Prompt {
+        destination_id: ActionDestinationId,
+    },
+    Message {
+        destination_id: ActionDestinationId,
+        prompt: String,
+    },
+}
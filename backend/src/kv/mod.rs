@@ -1 +1,4 @@
 pub mod auth;
+pub mod conversation;
+pub mod rate_limit;
+pub mod telegram_poll;
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use shared::backend::result::{ApiError, ApiResult};
+use worker::{Date, Env};
+
+use crate::{config::KV_BINDING_RATE_LIMIT, put_kv_expiring, try_get_kv_json};
+
+// A fixed window per key, e.g. test sends per user
+// KV is eventually consistent, so this is a soft limit - good enough to stop spamming a chat
+pub struct RateLimitKv {}
+
+impl RateLimitKv {
+    // counts this attempt, or fails with ApiError::RateLimited if the window is used up
+    pub async fn hit(env: &Env, key: &str, max: u32, window_secs: u64) -> ApiResult<()> {
+        let key = Self::key(key);
+        let now_secs = Date::now().as_millis() / 1000;
+
+        let window =
+            match try_get_kv_json::<RateLimitWindow>(env, KV_BINDING_RATE_LIMIT, &key).await? {
+                Some(window) if window.started_at + window_secs > now_secs => window,
+                _ => RateLimitWindow {
+                    started_at: now_secs,
+                    count: 0,
+                },
+            };
+
+        let retry_after = window.started_at + window_secs - now_secs;
+
+        if window.count >= max {
+            return Err(ApiError::RateLimited(retry_after));
+        }
+
+        put_kv_expiring(
+            env,
+            KV_BINDING_RATE_LIMIT,
+            &key,
+            &RateLimitWindow {
+                count: window.count + 1,
+                ..window
+            },
+            // KV won't expire anything sooner
+            retry_after.max(60),
+        )
+        .await
+    }
+
+    // shares the auth token namespace, so keep the keys distinct from token ids
+    fn key(key: &str) -> String {
+        format!("rate-limit-{key}")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct RateLimitWindow {
+    started_at: u64,
+    count: u32,
+}
@@ -0,0 +1,20 @@
+use shared::backend::result::ApiResult;
+use worker::Env;
+
+use crate::{config::KV_BINDING_TELEGRAM_POLL, put_kv, try_get_kv_json};
+
+// The getUpdates offset for dev polling, so each update is only handled once
+pub struct TelegramPollKv {}
+
+impl TelegramPollKv {
+    // shares the auth token namespace, so keep the key distinct from token ids
+    const KEY: &'static str = "telegram-poll-offset";
+
+    pub async fn load_offset(env: &Env) -> ApiResult<Option<i64>> {
+        try_get_kv_json(env, KV_BINDING_TELEGRAM_POLL, Self::KEY).await
+    }
+
+    pub async fn save_offset(env: &Env, offset: i64) -> ApiResult<()> {
+        put_kv(env, KV_BINDING_TELEGRAM_POLL, Self::KEY, &offset).await
+    }
+}
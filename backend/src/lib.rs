@@ -3,6 +3,8 @@ mod auth;
 mod config;
 mod context;
 mod db;
+mod destination;
+mod formatter;
 mod handlers;
 mod helpers;
 mod kv;
@@ -20,7 +22,7 @@ use shared::{
     auth::{HEADER_ADMIN_CODE, HEADER_ADMIN_UID, HEADER_AUTH_TOKEN_ID, HEADER_AUTH_TOKEN_KEY},
     logger::init_logger,
 };
-use worker::{event, Context, Env};
+use worker::{event, Context, Env, ScheduleContext, ScheduledEvent};
 
 #[event(fetch, respond_with_errors)]
 async fn main(req: HttpRequest, env: Env, ctx: Context) -> worker::Result<HttpResponse> {
@@ -50,6 +52,12 @@ async fn main(req: HttpRequest, env: Env, ctx: Context) -> worker::Result<HttpRe
                         OmiHookError::NoSuchUser(_) => StatusCode::NOT_FOUND,
                         OmiHookError::NoActions(_) => StatusCode::OK,
                     },
+                    ApiError::HomeAssistant(_) => StatusCode::BAD_GATEWAY,
+                    ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+                    ApiError::DestinationInUse(_) => StatusCode::CONFLICT,
+                    ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+                    ApiError::AlreadyExists(_) => StatusCode::CONFLICT,
+                    ApiError::MissingReference => StatusCode::CONFLICT,
                     ApiError::Kv(_) => StatusCode::INTERNAL_SERVER_ERROR,
                     ApiError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
                     ApiError::Parse(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -66,6 +74,39 @@ async fn main(req: HttpRequest, env: Env, ctx: Context) -> worker::Result<HttpRe
     }
 }
 
+// the daily delivery history cleanup, and in dev, a cron trigger that stands in for the telegram webhook
+#[event(scheduled)]
+async fn scheduled(event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    init_logger();
+
+    if event.cron() == config::DELIVERY_CLEANUP_CRON {
+        let days = env
+            .var(config::ENV_KEY_DELIVERY_RETENTION_DAYS)
+            .ok()
+            .and_then(|var| var.to_string().parse().ok())
+            .unwrap_or(config::DELIVERY_RETENTION_DEFAULT_DAYS);
+
+        match db::delivery::DeliveryDb::delete_older_than(&env, days).await {
+            Ok(deleted) => tracing::info!("deleted {deleted} deliveries older than {days} days"),
+            Err(err) => tracing::error!("failed to clean up deliveries: {:?}", err),
+        }
+        return;
+    }
+
+    if !config::TELEGRAM_POLLING_ENABLED {
+        return;
+    }
+
+    match handlers::telegram::poll_updates(&env).await {
+        Ok(res) => tracing::info!(
+            "polled telegram updates: {} handled, {} failed",
+            res.handled,
+            res.failed
+        ),
+        Err(err) => tracing::error!("failed to poll telegram updates: {:?}", err),
+    }
+}
+
 fn apply_cors(origin: Option<HeaderValue>, mut res: HttpResponse) -> HttpResponse {
     let headers = res.headers_mut();
 
@@ -2,9 +2,20 @@ use crate::api_ext::*;
 use crate::{auth::AuthUser, config::API_ROOT_PATH, not_found::NotFoundHandler, prelude::*};
 use shared::{
     api::{
-        action::{AddAction, DeleteAction, ListActionDestinations, ListActions},
-        admin::{AdminPopulateFakeUser, AdminTelegramSetWebHook},
-        auth::{AuthCheck, AuthRegister, AuthSignin, AuthSignout},
+        action::{
+            AddAction, AddHomeAssistantDestination, CreateDeepLink, CreateInvitation, DeleteAction,
+            DeleteDestination, ExportConfig, GetPause, ImportConfig, ListActionDestinations,
+            ListActions, ListDeliveries, ListInvitations, PingDestination, RenameDestination,
+            SetPause, SimulateTranscript, TestAction, UpdateAction,
+        },
+        admin::{
+            AdminPopulateFakeUser, AdminTelegramDeleteWebHook, AdminTelegramGetCommands,
+            AdminTelegramPollUpdates, AdminTelegramSetCommands, AdminTelegramSetWebHook,
+        },
+        auth::{
+            AuthCheck, AuthMiniAppSignin, AuthRegister, AuthRegisterDeepLink, AuthSignin,
+            AuthSignout,
+        },
         info::ServerInfo,
         omi::OmiWebHook,
         telegram::TelegramWebHook,
@@ -25,21 +36,52 @@ pub async fn handle_route(req: HttpRequest, env: Env, cf_ctx: Context) -> ApiRes
                         AuthRoute::Register => AuthRegister::router(ctx).await?,
                         AuthRoute::Check => AuthCheck::router(ctx).await?,
                         AuthRoute::Signin => AuthSignin::router(ctx).await?,
+                        AuthRoute::MiniAppSignin => AuthMiniAppSignin::router(ctx).await?,
+                        AuthRoute::RegisterDeepLink => AuthRegisterDeepLink::router(ctx).await?,
                         AuthRoute::Signout => AuthSignout::router(ctx).await?,
                     },
                     Route::Admin(admin_route) => match admin_route {
                         AdminRoute::TelegramSetWebHook => {
                             AdminTelegramSetWebHook::router(ctx).await?
                         }
+                        AdminRoute::TelegramDeleteWebHook => {
+                            AdminTelegramDeleteWebHook::router(ctx).await?
+                        }
+                        AdminRoute::TelegramSetCommands => {
+                            AdminTelegramSetCommands::router(ctx).await?
+                        }
+                        AdminRoute::TelegramGetCommands => {
+                            AdminTelegramGetCommands::router(ctx).await?
+                        }
+                        AdminRoute::TelegramPollUpdates => {
+                            AdminTelegramPollUpdates::router(ctx).await?
+                        }
                         AdminRoute::PopulateFakeUser => AdminPopulateFakeUser::router(ctx).await?,
                     },
                     Route::Action(action_route) => match action_route {
                         ActionRoute::ListDestinations => {
                             ListActionDestinations::router(ctx).await?
                         }
+                        ActionRoute::AddHomeAssistantDestination => {
+                            AddHomeAssistantDestination::router(ctx).await?
+                        }
                         ActionRoute::AddAction => AddAction::router(ctx).await?,
                         ActionRoute::DeleteAction => DeleteAction::router(ctx).await?,
                         ActionRoute::ListActions => ListActions::router(ctx).await?,
+                        ActionRoute::DeleteDestination => DeleteDestination::router(ctx).await?,
+                        ActionRoute::RenameDestination => RenameDestination::router(ctx).await?,
+                        ActionRoute::CreateDeepLink => CreateDeepLink::router(ctx).await?,
+                        ActionRoute::CreateInvitation => CreateInvitation::router(ctx).await?,
+                        ActionRoute::ListInvitations => ListInvitations::router(ctx).await?,
+                        ActionRoute::ListDeliveries => ListDeliveries::router(ctx).await?,
+                        ActionRoute::SimulateTranscript => SimulateTranscript::router(ctx).await?,
+                        ActionRoute::TestAction => TestAction::router(ctx).await?,
+                        ActionRoute::PingDestination => PingDestination::router(ctx).await?,
+                        ActionRoute::UpdateAction => UpdateAction::router(ctx).await?,
+                        ActionRoute::GetPause => GetPause::router(ctx).await?,
+                        ActionRoute::SetPause => SetPause::router(ctx).await?,
+                        ActionRoute::ExportConfig => ExportConfig::router(ctx).await?,
+                        ActionRoute::ImportConfig => ImportConfig::router(ctx).await?,
                     },
                     Route::Info => ServerInfo::router(ctx).await?,
                     Route::TelegramWebHook => TelegramWebHook::router(ctx).await?,
@@ -1,16 +1,27 @@
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use shared::{
-    api::telegram::{TelegramBotError, TelegramMessage, TelegramUser, TelegramWebHookInfo},
+    api::action::ParseMode,
+    api::telegram::{
+        TelegramBotError, TelegramChatMember, TelegramCommandInfo, TelegramCommandScope,
+        TelegramInlineKeyboardButton, TelegramMessage, TelegramUser, TelegramWebHookInfo,
+        TelegramWebHookRequest,
+    },
     backend::result::{ApiError, ApiResult},
 };
+use std::time::Duration;
 use web_sys::FormData;
-use worker::{Env, Fetch, Request};
+use worker::{Delay, Env, Fetch, Request};
 
-use crate::config::{ENV_KEY_TELEGRAM_BOT_TOKEN, ENV_KEY_TELEGRAM_WEBHOOK_SECRET};
+use crate::config::{
+    ENV_KEY_TELEGRAM_API_BASE_URL, ENV_KEY_TELEGRAM_BOT_TOKEN, ENV_KEY_TELEGRAM_WEBHOOK_SECRET,
+    TELEGRAM_API_DEFAULT_BASE_URL, TELEGRAM_BACKOFF_BASE_MS, TELEGRAM_MAX_ATTEMPTS,
+    TELEGRAM_MAX_RETRY_DELAY_MS,
+};
 
 pub struct TelegramBot {
     pub token: String,
     pub webhook_secret: String,
+    pub api_base_url: String,
 }
 
 impl TelegramBot {
@@ -21,6 +32,15 @@ impl TelegramBot {
                 .secret(ENV_KEY_TELEGRAM_WEBHOOK_SECRET)
                 .unwrap()
                 .to_string(),
+            // e.g. a self-hosted Bot API server, or the fake one for offline testing
+            api_base_url: env
+                .var(ENV_KEY_TELEGRAM_API_BASE_URL)
+                .map(|var| var.to_string())
+                .ok()
+                .filter(|url| !url.is_empty())
+                .unwrap_or_else(|| TELEGRAM_API_DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
         }
     }
 
@@ -51,36 +71,233 @@ impl TelegramBot {
         self.make_request_empty("getWebhookInfo").await
     }
 
+    // language_code None sets the fallback for all languages
+    pub async fn set_my_commands(
+        &self,
+        commands: &[TelegramCommandInfo],
+        scope: TelegramCommandScope,
+        language_code: Option<&str>,
+    ) -> ApiResult<()> {
+        let form_data = Self::scoped_form_data(scope, language_code)?;
+        form_data.append_with_str("commands", &Self::to_json(&commands)?)?;
+
+        self.make_request_params::<bool>("setMyCommands", form_data)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn get_my_commands(
+        &self,
+        scope: TelegramCommandScope,
+        language_code: Option<&str>,
+    ) -> ApiResult<Vec<TelegramCommandInfo>> {
+        let form_data = Self::scoped_form_data(scope, language_code)?;
+
+        self.make_request_params("getMyCommands", form_data).await
+    }
+
+    // shown in the empty chat, before the user hits start
+    pub async fn set_my_description(
+        &self,
+        description: &str,
+        language_code: Option<&str>,
+    ) -> ApiResult<()> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("description", description)?;
+        if let Some(language_code) = language_code {
+            form_data.append_with_str("language_code", language_code)?;
+        }
+
+        self.make_request_params::<bool>("setMyDescription", form_data)
+            .await
+            .map(|_| ())
+    }
+
+    // shown on the bot's profile and when it's shared
+    pub async fn set_my_short_description(
+        &self,
+        short_description: &str,
+        language_code: Option<&str>,
+    ) -> ApiResult<()> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("short_description", short_description)?;
+        if let Some(language_code) = language_code {
+            form_data.append_with_str("language_code", language_code)?;
+        }
+
+        self.make_request_params::<bool>("setMyShortDescription", form_data)
+            .await
+            .map(|_| ())
+    }
+
+    // the default menu button for all private chats, opening the url as a Mini App
+    pub async fn set_chat_menu_button_web_app(&self, text: &str, url: &str) -> ApiResult<()> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str(
+            "menu_button",
+            &Self::to_json(&serde_json::json!({
+                "type": "web_app",
+                "text": text,
+                "web_app": { "url": url },
+            }))?,
+        )?;
+
+        self.make_request_params::<bool>("setChatMenuButton", form_data)
+            .await
+            .map(|_| ())
+    }
+
+    // getUpdates is refused while a webhook is set
+    pub async fn delete_webhook(&self) -> ApiResult<()> {
+        let success = self.make_request_empty::<bool>("deleteWebhook").await?;
+
+        if success {
+            Ok(())
+        } else {
+            Err(ApiError::Telegram(TelegramBotError::Internal(
+                "Failed to delete webhook".to_string(),
+            )))
+        }
+    }
+
+    // long polling, offset confirms everything before it
+    // https://core.telegram.org/bots/api#getupdates
+    pub async fn get_updates(
+        &self,
+        offset: Option<i64>,
+        timeout_secs: u64,
+    ) -> ApiResult<Vec<TelegramWebHookRequest>> {
+        let form_data = FormData::new()?;
+        if let Some(offset) = offset {
+            form_data.append_with_str("offset", &offset.to_string())?;
+        }
+        form_data.append_with_str("timeout", &timeout_secs.to_string())?;
+
+        self.make_request_params("getUpdates", form_data).await
+    }
+
     pub async fn send_message(&self, chat_id: i64, text: &str) -> ApiResult<TelegramMessage> {
+        self.send_thread_message(chat_id, None, text).await
+    }
+
+    // message_thread_id targets a forum topic, None is the main thread
+    pub async fn send_thread_message(
+        &self,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+        text: &str,
+    ) -> ApiResult<TelegramMessage> {
+        self.send_formatted_message(chat_id, message_thread_id, text, ParseMode::Plain)
+            .await
+    }
+
+    // the text must already be valid for the parse_mode, see formatter
+    pub async fn send_formatted_message(
+        &self,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+        text: &str,
+        parse_mode: ParseMode,
+    ) -> ApiResult<TelegramMessage> {
         let form_data = FormData::new()?;
         form_data.append_with_str("chat_id", &chat_id.to_string())?;
+        if let Some(message_thread_id) = message_thread_id {
+            form_data.append_with_str("message_thread_id", &message_thread_id.to_string())?;
+        }
         form_data.append_with_str("text", text)?;
+        if let Some(parse_mode) = parse_mode.telegram_value() {
+            form_data.append_with_str("parse_mode", parse_mode)?;
+        }
 
         self.make_request_params("sendMessage", form_data).await
     }
 
-    async fn make_request_empty<T: DeserializeOwned>(&self, method: &str) -> ApiResult<T> {
-        let url = format!("https://api.telegram.org/bot{}/{}", self.token, method);
-        let request = Request::new(&url, worker::Method::Get)
-            .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))?;
-        let mut res = Fetch::Request(request)
-            .send()
+    // a single row of inline buttons, pressing one sends a callback_query update with its data
+    pub async fn send_message_with_buttons(
+        &self,
+        chat_id: i64,
+        text: &str,
+        buttons: &[TelegramInlineKeyboardButton],
+    ) -> ApiResult<TelegramMessage> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("chat_id", &chat_id.to_string())?;
+        form_data.append_with_str("text", text)?;
+        form_data.append_with_str(
+            "reply_markup",
+            &Self::to_json(&serde_json::json!({ "inline_keyboard": [buttons] }))?,
+        )?;
+
+        self.make_request_params("sendMessage", form_data).await
+    }
+
+    // replaces the text, and drops any inline buttons
+    pub async fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        text: &str,
+    ) -> ApiResult<()> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("chat_id", &chat_id.to_string())?;
+        form_data.append_with_str("message_id", &message_id.to_string())?;
+        form_data.append_with_str("text", text)?;
+
+        // returns the edited message, which we don't need
+        self.make_request_params::<serde_json::Value>("editMessageText", form_data)
             .await
-            .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))?;
-        let text = res.text().await?;
+            .map(|_| ())
+    }
 
-        tracing::info!("Response: {}", text);
+    // every callback_query must be answered, or the button keeps spinning
+    // text is shown as a small notification
+    pub async fn answer_callback_query(
+        &self,
+        callback_query_id: &str,
+        text: Option<&str>,
+    ) -> ApiResult<()> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("callback_query_id", callback_query_id)?;
+        if let Some(text) = text {
+            form_data.append_with_str("text", text)?;
+        }
 
-        let json: TelegramResult<T> = serde_json::from_str(&text)
-            .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))?;
+        self.make_request_params::<bool>("answerCallbackQuery", form_data)
+            .await
+            .map(|_| ())
+    }
 
-        if json.ok {
-            Ok(json.result)
-        } else {
-            Err(ApiError::Telegram(TelegramBotError::Internal(
-                "Telegram API error".to_string(),
-            )))
+    pub async fn get_chat_member(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+    ) -> ApiResult<TelegramChatMember> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("chat_id", &chat_id.to_string())?;
+        form_data.append_with_str("user_id", &user_id.to_string())?;
+
+        self.make_request_params("getChatMember", form_data).await
+    }
+
+    fn scoped_form_data(
+        scope: TelegramCommandScope,
+        language_code: Option<&str>,
+    ) -> ApiResult<FormData> {
+        let form_data = FormData::new()?;
+        form_data.append_with_str("scope", &Self::to_json(&scope)?)?;
+        if let Some(language_code) = language_code {
+            form_data.append_with_str("language_code", language_code)?;
         }
+        Ok(form_data)
+    }
+
+    // complex parameters are sent as JSON strings
+    fn to_json<T: Serialize>(value: &T) -> ApiResult<String> {
+        serde_json::to_string(value)
+            .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))
+    }
+
+    async fn make_request_empty<T: DeserializeOwned>(&self, method: &str) -> ApiResult<T> {
+        self.make_request(method, None).await
     }
 
     async fn make_request_params<T: DeserializeOwned>(
@@ -88,17 +305,76 @@ impl TelegramBot {
         method: &str,
         form_data: FormData,
     ) -> ApiResult<T> {
-        let url = format!("https://api.telegram.org/bot{}/{}", self.token, method);
+        self.make_request(method, Some(form_data)).await
+    }
+
+    // retries rate limits for every method, and server errors only for methods that are safe to repeat
+    // (a 5xx may come after Telegram already sent the message), everything else is returned as-is
+    async fn make_request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        form_data: Option<FormData>,
+    ) -> ApiResult<T> {
+        let mut attempt = 0;
+
+        loop {
+            let err = match self.make_request_once(method, form_data.as_ref()).await {
+                Ok(result) => return Ok(result),
+                Err(err) => err,
+            };
+
+            attempt += 1;
+
+            let delay_ms = match &err {
+                _ if attempt >= TELEGRAM_MAX_ATTEMPTS => None,
+                ApiError::Telegram(TelegramBotError::TooManyRequests { retry_after }) => {
+                    Some((*retry_after * 1000).min(TELEGRAM_MAX_RETRY_DELAY_MS))
+                }
+                ApiError::Telegram(TelegramBotError::Server { .. }) if is_idempotent(method) => {
+                    Some(TELEGRAM_BACKOFF_BASE_MS * 2u64.pow(attempt - 1))
+                }
+                _ => None,
+            };
+
+            match delay_ms {
+                Some(delay_ms) => {
+                    tracing::warn!(
+                        "{} failed (attempt {}), retrying in {}ms: {}",
+                        method,
+                        attempt,
+                        delay_ms,
+                        err
+                    );
+                    Delay::from(Duration::from_millis(delay_ms)).await;
+                }
+                None => return Err(err),
+            }
+        }
+    }
+
+    async fn make_request_once<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        form_data: Option<&FormData>,
+    ) -> ApiResult<T> {
+        let url = format!("{}/bot{}/{}", self.api_base_url, self.token, method);
 
         tracing::info!("Request: {}", url);
-        tracing::info!("{:?}", form_data);
 
-        let mut init = worker::RequestInit::new();
-        init.with_body(Some(form_data.into()));
-        init.with_method(worker::Method::Post);
+        let request = match form_data {
+            None => Request::new(&url, worker::Method::Get),
+            Some(form_data) => {
+                tracing::info!("{:?}", form_data);
+
+                let mut init = worker::RequestInit::new();
+                init.with_body(Some(form_data.into()));
+                init.with_method(worker::Method::Post);
+
+                Request::new_with_init(&url, &init)
+            }
+        }
+        .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))?;
 
-        let request = Request::new_with_init(&url, &init)
-            .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))?;
         let mut res = Fetch::Request(request)
             .send()
             .await
@@ -111,18 +387,90 @@ impl TelegramBot {
         let json: TelegramResult<T> = serde_json::from_str(&text)
             .map_err(|e| ApiError::Telegram(TelegramBotError::Internal(e.to_string())))?;
 
-        if json.ok {
-            Ok(json.result)
-        } else {
-            Err(ApiError::Telegram(TelegramBotError::Internal(
-                "Telegram API error".to_string(),
-            )))
-        }
+        json.into_result()
     }
 }
 
+// https://core.telegram.org/bots/api#making-requests
 #[derive(Deserialize, Serialize, Debug)]
 struct TelegramResult<T> {
     ok: bool,
-    result: T,
+    result: Option<T>,
+    error_code: Option<u16>,
+    description: Option<String>,
+    parameters: Option<TelegramResponseParameters>,
+}
+
+// https://core.telegram.org/bots/api#responseparameters
+#[derive(Deserialize, Serialize, Debug)]
+struct TelegramResponseParameters {
+    migrate_to_chat_id: Option<i64>,
+    retry_after: Option<u64>,
+}
+
+impl<T> TelegramResult<T> {
+    fn into_result(self) -> ApiResult<T> {
+        if self.ok {
+            return self.result.ok_or_else(|| {
+                ApiError::Telegram(TelegramBotError::Internal(
+                    "missing result in response".to_string(),
+                ))
+            });
+        }
+
+        let description = self
+            .description
+            .unwrap_or_else(|| "Telegram API error".to_string());
+        let parameters = self.parameters.unwrap_or(TelegramResponseParameters {
+            migrate_to_chat_id: None,
+            retry_after: None,
+        });
+
+        let err = match (self.error_code, parameters) {
+            (
+                _,
+                TelegramResponseParameters {
+                    migrate_to_chat_id: Some(migrate_to_chat_id),
+                    ..
+                },
+            ) => TelegramBotError::ChatMigrated { migrate_to_chat_id },
+            (Some(429), TelegramResponseParameters { retry_after, .. }) => {
+                TelegramBotError::TooManyRequests {
+                    retry_after: retry_after.unwrap_or(1),
+                }
+            }
+            (Some(400), _) => TelegramBotError::BadRequest(description),
+            // blocked by the user, kicked from the group, etc.
+            (Some(403), _) => TelegramBotError::Forbidden(description),
+            (Some(error_code), _) if error_code >= 500 => TelegramBotError::Server {
+                error_code,
+                description,
+            },
+            (Some(error_code), _) => TelegramBotError::Api {
+                error_code,
+                description,
+            },
+            (None, _) => TelegramBotError::Internal(description),
+        };
+
+        Err(ApiError::Telegram(err))
+    }
+}
+
+// reads, and writes that set the same state again, see make_request
+fn is_idempotent(method: &str) -> bool {
+    matches!(
+        method,
+        "getMe"
+            | "getWebhookInfo"
+            | "getMyCommands"
+            | "getUpdates"
+            | "getChatMember"
+            | "setWebhook"
+            | "deleteWebhook"
+            | "setMyCommands"
+            | "setMyDescription"
+            | "setMyShortDescription"
+            | "setChatMenuButton"
+    )
 }
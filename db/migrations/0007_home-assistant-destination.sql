@@ -0,0 +1,12 @@
+-- Migration number: 0007 	 2026-10-19T09:12:44.310Z
+CREATE TABLE home_assistant_destination (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    base_url TEXT NOT NULL,
+    token TEXT NOT NULL,
+    target TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS idx_home_assistant_destination_user_id ON home_assistant_destination(user_id);
@@ -0,0 +1,40 @@
+-- Migration number: 0008 	 2026-10-19T10:02:17.551Z
+-- one generic table for all destination kinds
+-- the kind determines the shape of the json config
+CREATE TABLE destination (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    config TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS idx_destination_user_id ON destination(user_id);
+
+INSERT INTO destination (id, user_id, name, kind, config, created_at)
+SELECT
+    id,
+    user_id,
+    name,
+    CASE kind WHEN 1 THEN 'telegram_dm' ELSE 'telegram_group' END,
+    json_object('chat_id', chat_id),
+    created_at
+FROM telegram_destination;
+
+INSERT INTO destination (id, user_id, name, kind, config, created_at)
+SELECT
+    id,
+    user_id,
+    name,
+    'home_assistant',
+    json_object('base_url', base_url, 'token', token, 'target', json(target)),
+    created_at
+FROM home_assistant_destination;
+
+DROP INDEX IF EXISTS idx_telegram_destination_id;
+DROP INDEX IF EXISTS idx_telegram_destination_user_id;
+DROP TABLE telegram_destination;
+
+DROP INDEX IF EXISTS idx_home_assistant_destination_user_id;
+DROP TABLE home_assistant_destination;
@@ -0,0 +1,4 @@
+-- Migration number: 0009 	 2026-10-19T11:24:53.108Z
+-- destinations the bot can't reach anymore (removed from the group, blocked, etc.)
+ALTER TABLE destination
+ADD COLUMN active INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,4 @@
+-- Migration number: 0010 	 2026-10-19T13:02:17.441Z
+-- how the action message is formatted for telegram, existing actions stay plain text
+ALTER TABLE telegram_action
+ADD COLUMN parse_mode TEXT NOT NULL DEFAULT 'plain';
@@ -0,0 +1,24 @@
+-- Migration number: 0011 	 2026-10-19T15:41:08.276Z
+-- contacts invited to be a DM destination, and whether they still agree to it
+CREATE TABLE invitation (
+    id TEXT PRIMARY KEY,
+    -- the inviter
+    user_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    -- whoever opened the link, set before they accept or decline
+    tg_uid INTEGER,
+    chat_id INTEGER,
+    contact_name TEXT,
+    -- set once accepted
+    destination_id TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS idx_invitation_user_id ON invitation(user_id);
+CREATE INDEX IF NOT EXISTS idx_invitation_chat_id ON invitation(chat_id);
+
+-- existing destinations were all linked by their owner
+ALTER TABLE destination
+ADD COLUMN consented INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,17 @@
+-- Migration number: 0012 	 2026-10-19T16:20:44.512Z
+-- every attempt at sending a triggered action, kept for DELIVERY_RETENTION_DAYS
+-- action_id and destination_id aren't foreign keys, the history outlives them
+CREATE TABLE delivery (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    action_id TEXT NOT NULL,
+    destination_id TEXT NOT NULL,
+    text TEXT NOT NULL,
+    message_id INTEGER,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS idx_delivery_user_id ON delivery(user_id, id);
+CREATE INDEX IF NOT EXISTS idx_delivery_created_at ON delivery(created_at);
@@ -0,0 +1,4 @@
+-- Migration number: 0013 	 2026-10-19T16:40:03.118Z
+-- when an action was last edited, existing actions have never been
+ALTER TABLE telegram_action
+ADD COLUMN updated_at DATETIME;
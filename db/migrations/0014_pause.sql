@@ -0,0 +1,7 @@
+-- Migration number: 0014 	 2026-10-19T17:12:45.902Z
+-- actions can be turned off without deleting them, and everything can be paused for a while
+ALTER TABLE telegram_action
+ADD COLUMN enabled INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE user_account
+ADD COLUMN paused_until DATETIME;
@@ -0,0 +1,164 @@
+-- Migration number: 0015 	 2026-10-19T18:03:27.614Z
+-- foreign keys, unique user ids on the linked accounts, and cascading deletes
+-- sqlite can't add constraints to an existing table, so each one is rebuilt
+PRAGMA defer_foreign_keys = true;
+
+-- orphans first, so the copies below satisfy the new constraints
+
+-- linked accounts whose user is gone
+DELETE FROM omi_account WHERE user_id NOT IN (SELECT id FROM user_account);
+DELETE FROM telegram_account WHERE user_id NOT IN (SELECT id FROM user_account);
+
+-- half-finished registrations, every complete one has a telegram account
+-- these are what kept the omi id from registering again
+DELETE FROM omi_account WHERE user_id NOT IN (SELECT user_id FROM telegram_account);
+DELETE FROM user_account WHERE id NOT IN (SELECT user_id FROM telegram_account);
+
+-- a user has at most one of each linked account, the oldest is kept
+DELETE FROM omi_account
+WHERE EXISTS (
+    SELECT 1 FROM omi_account AS other
+    WHERE other.user_id = omi_account.user_id
+    AND (other.created_at < omi_account.created_at
+        OR (other.created_at = omi_account.created_at AND other.id < omi_account.id))
+);
+
+DELETE FROM telegram_account
+WHERE EXISTS (
+    SELECT 1 FROM telegram_account AS other
+    WHERE other.user_id = telegram_account.user_id
+    AND (other.created_at < telegram_account.created_at
+        OR (other.created_at = telegram_account.created_at AND other.id < telegram_account.id))
+);
+
+DELETE FROM destination WHERE user_id NOT IN (SELECT id FROM user_account);
+DELETE FROM telegram_action WHERE destination_id NOT IN (SELECT id FROM destination);
+DELETE FROM invitation WHERE user_id NOT IN (SELECT id FROM user_account);
+UPDATE invitation SET destination_id = NULL
+WHERE destination_id IS NOT NULL AND destination_id NOT IN (SELECT id FROM destination);
+DELETE FROM delivery WHERE user_id NOT IN (SELECT id FROM user_account);
+
+-- omi_account
+
+CREATE TABLE omi_account_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL UNIQUE REFERENCES user_account(id) ON DELETE CASCADE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+INSERT INTO omi_account_new (id, user_id, created_at)
+SELECT id, user_id, created_at FROM omi_account;
+
+DROP TABLE omi_account;
+ALTER TABLE omi_account_new RENAME TO omi_account;
+
+-- telegram_account
+
+CREATE TABLE telegram_account_new (
+    id BIGINT PRIMARY KEY,
+    user_id TEXT NOT NULL UNIQUE REFERENCES user_account(id) ON DELETE CASCADE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    first_name TEXT NOT NULL DEFAULT 'default_name',
+    username TEXT
+) WITHOUT ROWID;
+
+INSERT INTO telegram_account_new (id, user_id, created_at, first_name, username)
+SELECT id, user_id, created_at, first_name, username FROM telegram_account;
+
+DROP TABLE telegram_account;
+ALTER TABLE telegram_account_new RENAME TO telegram_account;
+
+-- destination, before the tables that refer to it
+
+CREATE TABLE destination_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES user_account(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    config TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    active INTEGER NOT NULL DEFAULT 1,
+    consented INTEGER NOT NULL DEFAULT 1
+) WITHOUT ROWID;
+
+INSERT INTO destination_new (id, user_id, name, kind, config, created_at, active, consented)
+SELECT id, user_id, name, kind, config, created_at, active, consented FROM destination;
+
+DROP INDEX IF EXISTS idx_destination_user_id;
+DROP TABLE destination;
+ALTER TABLE destination_new RENAME TO destination;
+
+CREATE INDEX IF NOT EXISTS idx_destination_user_id ON destination(user_id);
+
+-- telegram_action, deleted along with its destination
+
+CREATE TABLE telegram_action_new (
+    id TEXT PRIMARY KEY,
+    destination_id TEXT NOT NULL REFERENCES destination(id) ON DELETE CASCADE,
+    prompt TEXT NOT NULL,
+    msg TEXT NOT NULL,
+    parse_mode TEXT NOT NULL DEFAULT 'plain',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME,
+    enabled INTEGER NOT NULL DEFAULT 1
+) WITHOUT ROWID;
+
+INSERT INTO telegram_action_new (id, destination_id, prompt, msg, parse_mode, created_at, updated_at, enabled)
+SELECT id, destination_id, prompt, msg, parse_mode, created_at, updated_at, enabled FROM telegram_action;
+
+DROP INDEX IF EXISTS idx_telegram_action_destination_id;
+DROP TABLE telegram_action;
+ALTER TABLE telegram_action_new RENAME TO telegram_action;
+
+CREATE INDEX IF NOT EXISTS idx_telegram_action_destination_id ON telegram_action(destination_id);
+
+-- invitation, kept when its destination is deleted so the inviter still sees it
+
+CREATE TABLE invitation_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES user_account(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    tg_uid INTEGER,
+    chat_id INTEGER,
+    contact_name TEXT,
+    destination_id TEXT REFERENCES destination(id) ON DELETE SET NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+INSERT INTO invitation_new (id, user_id, reason, status, tg_uid, chat_id, contact_name, destination_id, created_at, updated_at)
+SELECT id, user_id, reason, status, tg_uid, chat_id, contact_name, destination_id, created_at, updated_at FROM invitation;
+
+DROP INDEX IF EXISTS idx_invitation_user_id;
+DROP INDEX IF EXISTS idx_invitation_chat_id;
+DROP TABLE invitation;
+ALTER TABLE invitation_new RENAME TO invitation;
+
+CREATE INDEX IF NOT EXISTS idx_invitation_user_id ON invitation(user_id);
+CREATE INDEX IF NOT EXISTS idx_invitation_chat_id ON invitation(chat_id);
+
+-- delivery, only the user is a foreign key, the history outlives actions and destinations
+
+CREATE TABLE delivery_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES user_account(id) ON DELETE CASCADE,
+    action_id TEXT NOT NULL,
+    destination_id TEXT NOT NULL,
+    text TEXT NOT NULL,
+    message_id INTEGER,
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+) WITHOUT ROWID;
+
+INSERT INTO delivery_new (id, user_id, action_id, destination_id, text, message_id, status, error, created_at)
+SELECT id, user_id, action_id, destination_id, text, message_id, status, error, created_at FROM delivery;
+
+DROP INDEX IF EXISTS idx_delivery_user_id;
+DROP INDEX IF EXISTS idx_delivery_created_at;
+DROP TABLE delivery;
+ALTER TABLE delivery_new RENAME TO delivery;
+
+CREATE INDEX IF NOT EXISTS idx_delivery_user_id ON delivery(user_id, id);
+CREATE INDEX IF NOT EXISTS idx_delivery_created_at ON delivery(created_at);
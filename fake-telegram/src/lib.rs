@@ -0,0 +1,519 @@
+// A fake Telegram Bot API server
+//
+// The backend talks to it like the real thing (point TELEGRAM_API_BASE_URL at it),
+// and tests drive it through the /fake routes (see tests/e2e.rs):
+//
+// GET    /fake/sent                          every message the bot sent
+// DELETE /fake/sent                          forget them
+// POST   /fake/updates                       inject an update (update_id is filled in if missing)
+//                                            forwarded to the webhook if one is set, otherwise queued for getUpdates
+// PUT    /fake/chats/:chat_id/administrators the result of getChatAdministrators for that chat
+// POST   /fake/failures/:method              the next call to that method fails with this error
+// POST   /fake/reset                         back to a clean slate
+//
+// Only the methods the backend actually uses are implemented
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::{FromRequest, Multipart, Path, Request, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::{net::TcpListener, sync::Mutex};
+
+#[derive(Clone, Default)]
+pub struct FakeBotApi {
+    state: Arc<Mutex<FakeState>>,
+    // if set, requests for any other token are rejected
+    token: Option<String>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    next_message_id: i64,
+    next_update_id: i64,
+    sent: Vec<SentMessage>,
+    // waiting for getUpdates
+    updates: VecDeque<Value>,
+    webhook: Option<Webhook>,
+    chat_administrators: HashMap<i64, Vec<Value>>,
+    // keyed by (scope, language_code) as they were sent
+    commands: HashMap<(String, String), Value>,
+    failures: HashMap<String, VecDeque<FakeFailure>>,
+}
+
+struct Webhook {
+    url: String,
+    secret_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SentMessage {
+    pub message_id: i64,
+    pub chat_id: i64,
+    pub message_thread_id: Option<i64>,
+    pub text: String,
+    pub parse_mode: Option<String>,
+}
+
+// https://core.telegram.org/bots/api#making-requests
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FakeFailure {
+    pub error_code: u16,
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Option<Value>,
+}
+
+impl FakeBotApi {
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            state: Arc::default(),
+            token,
+        }
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/:bot_token/:method", get(bot_method).post(bot_method))
+            .route("/fake/sent", get(list_sent).delete(clear_sent))
+            .route("/fake/updates", post(inject_update))
+            .route(
+                "/fake/chats/:chat_id/administrators",
+                put(set_chat_administrators),
+            )
+            .route("/fake/failures/:method", post(add_failure))
+            .route("/fake/reset", post(reset))
+            .with_state(self.clone())
+    }
+
+    // for tests, binds to the given address (port 0 for any) and serves in the background
+    pub async fn spawn(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let router = self.router();
+
+        tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, router).await {
+                tracing::error!("fake telegram server stopped: {err}");
+            }
+        });
+
+        Ok(addr)
+    }
+
+    pub async fn sent(&self) -> Vec<SentMessage> {
+        self.state.lock().await.sent.clone()
+    }
+}
+
+async fn bot_method(
+    State(api): State<FakeBotApi>,
+    Path((bot_token, method)): Path<(String, String)>,
+    request: Request,
+) -> Response {
+    let token = match bot_token.strip_prefix("bot") {
+        Some(token) => token,
+        None => return error(404, "Not Found", None),
+    };
+
+    if api
+        .token
+        .as_deref()
+        .is_some_and(|expected| expected != token)
+    {
+        return error(401, "Unauthorized", None);
+    }
+
+    let params = match read_params(&api, request).await {
+        Ok(params) => params,
+        Err(err) => return error(400, &format!("Bad Request: {err}"), None),
+    };
+
+    tracing::info!("{method} {params:?}");
+
+    let mut state = api.state.lock().await;
+
+    if let Some(failure) = state
+        .failures
+        .get_mut(&method)
+        .and_then(|failures| failures.pop_front())
+    {
+        return error(failure.error_code, &failure.description, failure.parameters);
+    }
+
+    let result = match method.as_str() {
+        "getMe" => Ok(json!({
+            "id": 1,
+            "is_bot": true,
+            "first_name": "Fake Omi Assist",
+            "username": "fake_omi_assist_bot",
+        })),
+        "setWebhook" => param(&params, "url").map(|url| {
+            state.webhook = Some(Webhook {
+                url,
+                secret_token: params.get("secret_token").cloned(),
+            });
+            json!(true)
+        }),
+        "deleteWebhook" => {
+            state.webhook = None;
+            Ok(json!(true))
+        }
+        "getWebhookInfo" => Ok(json!({
+            "url": state.webhook.as_ref().map(|webhook| webhook.url.clone()).unwrap_or_default(),
+            "has_custom_certificate": false,
+            "pending_update_count": state.updates.len(),
+        })),
+        "getUpdates" => {
+            if state.webhook.is_some() {
+                return error(
+                    409,
+                    "Conflict: can't use getUpdates method while webhook is active",
+                    None,
+                );
+            }
+            // like the real thing, asking for an offset confirms everything before it
+            let offset = params
+                .get("offset")
+                .and_then(|offset| offset.parse::<i64>().ok());
+            if let Some(offset) = offset {
+                state
+                    .updates
+                    .retain(|update| update["update_id"].as_i64().unwrap_or_default() >= offset);
+            }
+            Ok(Value::Array(state.updates.iter().cloned().collect()))
+        }
+        "sendMessage" => send_message(&mut state, &params),
+        "setMyCommands" => param(&params, "commands").and_then(|commands| {
+            let commands = serde_json::from_str(&commands).map_err(|err| err.to_string())?;
+            state.commands.insert(commands_key(&params), commands);
+            Ok(json!(true))
+        }),
+        "getMyCommands" => Ok(state
+            .commands
+            .get(&commands_key(&params))
+            .cloned()
+            .unwrap_or_else(|| json!([]))),
+        "editMessageText" => edit_message_text(&mut state, &params),
+        "setMyDescription"
+        | "setMyShortDescription"
+        | "setChatMenuButton"
+        | "answerCallbackQuery" => Ok(json!(true)),
+        "getChatMember" => param_i64(&params, "chat_id").and_then(|chat_id| {
+            let user_id = param_i64(&params, "user_id")?;
+            // anyone who isn't set as an admin is a regular member, including the bot itself
+            Ok(state
+                .chat_administrators
+                .get(&chat_id)
+                .and_then(|admins| admins.iter().find(|admin| admin["user"]["id"] == user_id))
+                .cloned()
+                .unwrap_or_else(|| {
+                    json!({
+                        "status": "member",
+                        "user": {
+                            "id": user_id,
+                            "is_bot": user_id == 1,
+                            "first_name": format!("Fake user {user_id}"),
+                        },
+                    })
+                }))
+        }),
+        "getChatAdministrators" => param_i64(&params, "chat_id").map(|chat_id| {
+            Value::Array(
+                state
+                    .chat_administrators
+                    .get(&chat_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+        }),
+        _ => return error(404, "Not Found: method not found", None),
+    };
+
+    match result {
+        Ok(result) => Json(json!({ "ok": true, "result": result })).into_response(),
+        Err(err) => error(400, &format!("Bad Request: {err}"), None),
+    }
+}
+
+fn send_message(state: &mut FakeState, params: &HashMap<String, String>) -> Result<Value, String> {
+    let chat_id = param_i64(params, "chat_id")?;
+    let text = param(params, "text")?;
+    let message_thread_id = params
+        .get("message_thread_id")
+        .and_then(|id| id.parse::<i64>().ok());
+    let parse_mode = params.get("parse_mode").cloned();
+
+    if text.is_empty() {
+        return Err("message text is empty".to_string());
+    }
+
+    state.next_message_id += 1;
+    let message_id = state.next_message_id;
+
+    state.sent.push(SentMessage {
+        message_id,
+        chat_id,
+        message_thread_id,
+        text: text.clone(),
+        parse_mode,
+    });
+
+    let mut message = json!({
+        "message_id": message_id,
+        "chat": fake_chat(chat_id),
+        "date": now(),
+        "text": text,
+    });
+
+    if let Some(message_thread_id) = message_thread_id {
+        message["message_thread_id"] = json!(message_thread_id);
+        message["is_topic_message"] = json!(true);
+    }
+
+    Ok(message)
+}
+
+fn edit_message_text(
+    state: &mut FakeState,
+    params: &HashMap<String, String>,
+) -> Result<Value, String> {
+    let chat_id = param_i64(params, "chat_id")?;
+    let message_id = param_i64(params, "message_id")?;
+    let text = param(params, "text")?;
+
+    let sent = state
+        .sent
+        .iter_mut()
+        .find(|sent| sent.chat_id == chat_id && sent.message_id == message_id)
+        .ok_or_else(|| "message to edit not found".to_string())?;
+
+    sent.text = text.clone();
+
+    Ok(json!({
+        "message_id": message_id,
+        "chat": fake_chat(chat_id),
+        "date": now(),
+        "text": text,
+    }))
+}
+
+async fn list_sent(State(api): State<FakeBotApi>) -> Json<Vec<SentMessage>> {
+    Json(api.sent().await)
+}
+
+async fn clear_sent(State(api): State<FakeBotApi>) -> StatusCode {
+    api.state.lock().await.sent.clear();
+    StatusCode::NO_CONTENT
+}
+
+async fn inject_update(
+    State(api): State<FakeBotApi>,
+    Json(mut update): Json<Value>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let webhook = {
+        let mut state = api.state.lock().await;
+
+        let update_id = match update.get("update_id").and_then(Value::as_i64) {
+            Some(update_id) => update_id,
+            None => {
+                state.next_update_id += 1;
+                update["update_id"] = json!(state.next_update_id);
+                state.next_update_id
+            }
+        };
+        state.next_update_id = state.next_update_id.max(update_id);
+
+        match &state.webhook {
+            Some(webhook) => Some((webhook.url.clone(), webhook.secret_token.clone())),
+            None => {
+                state.updates.push_back(update.clone());
+                None
+            }
+        }
+    };
+
+    // delivered right away, the caller sees whatever the webhook responded with
+    if let Some((url, secret_token)) = webhook {
+        let mut request = reqwest::Client::new().post(&url).json(&update);
+        if let Some(secret_token) = secret_token {
+            request = request.header("X-Telegram-Bot-Api-Secret-Token", secret_token);
+        }
+
+        let res = request
+            .send()
+            .await
+            .map_err(|err| (StatusCode::BAD_GATEWAY, err.to_string()))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().await.unwrap_or_default();
+            return Err((
+                StatusCode::BAD_GATEWAY,
+                format!("webhook returned {status}: {body}"),
+            ));
+        }
+    }
+
+    Ok(Json(update))
+}
+
+async fn set_chat_administrators(
+    State(api): State<FakeBotApi>,
+    Path(chat_id): Path<i64>,
+    Json(administrators): Json<Vec<Value>>,
+) -> StatusCode {
+    api.state
+        .lock()
+        .await
+        .chat_administrators
+        .insert(chat_id, administrators);
+    StatusCode::NO_CONTENT
+}
+
+async fn add_failure(
+    State(api): State<FakeBotApi>,
+    Path(method): Path<String>,
+    Json(failure): Json<FakeFailure>,
+) -> StatusCode {
+    api.state
+        .lock()
+        .await
+        .failures
+        .entry(method)
+        .or_default()
+        .push_back(failure);
+    StatusCode::NO_CONTENT
+}
+
+async fn reset(State(api): State<FakeBotApi>) -> StatusCode {
+    *api.state.lock().await = FakeState::default();
+    StatusCode::NO_CONTENT
+}
+
+// the backend sends multipart form data, but the Bot API accepts all of these
+async fn read_params(
+    api: &FakeBotApi,
+    request: Request,
+) -> Result<HashMap<String, String>, String> {
+    let mut params: HashMap<String, String> = request
+        .uri()
+        .query()
+        .map(|query| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let content_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    if content_type.starts_with("multipart/form-data") {
+        let mut multipart = Multipart::from_request(request, api)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|err| err.to_string())?
+        {
+            let name = field.name().unwrap_or_default().to_string();
+            let value = field.text().await.map_err(|err| err.to_string())?;
+            params.insert(name, value);
+        }
+    } else if content_type.starts_with("application/json") {
+        let Json(body) = Json::<Map<String, Value>>::from_request(request, api)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        for (key, value) in body {
+            let value = match value {
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+            params.insert(key, value);
+        }
+    }
+
+    Ok(params)
+}
+
+fn commands_key(params: &HashMap<String, String>) -> (String, String) {
+    let scope = params
+        .get("scope")
+        .and_then(|scope| serde_json::from_str::<Value>(scope).ok())
+        .and_then(|scope| scope["type"].as_str().map(|scope| scope.to_string()))
+        .unwrap_or_else(|| "default".to_string());
+
+    (
+        scope,
+        params.get("language_code").cloned().unwrap_or_default(),
+    )
+}
+
+fn param(params: &HashMap<String, String>, name: &str) -> Result<String, String> {
+    params
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("{name} is required"))
+}
+
+fn param_i64(params: &HashMap<String, String>, name: &str) -> Result<i64, String> {
+    param(params, name)?
+        .parse()
+        .map_err(|_| format!("{name} must be a number"))
+}
+
+// the real API knows what kind of chat it is, here it's guessed from the id
+fn fake_chat(chat_id: i64) -> Value {
+    let chat_type = if chat_id > 0 {
+        "private"
+    } else if chat_id < -1_000_000_000_000 {
+        "supergroup"
+    } else {
+        "group"
+    };
+
+    json!({ "id": chat_id, "type": chat_type })
+}
+
+fn error(error_code: u16, description: &str, parameters: Option<Value>) -> Response {
+    let status = StatusCode::from_u16(error_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let mut body = json!({
+        "ok": false,
+        "error_code": error_code,
+        "description": description,
+    });
+
+    if let Some(parameters) = parameters {
+        body["parameters"] = parameters;
+    }
+
+    (status, Json(body)).into_response()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,32 @@
+// Runs the fake Telegram Bot API, e.g. for "task backend-dev-fake-telegram"
+//
+// usage: fake-telegram [port] [bot token]
+// without a token, requests for any bot are accepted
+use std::net::SocketAddr;
+
+use fake_telegram::FakeBotApi;
+
+const DEFAULT_PORT: u16 = 8081;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+
+    let port = match args.next() {
+        Some(port) => port.parse()?,
+        None => DEFAULT_PORT,
+    };
+    let token = args.next();
+
+    let addr = FakeBotApi::new(token)
+        .spawn(SocketAddr::from(([127, 0, 0, 1], port)))
+        .await?;
+
+    tracing::info!("fake telegram bot api listening on http://{addr}");
+
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
@@ -0,0 +1,385 @@
+// End-to-end tests, the fake Telegram API on one side and the backend on the other
+//
+// The ones marked #[ignore] need the backend running against this fake, with the users from seed.sql
+// "task test-e2e" sets all of that up and runs them
+// the rest only check the fake itself, and run with a plain "cargo test"
+use std::net::SocketAddr;
+
+use axum::{http::HeaderMap, routing::post, Json, Router};
+use fake_telegram::{FakeBotApi, SentMessage};
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::mpsc};
+
+// must match "task test-e2e"
+const BACKEND_URL: &str = "http://127.0.0.1:8787";
+const FAKE_TELEGRAM_PORT: u16 = 8081;
+const WEBHOOK_SECRET: &str = "e2e-webhook-secret";
+
+// see seed.sql
+const LINKED_TG_ID: i64 = 1001;
+const UNLINKED_TG_ID: i64 = 1002;
+const UNREGISTERED_TG_ID: i64 = 1003;
+const LINKED_OMI_UID: &str = "e2e-omi";
+const GROUP_CHAT_ID: i64 = -1001;
+
+struct Harness {
+    api: FakeBotApi,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl Harness {
+    async fn spawn(port: u16) -> Self {
+        let api = FakeBotApi::new(None);
+        let addr = api
+            .spawn(SocketAddr::from(([127, 0, 0, 1], port)))
+            .await
+            .unwrap();
+
+        Self {
+            api,
+            url: format!("http://{addr}"),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    // the backend as the webhook, so injected updates reach it right away
+    async fn with_backend() -> Self {
+        let harness = Self::spawn(FAKE_TELEGRAM_PORT).await;
+        harness.set_webhook(&format!("{BACKEND_URL}/tg")).await;
+        harness
+    }
+
+    async fn set_webhook(&self, url: &str) {
+        let res: Value = self
+            .client
+            .post(format!("{}/botfake/setWebhook", self.url))
+            .json(&json!({ "url": url, "secret_token": WEBHOOK_SECRET }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(res["ok"], true);
+    }
+
+    async fn inject(&self, update: Value) -> reqwest::Response {
+        self.client
+            .post(format!("{}/fake/updates", self.url))
+            .json(&update)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    async fn fail_next(&self, method: &str, error_code: u16, description: &str) {
+        let res = self
+            .client
+            .post(format!("{}/fake/failures/{method}", self.url))
+            .json(&json!({ "error_code": error_code, "description": description }))
+            .send()
+            .await
+            .unwrap();
+
+        assert!(res.status().is_success());
+    }
+
+    async fn sent(&self) -> Vec<SentMessage> {
+        self.client
+            .get(format!("{}/fake/sent", self.url))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+
+    async fn clear_sent(&self) {
+        self.client
+            .delete(format!("{}/fake/sent", self.url))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    async fn transcript(&self, omi_uid: &str, text: &str) -> reqwest::Response {
+        self.client
+            .post(format!("{BACKEND_URL}/omi?uid={omi_uid}"))
+            .json(&json!({
+                "session_id": "e2e",
+                "segments": [{ "text": text, "is_user": true }],
+            }))
+            .send()
+            .await
+            .unwrap()
+    }
+}
+
+fn private_message(tg_id: i64, first_name: &str, text: &str) -> Value {
+    json!({
+        "message": {
+            "message_id": 1,
+            "from": { "id": tg_id, "is_bot": false, "first_name": first_name },
+            "chat": { "id": tg_id, "type": "private", "first_name": first_name },
+            "date": 0,
+            "text": text,
+        }
+    })
+}
+
+// what the seeded action delivers, see format_delivery
+const DELIVERED_TEXT: &str = "message from E2E: *remember* the milk";
+
+#[tokio::test]
+async fn fake_records_sent_messages() {
+    let harness = Harness::spawn(0).await;
+
+    let res: Value = harness
+        .client
+        .post(format!("{}/botfake/sendMessage", harness.url))
+        .json(&json!({ "chat_id": 42, "text": "hello", "parse_mode": "HTML" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(res["ok"], true);
+    assert_eq!(res["result"]["chat"]["type"], "private");
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].chat_id, 42);
+    assert_eq!(sent[0].text, "hello");
+    assert_eq!(sent[0].parse_mode.as_deref(), Some("HTML"));
+    assert_eq!(harness.api.sent().await.len(), 1);
+
+    harness.clear_sent().await;
+    assert!(harness.sent().await.is_empty());
+}
+
+#[tokio::test]
+async fn fake_failures_happen_once() {
+    let harness = Harness::spawn(0).await;
+
+    harness
+        .fail_next("sendMessage", 403, "Forbidden: bot was blocked by the user")
+        .await;
+
+    let send = || async {
+        harness
+            .client
+            .post(format!("{}/botfake/sendMessage", harness.url))
+            .json(&json!({ "chat_id": 42, "text": "hello" }))
+            .send()
+            .await
+            .unwrap()
+    };
+
+    let res = send().await;
+    assert_eq!(res.status(), 403);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["ok"], false);
+    assert_eq!(body["error_code"], 403);
+
+    assert!(send().await.status().is_success());
+    assert_eq!(harness.sent().await.len(), 1);
+}
+
+#[tokio::test]
+async fn fake_forwards_updates_to_webhook() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let webhook = Router::new().route(
+        "/tg",
+        post(move |headers: HeaderMap, Json(update): Json<Value>| {
+            let tx = tx.clone();
+            async move {
+                let secret = headers
+                    .get("X-Telegram-Bot-Api-Secret-Token")
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string());
+                tx.send((secret, update)).unwrap();
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let webhook_addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, webhook).await });
+
+    let harness = Harness::spawn(0).await;
+    harness
+        .set_webhook(&format!("http://{webhook_addr}/tg"))
+        .await;
+
+    let res = harness
+        .inject(private_message(LINKED_TG_ID, "E2E", "/link"))
+        .await;
+    assert!(res.status().is_success());
+
+    let (secret, update) = rx.recv().await.unwrap();
+    assert_eq!(secret.as_deref(), Some(WEBHOOK_SECRET));
+    assert_eq!(update["update_id"], 1);
+    assert_eq!(update["message"]["text"], "/link");
+}
+
+#[tokio::test]
+#[ignore = "needs the backend, see \"task test-e2e\""]
+async fn link_registered_user() {
+    let harness = Harness::with_backend().await;
+
+    let res = harness
+        .inject(private_message(UNLINKED_TG_ID, "E2E Unlinked", "/link"))
+        .await;
+    assert!(res.status().is_success(), "{}", res.text().await.unwrap());
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].chat_id, UNLINKED_TG_ID);
+    assert_eq!(sent[0].text, "Chat linked successfully");
+
+    // the same chat again
+    harness.clear_sent().await;
+    harness
+        .inject(private_message(UNLINKED_TG_ID, "E2E Unlinked", "/link"))
+        .await;
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].text, "You have already linked this chat");
+}
+
+#[tokio::test]
+#[ignore = "needs the backend, see \"task test-e2e\""]
+async fn link_unregistered_user() {
+    let harness = Harness::with_backend().await;
+
+    harness
+        .inject(private_message(UNREGISTERED_TG_ID, "Stranger", "/link"))
+        .await;
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].chat_id, UNREGISTERED_TG_ID);
+    assert!(
+        sent[0].text.starts_with("you need to first register"),
+        "{}",
+        sent[0].text
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the backend, see \"task test-e2e\""]
+async fn transcript_is_delivered() {
+    let harness = Harness::with_backend().await;
+
+    let res = harness
+        .transcript(LINKED_OMI_UID, "I need to Buy Milk on the way home")
+        .await;
+    assert!(res.status().is_success(), "{}", res.text().await.unwrap());
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].chat_id, LINKED_TG_ID);
+    assert_eq!(sent[0].text, DELIVERED_TEXT);
+    assert_eq!(sent[0].parse_mode.as_deref(), Some("MarkdownV2"));
+
+    // nothing matches
+    harness.clear_sent().await;
+    harness
+        .transcript(LINKED_OMI_UID, "nothing to do here")
+        .await;
+    assert!(harness.sent().await.is_empty());
+}
+
+#[tokio::test]
+#[ignore = "needs the backend, see \"task test-e2e\""]
+async fn transcript_falls_back_to_plain_text() {
+    let harness = Harness::with_backend().await;
+
+    harness
+        .fail_next(
+            "sendMessage",
+            400,
+            "Bad Request: can't parse entities: can't find end of the entity",
+        )
+        .await;
+
+    let res = harness.transcript(LINKED_OMI_UID, "buy milk").await;
+    assert!(res.status().is_success(), "{}", res.text().await.unwrap());
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].text, "message from E2E: remember the milk");
+    assert_eq!(sent[0].parse_mode, None);
+}
+
+#[tokio::test]
+#[ignore = "needs the backend, see \"task test-e2e\""]
+async fn blocked_destination_goes_inactive() {
+    let harness = Harness::with_backend().await;
+
+    harness
+        .fail_next("sendMessage", 403, "Forbidden: bot was blocked by the user")
+        .await;
+
+    // the failure is recorded, not returned to Omi
+    let res = harness.transcript(LINKED_OMI_UID, "buy milk").await;
+    assert!(res.status().is_success(), "{}", res.text().await.unwrap());
+    assert!(harness.sent().await.is_empty());
+
+    // skipped from now on
+    harness.transcript(LINKED_OMI_UID, "buy milk").await;
+    assert!(harness.sent().await.is_empty());
+
+    // linking again brings it back
+    harness
+        .inject(private_message(LINKED_TG_ID, "E2E", "/link"))
+        .await;
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].text, "Chat linked successfully");
+
+    harness.clear_sent().await;
+    harness.transcript(LINKED_OMI_UID, "buy milk").await;
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].text, DELIVERED_TEXT);
+}
+
+#[tokio::test]
+#[ignore = "needs the backend, see \"task test-e2e\""]
+async fn upgraded_group_keeps_delivering() {
+    let harness = Harness::with_backend().await;
+
+    // a real supergroup id, bound as a float on the way into D1
+    let supergroup_chat_id = -1001234567890_i64;
+
+    // the service message sent to the old group
+    let res = harness
+        .inject(json!({
+            "message": {
+                "message_id": 1,
+                "chat": { "id": GROUP_CHAT_ID, "type": "group", "title": "E2E Group" },
+                "date": 0,
+                "migrate_to_chat_id": supergroup_chat_id,
+            }
+        }))
+        .await;
+    assert!(res.status().is_success(), "{}", res.text().await.unwrap());
+
+    // the migrated config has to load again for this to be sent
+    let res = harness
+        .transcript(LINKED_OMI_UID, "remind me to water the plants")
+        .await;
+    assert!(res.status().is_success(), "{}", res.text().await.unwrap());
+
+    let sent = harness.sent().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].chat_id, supergroup_chat_id);
+    assert_eq!(sent[0].text, "message from E2E: plants need water");
+}
@@ -0,0 +1,31 @@
+-- Registered users for the end-to-end tests, see tests/e2e.rs and "task test-e2e"
+-- safe to run again, everything is reset to the same state
+-- ids are in the simple uuid form (no hyphens), the same as the backend binds them
+
+DELETE FROM user_account WHERE id IN ('000000000000400080000000000e2e01', '000000000000400080000000000e2e02');
+
+INSERT INTO user_account (id, user_token) VALUES
+    ('000000000000400080000000000e2e01', 'e2e-token'),
+    ('000000000000400080000000000e2e02', 'e2e-token-unlinked');
+
+INSERT INTO omi_account (id, user_id) VALUES
+    ('e2e-omi', '000000000000400080000000000e2e01'),
+    ('e2e-omi-unlinked', '000000000000400080000000000e2e02');
+
+INSERT INTO telegram_account (id, user_id, first_name) VALUES
+    (1001, '000000000000400080000000000e2e01', 'E2E'),
+    (1002, '000000000000400080000000000e2e02', 'E2E Unlinked');
+
+-- the private chat with the first user
+INSERT INTO destination (id, user_id, name, kind, config) VALUES
+    ('000000000000400080000000000e2e03', '000000000000400080000000000e2e01', 'E2E', 'telegram_dm', '{"chat_id":1001}');
+
+INSERT INTO telegram_action (id, destination_id, prompt, msg, parse_mode) VALUES
+    ('000000000000400080000000000e2e04', '000000000000400080000000000e2e03', 'buy milk', '*remember* the milk', 'markdown_v2');
+
+-- a group of the first user's, for the supergroup migration
+INSERT INTO destination (id, user_id, name, kind, config) VALUES
+    ('000000000000400080000000000e2e05', '000000000000400080000000000e2e01', 'E2E Group', 'telegram_group', '{"chat_id":-1001}');
+
+INSERT INTO telegram_action (id, destination_id, prompt, msg, parse_mode) VALUES
+    ('000000000000400080000000000e2e06', '000000000000400080000000000e2e05', 'water the plants', 'plants need water', 'plain');
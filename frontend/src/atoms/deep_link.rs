@@ -0,0 +1,76 @@
+use qrcode::{render::svg, QrCode};
+use shared::api::auth::DeepLinkResponse;
+
+use crate::{
+    atoms::buttons::{Button, ButtonSize},
+    prelude::*,
+};
+
+// A t.me link into the bot, as a button for this device and a QR code for a phone
+pub struct DeepLink {}
+
+impl DeepLink {
+    pub fn render(link: &DeepLinkResponse) -> Dom {
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("flex-direction", "column")
+                .style("align-items", "center")
+                .style("gap", "1rem")
+                .style("text-align", "center")
+            }
+        });
+
+        static QR_CODE: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("width", "12rem")
+                .style("height", "12rem")
+                .style("background-color", "#ffffff")
+            }
+        });
+
+        let qr_code = match QrCode::new(link.url.as_bytes()) {
+            Ok(code) => Some(
+                code.render::<svg::Color>()
+                    .min_dimensions(192, 192)
+                    .quiet_zone(true)
+                    .build(),
+            ),
+            Err(err) => {
+                tracing::error!("couldn't make a qr code for {}: {err:?}", link.url);
+                None
+            }
+        };
+
+        html!("div", {
+            .class(&*CONTAINER)
+            // not Button::with_link, that's for our own routes
+            .child(html!("a", {
+                .attr("href", &link.url)
+                .attr("target", "_blank")
+                .attr("rel", "noopener noreferrer")
+                .child(Button::new()
+                    .with_size(ButtonSize::Lg)
+                    .with_text(&get_text!("atom-deep-link-open"))
+                    .render()
+                )
+            }))
+            .apply_if(qr_code.is_some(), |dom| {
+                dom
+                    .child(html!("div", {
+                        .class([FontSize::Lg.class(), ColorText::Byline.class()])
+                        .text(&get_text!("atom-deep-link-scan"))
+                    }))
+                    .child(html!("div", {
+                        .class(&*QR_CODE)
+                        // generated locally from our own url, not user input
+                        .prop("innerHTML", qr_code.as_deref().unwrap_or_default())
+                    }))
+            })
+            .child(html!("div", {
+                .class([FontSize::Sm.class(), ColorText::Byline.class()])
+                .text(&get_text!("atom-deep-link-expires", {"hours" => link.expires_in_secs / 3600}))
+            }))
+        })
+    }
+}
@@ -1,5 +1,6 @@
 pub mod buttons;
 pub mod checkbox;
+pub mod deep_link;
 pub mod dropdown;
 pub mod dynamic_svg;
 pub mod label;
@@ -28,6 +28,14 @@ impl Modal {
         });
     }
 
+    pub fn is_open() -> bool {
+        MODAL.with(|modal| modal.content.lock_ref().is_some())
+    }
+
+    pub fn is_open_signal() -> impl Signal<Item = bool> {
+        MODAL.with(|modal| modal.content.signal_ref(|content| content.is_some()))
+    }
+
     pub fn render() -> impl Fragment {
         MODAL.with(|modal| modal.render())
     }
@@ -2,13 +2,16 @@ use dominator::clone;
 use dominator_helpers::futures::AsyncLoader;
 use futures_signals::signal::Mutable;
 use shared::{
-    api::auth::{AuthCheck, AuthSignout, AuthSignoutRequest},
+    api::auth::{
+        AuthCheck, AuthMiniAppSignin, AuthMiniAppSigninRequest, AuthSigninResponse, AuthSignout,
+        AuthSignoutRequest,
+    },
     backend::result::ApiResult,
     user::UserId,
 };
 use std::sync::{Arc, RwLock};
 
-use crate::prelude::*;
+use crate::{mini_app::MiniApp, prelude::*};
 
 /////// This is a global singleton that holds the current auth state
 pub static AUTH: LazyLock<Auth> = LazyLock::new(|| {
@@ -99,6 +102,25 @@ impl Auth {
             }
             Err(err) => {
                 tracing::error!("auth check failed: {:?}", err);
+
+                // inside Telegram there's no login widget, the Mini App launch itself signs us in
+                if let Some(mini_app) = MiniApp::try_new() {
+                    let req = AuthMiniAppSigninRequest {
+                        init_data: mini_app.init_data(),
+                    };
+
+                    match AuthMiniAppSignin::fetch(req).await {
+                        Ok(AuthSigninResponse { uid, auth_key }) => {
+                            // infallible, it's just local state
+                            let _ = self.on_signin(uid, auth_key).await;
+                            return;
+                        }
+                        Err(err) => {
+                            tracing::error!("mini app signin failed: {:?}", err);
+                        }
+                    }
+                }
+
                 self.phase.set_neq(AuthPhase::Unauthenticated);
             }
         }
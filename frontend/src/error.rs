@@ -29,6 +29,20 @@ impl ApiErrorExt for ApiError {
             Self::Unknown(_) => ("error-api-unknown", None),
             Self::Telegram(_) => ("error-api-telegram", None),
             Self::Omi(_) => ("error-api-omi", None),
+            Self::HomeAssistant(_) => ("error-api-home-assistant", None),
+            Self::Validation(_) => ("error-api-validation", None),
+            Self::DestinationInUse(count) => {
+                let mut args = fluent::FluentArgs::new();
+                args.set("count", *count);
+                ("error-api-destination-in-use", Some(args))
+            }
+            Self::RateLimited(secs) => {
+                let mut args = fluent::FluentArgs::new();
+                args.set("secs", *secs);
+                ("error-api-rate-limited", Some(args))
+            }
+            Self::AlreadyExists(_) => ("error-api-already-exists", None),
+            Self::MissingReference => ("error-api-missing-reference", None),
             Self::Kv(_) => ("error-api-unknown", None),
             Self::Db(_) => ("error-api-unknown", None),
         };
@@ -4,6 +4,7 @@ pub mod auth;
 pub mod config;
 pub mod error;
 pub mod locale;
+pub mod mini_app;
 pub mod page;
 pub mod prelude;
 pub mod route;
@@ -20,6 +21,10 @@ pub async fn run() -> Result<(), JsValue> {
 
     theme::stylesheet::init();
 
+    if let Some(mini_app) = mini_app::MiniApp::try_new() {
+        mini_app.init();
+    }
+
     dominator::append_dom(&dominator::body(), route::render());
 
     Ok(())
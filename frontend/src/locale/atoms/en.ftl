@@ -1 +1,4 @@
-atom-dropdown-select = Select...
\ No newline at end of file
+atom-dropdown-select = Select...
+atom-deep-link-open = Open in Telegram
+atom-deep-link-scan = Or scan with your phone's camera
+atom-deep-link-expires = This link works once, within {$hours} hours
@@ -1,6 +1,8 @@
 dashboard-logo-label = Omi Assist
 dashboard-label-actions = Actions 
 dashboard-label-destinations = Destinations 
+dashboard-label-history = History
+dashboard-label-playground = Playground
 dashboard-label-signout = Sign out
 
 # Actions
@@ -8,25 +10,132 @@ dashboard-actions-add-button = + Create an action
 dashboard-actions-add-kind-label = Action kind 
 dashboard-actions-add-kind-tg-dm = Telegram DM 
 dashboard-actions-add-kind-tg-group = Telegram Group
+dashboard-actions-add-kind-tg-channel = Telegram Channel
+dashboard-actions-add-kind-home-assistant = Home Assistant
 dashboard-actions-add-destination-tg-group-label = Destination Group 
 dashboard-actions-add-destination-tg-dm-label = Destination User
+dashboard-actions-add-destination-tg-channel-label = Destination Channel
+dashboard-actions-add-destination-home-assistant-label = Destination Home Assistant
+dashboard-actions-add-message-home-assistant-placeholder = {"{"}"message": "{"{{"}transcript{"}}"}"{"}"}
 dashboard-actions-add-id = Id 
 dashboard-actions-add-prompt = Prompt 
 dashboard-actions-add-message = Message
 dashboard-actions-add-submit = Submit
+dashboard-actions-add-parse-mode = Formatting
+dashboard-actions-add-parse-mode-plain = Plain text
+dashboard-actions-add-parse-mode-markdown = MarkdownV2
+dashboard-actions-add-parse-mode-html = HTML
+dashboard-actions-add-preview = Preview
+dashboard-actions-preview-error = Telegram won't accept this message
 dashboard-actions-list-title = My actions
 dashboard-actions-delete-button = Delete 
+dashboard-actions-test-button = Send test
+dashboard-actions-edit-button = Edit
+dashboard-actions-edit-submit = Save
+dashboard-actions-disable-button = Disable
+dashboard-actions-enable-button = Enable
+dashboard-actions-disabled = Disabled: never triggered until enabled again
+dashboard-actions-pause-button = Pause all
+dashboard-actions-resume-button = Resume
+dashboard-actions-paused-until = All actions are paused until {$until} UTC
+dashboard-actions-pause-1h = 1 hour
+dashboard-actions-pause-2h = 2 hours
+dashboard-actions-pause-8h = 8 hours
+dashboard-actions-pause-1d = 1 day
+dashboard-actions-pause-1w = 1 week
+dashboard-actions-export-button = Download
+dashboard-actions-import-button = Upload
+dashboard-actions-import-merge = Merge with my actions
+dashboard-actions-import-replace = Replace my actions
+dashboard-actions-import-summary = {$added} to add, {$updated} to update, {$unchanged} unchanged, {$removed} to remove
+dashboard-actions-import-apply = Import
+dashboard-actions-import-cancel = Cancel
+dashboard-actions-import-done = Imported {$added} new and {$updated} updated actions
 
 # Destinations 
 dashboard-destinations-instructions-tg-dm-title = To Add Telegram DM Destinations:
 dashboard-destinations-instructions-tg-dm-body = DM "/link" to @OmiSmartBot 
 dashboard-destinations-instructions-tg-group-title = To Add Telegram Group Destinations:
-dashboard-destinations-instructions-tg-group-body = Invite @OmiSmartBot to a group, and send "/omi link" to the group chat
+dashboard-destinations-instructions-tg-group-body = Invite @OmiSmartBot to a group, and send "/omi link" to the group chat. In a group with topics, send it from the topic you want to link
+dashboard-destinations-instructions-tg-channel-title = To Add Telegram Channel Destinations:
+dashboard-destinations-instructions-tg-channel-body = Add @OmiSmartBot as an admin of the channel, send /linkchannel to @OmiSmartBot for a one-time code, and post "/omi link <code>" to the channel
 dashboard-destinations-list-title = My destinations
 dashboard-destinations-list-id = Id 
+dashboard-destinations-inactive = Inactive: the bot was removed or blocked. Link it again to reactivate
+dashboard-destinations-not-consented = Stopped by recipient: they sent /stop. Invite them again to resume
+dashboard-destinations-rename-button = Rename
+dashboard-destinations-rename-label = Name
+dashboard-destinations-rename-submit = Save
+dashboard-destinations-delete-button = Delete
+dashboard-destinations-ping-button = Send test message
+dashboard-destinations-delete-in-use = This destination is still used by {$count} {$count ->
+    [one] action
+   *[other] actions
+}, delete {$count ->
+    [one] it
+   *[other] them
+} too?
+dashboard-destinations-delete-cascade = Delete destination and actions
 dashboard-destinations-tg-dm-label = Telegram DM
 dashboard-destinations-tg-group-label = Telegram Group
+dashboard-destinations-tg-channel-label = Telegram Channel
+dashboard-destinations-home-assistant-label = Home Assistant
+dashboard-destinations-instructions-home-assistant-title = To Add Home Assistant Destinations:
+dashboard-destinations-instructions-home-assistant-body = Create a long-lived access token in your Home Assistant profile. The action message is sent as JSON event or service data, and can use {"{{"}prompt{"}}"}, {"{{"}transcript{"}}"} and {"{{"}sender{"}}"}
+dashboard-destinations-add-home-assistant-button = + Add Home Assistant
+dashboard-destinations-add-home-assistant-name = Name
+dashboard-destinations-add-home-assistant-base-url = Base URL
+dashboard-destinations-add-home-assistant-token = Long-lived access token
+dashboard-destinations-add-home-assistant-target-kind = Trigger
+dashboard-destinations-add-home-assistant-target-event = Fire event
+dashboard-destinations-add-home-assistant-target-service = Call service
+dashboard-destinations-add-home-assistant-event-type = Event type
+dashboard-destinations-add-home-assistant-domain = Service domain
+dashboard-destinations-add-home-assistant-service = Service
+dashboard-destinations-add-home-assistant-submit = Submit
+dashboard-destinations-invite-contact-button = + Invite a contact
+dashboard-destinations-link-group-button = + Link a group
+dashboard-destinations-deep-link-invite-contact-instructions = Send this link to someone. They'll see who you are and why, and can accept or decline. Reload this page afterwards to see the new destination
+dashboard-destinations-invite-contact-reason = Why should they receive your alerts?
+dashboard-destinations-invite-contact-submit = Create invite link
+dashboard-destinations-invitations-title = Invitations
+dashboard-destinations-invitation-pending = Pending
+dashboard-destinations-invitation-accepted = Accepted
+dashboard-destinations-invitation-declined = Declined
+dashboard-destinations-invitation-revoked = Stopped by recipient
+dashboard-destinations-deep-link-group-instructions = Open this link and pick a group, the bot will be added and the group linked. Reload this page afterwards to see the new destination
 
 # Misc
 dashboard-please-wait = Please wait...
-dashboard-loading = Loading... 
\ No newline at end of file
+dashboard-loading = Loading... 
+
+# History
+dashboard-history-filter-all = All
+dashboard-history-filter-action = Action
+dashboard-history-filter-destination = Destination
+dashboard-history-filter-status = Status
+dashboard-history-status-sent = Sent
+dashboard-history-status-failed = Failed
+dashboard-history-status-skipped = Skipped
+dashboard-history-action = Action
+dashboard-history-destination = Destination
+dashboard-history-deleted = (deleted)
+dashboard-history-empty = Nothing has been sent yet
+dashboard-history-load-more = Load more
+
+# Playground
+dashboard-playground-instructions = Type or paste a transcript, one segment per line, to see which actions would fire. Nothing is sent
+dashboard-playground-transcript = Transcript
+dashboard-playground-no-actions = You don't have any actions yet
+dashboard-playground-no-match = Not triggered: the phrase isn't in the transcript
+dashboard-playground-would-send = Would be sent
+dashboard-playground-skipped-paused = Triggered, but skipped: all actions are paused
+dashboard-playground-skipped-disabled = Triggered, but skipped: the action is disabled
+dashboard-playground-skipped-inactive = Triggered, but skipped: the destination is inactive
+dashboard-playground-skipped-not-consented = Triggered, but skipped: the recipient sent /stop
+dashboard-playground-render-error = Triggered, but the message couldn't be rendered
+dashboard-playground-matched = Matched
+
+dashboard-test-send-sent = Sent, check the destination
+dashboard-test-send-skipped = Not sent, {$reason}
+dashboard-test-send-failed = Failed to send, {$reason}
@@ -4,6 +4,15 @@ error-api-missing-body = Missing body
 error-api-parse-body = Unable to parse body 
 error-api-telegram = Telegram error
 error-api-omi = Omi error
+error-api-home-assistant = Home Assistant error
+error-api-validation = Invalid input
+error-api-destination-in-use = This destination is still used by {$count} {$count ->
+    [one] action
+   *[other] actions
+}
+error-api-rate-limited = Too many requests, try again in {$secs} seconds
+error-api-already-exists = That already exists
+error-api-missing-reference = Something this refers to no longer exists, try reloading
 error-api-not-authorized = Not authorized 
 error-api-omi-id-already-exists = Omi id already exists
 error-api-omi-id-mismatch = Telegram id mismatch 
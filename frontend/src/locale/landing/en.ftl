@@ -27,6 +27,7 @@ landing-register-instruction-no-uid1 = Open this page from the Omi plugin
 landing-register-instruction-no-uid2 = to continue the registration 
 landing-register-instructions-with-uid1 = Login with Telegram 
 landing-register-instructions-with-uid2 = to complete the registration 
+landing-register-instructions-deep-link = Or finish registering in the Telegram app
 
 # landing misc 
 landing-submit = Submit 
@@ -0,0 +1,115 @@
+// When the dashboard is opened as a Telegram Mini App
+// telegram-web-app.js (see index.html) always sets up window.Telegram.WebApp,
+// but initData is only filled in when we're actually running inside Telegram
+// https://core.telegram.org/bots/webapps#initializing-mini-apps
+use dominator::stylesheet;
+use web_sys::js_sys::{Function, Reflect};
+
+use crate::{atoms::modal::Modal, prelude::*};
+
+pub struct MiniApp {
+    web_app: JsValue,
+}
+
+impl MiniApp {
+    pub fn try_new() -> Option<Self> {
+        let telegram = get(&web_sys::window()?.into(), "Telegram")?;
+        let web_app = get(&telegram, "WebApp")?;
+
+        let mini_app = Self { web_app };
+
+        if mini_app.init_data().is_empty() {
+            None
+        } else {
+            Some(mini_app)
+        }
+    }
+
+    // signed by Telegram, validated by the backend as-is
+    pub fn init_data(&self) -> String {
+        get(&self.web_app, "initData")
+            .and_then(|init_data| init_data.as_string())
+            .unwrap_or_default()
+    }
+
+    pub fn init(&self) {
+        self.init_theme();
+        self.init_back_button();
+
+        call(&self.web_app, "expand", &[]);
+        // hides Telegram's own loading placeholder
+        call(&self.web_app, "ready", &[]);
+    }
+
+    // Telegram keeps the --tg-theme-* css variables in sync with the user's theme (light/dark/custom)
+    fn init_theme(&self) {
+        stylesheet!("html, body", {
+            .style("background-color", "var(--tg-theme-bg-color)")
+            .style("color", "var(--tg-theme-text-color)")
+        });
+
+        call(
+            &self.web_app,
+            "setHeaderColor",
+            &[JsValue::from_str("bg_color")],
+        );
+    }
+
+    // Telegram's back button replaces the browser one, which doesn't exist in the Mini App
+    // it closes the modal if one is open, otherwise goes back in history
+    fn init_back_button(&self) {
+        let back_button = match get(&self.web_app, "BackButton") {
+            Some(back_button) => back_button,
+            None => return,
+        };
+
+        let on_click = Closure::<dyn FnMut()>::new(|| {
+            if Modal::is_open() {
+                Modal::close();
+            } else if let Some(history) = web_sys::window().and_then(|window| window.history().ok())
+            {
+                let _ = history.back();
+            }
+        });
+        call(&back_button, "onClick", &[on_click.as_ref().clone()]);
+        // lives as long as the app
+        on_click.forget();
+
+        let visible_sig = map_ref! {
+            let route = Route::signal(),
+            let modal_open = Modal::is_open_signal()
+            => {
+                // the top-level page, where there's nowhere to go back to
+                *modal_open || !matches!(route, Route::Dashboard(Dashboard::Actions))
+            }
+        };
+
+        wasm_bindgen_futures::spawn_local(visible_sig.dedupe().for_each(move |visible| {
+            call(&back_button, if visible { "show" } else { "hide" }, &[]);
+            async {}
+        }));
+    }
+}
+
+fn get(target: &JsValue, key: &str) -> Option<JsValue> {
+    Reflect::get(target, &JsValue::from_str(key))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+// older Telegram clients don't have every method, so a missing one is just skipped
+fn call(target: &JsValue, method: &str, args: &[JsValue]) {
+    let func = match get(target, method).and_then(|func| func.dyn_into::<Function>().ok()) {
+        Some(func) => func,
+        None => {
+            tracing::warn!("Telegram.WebApp has no {method}");
+            return;
+        }
+    };
+
+    let args = args.iter().collect::<web_sys::js_sys::Array>();
+
+    if let Err(err) = func.apply(target, &args) {
+        tracing::error!("Telegram.WebApp {method} failed: {err:?}");
+    }
+}
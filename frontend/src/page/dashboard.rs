@@ -1,11 +1,17 @@
 mod actions;
 mod destinations;
+mod fetch_all;
+mod history;
+mod playground;
 mod sidebar;
+mod test_send;
 
 use std::sync::LazyLock;
 
 use actions::DashboardActions;
 use destinations::DashboardDestinations;
+use history::DashboardHistory;
+use playground::DashboardPlayground;
 
 use crate::prelude::*;
 
@@ -52,7 +58,9 @@ impl DashboardPage {
                             .class(&*CONTENT)
                             .child(match dashboard {
                                 Dashboard::Actions => DashboardActions::new().render(),
-                                Dashboard::Destinations => DashboardDestinations::new().render()
+                                Dashboard::Destinations => DashboardDestinations::new().render(),
+                                Dashboard::History => DashboardHistory::new().render(),
+                                Dashboard::Playground => DashboardPlayground::new().render(),
                             })
                         }))
                     },
@@ -1,8 +1,13 @@
 mod add_modal;
+mod config_transfer;
 mod list_actions;
+mod message_preview;
+mod pause;
 
 use add_modal::AddModal;
+use config_transfer::ConfigTransferUi;
 use list_actions::ListActionsUi;
+use pause::PauseUi;
 
 use crate::{
     atoms::buttons::{Button, ButtonSize},
@@ -11,12 +16,18 @@ use crate::{
 
 pub struct DashboardActions {
     list_actions: Arc<ListActionsUi>,
+    pause: Arc<PauseUi>,
+    config_transfer: Arc<ConfigTransferUi>,
 }
 
 impl DashboardActions {
     pub fn new() -> Arc<Self> {
+        let list_actions = ListActionsUi::new();
+
         Arc::new(Self {
-            list_actions: ListActionsUi::new(),
+            config_transfer: ConfigTransferUi::new(list_actions.clone()),
+            list_actions,
+            pause: PauseUi::new(),
         })
     }
 
@@ -28,6 +39,7 @@ impl DashboardActions {
                 AUTH.check().await;
             })
             .child(state.render_add_action())
+            .child(state.config_transfer.render())
             .child(state.list_actions.render())
         })
     }
@@ -57,6 +69,7 @@ impl DashboardActions {
                 }))
                 .render()
             )
+            .child(state.pause.render())
         })
     }
 }
@@ -1,7 +1,7 @@
 use dominator_helpers::futures::AsyncLoader;
 use shared::api::action::{
-    ActionDestination, ActionDestinationId, ActionDestinationKind, AddAction, AddActionRequest,
-    ListActionDestinations, ListActionDestinationsRequest,
+    Action, ActionDestination, ActionDestinationId, ActionDestinationKind, AddAction,
+    AddActionRequest, ParseMode, UpdateAction, UpdateActionRequest,
 };
 
 use crate::{
@@ -12,26 +12,44 @@ use crate::{
         modal::Modal,
         text_area::TextArea,
     },
+    page::dashboard::fetch_all::all_destinations,
     prelude::*,
 };
 
-use super::list_actions::ListActionsUi;
+use super::{list_actions::ListActionsUi, message_preview::MessagePreview};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ActionKind {
     TelegramDm,
     TelegramGroup,
+    TelegramChannel,
+    HomeAssistant,
 }
 
+impl From<&ActionDestinationKind> for ActionKind {
+    fn from(kind: &ActionDestinationKind) -> Self {
+        match kind {
+            ActionDestinationKind::TelegramDm { .. } => Self::TelegramDm,
+            ActionDestinationKind::TelegramGroup { .. } => Self::TelegramGroup,
+            ActionDestinationKind::TelegramChannel { .. } => Self::TelegramChannel,
+            ActionDestinationKind::HomeAssistant { .. } => Self::HomeAssistant,
+        }
+    }
+}
+
+// Also used to edit an existing action, prefilled and submitted as an update
 pub struct AddModal {
     action_kind: Mutable<Option<ActionKind>>,
     action_destination_id: Mutable<Option<ActionDestinationId>>,
     prompt: Mutable<Option<String>>,
     message: Mutable<Option<String>>,
+    parse_mode: Mutable<ParseMode>,
     available_destinations: Mutable<Option<std::result::Result<Vec<ActionDestination>, String>>>,
     error: Mutable<Option<String>>,
     add_loader: AsyncLoader,
     list_actions: Arc<ListActionsUi>,
+    // the action being edited, and its index in the list
+    editing: Option<(Action, usize)>,
 }
 
 impl AddModal {
@@ -41,10 +59,27 @@ impl AddModal {
             action_destination_id: Mutable::new(None),
             prompt: Mutable::new(None),
             message: Mutable::new(None),
+            parse_mode: Mutable::new(ParseMode::Plain),
+            available_destinations: Mutable::new(None),
+            error: Mutable::new(None),
+            add_loader: AsyncLoader::new(),
+            list_actions,
+            editing: None,
+        })
+    }
+
+    pub fn new_edit(list_actions: Arc<ListActionsUi>, action: Action, index: usize) -> Arc<Self> {
+        Arc::new(Self {
+            action_kind: Mutable::new(Some(ActionKind::from(&action.destination.kind))),
+            action_destination_id: Mutable::new(Some(action.destination.id.clone())),
+            prompt: Mutable::new(Some(action.prompt.clone())),
+            message: Mutable::new(Some(action.message.clone())),
+            parse_mode: Mutable::new(action.parse_mode),
             available_destinations: Mutable::new(None),
             error: Mutable::new(None),
             add_loader: AsyncLoader::new(),
             list_actions,
+            editing: Some((action, index)),
         })
     }
 
@@ -54,9 +89,9 @@ impl AddModal {
         Modal::open(clone!(state => move || {
             html!("div", {
                 .future(clone!(state => async move {
-                    match ListActionDestinations::fetch(ListActionDestinationsRequest{cursor: None}).await {
-                        Ok(resp) => {
-                            state.available_destinations.set(Some(Ok(resp.destinations)));
+                    match all_destinations().await {
+                        Ok(destinations) => {
+                            state.available_destinations.set(Some(Ok(destinations)));
                         },
                         Err(err) => {
                             state.available_destinations.set(Some(Err(err.to_string())));
@@ -124,19 +159,28 @@ impl AddModal {
                     .with_text(&get_text!("dashboard-actions-add-kind-label"))
                     .render(Dropdown::new()
                         .with_bg_color(ColorBackground::ModalContent)
+                        .with_intial_selected(state.action_kind.get())
                         .with_options([
                             (get_text!("dashboard-actions-add-kind-tg-dm"), ActionKind::TelegramDm),
                             (get_text!("dashboard-actions-add-kind-tg-group"), ActionKind::TelegramGroup),
+                            (get_text!("dashboard-actions-add-kind-tg-channel"), ActionKind::TelegramChannel),
+                            (get_text!("dashboard-actions-add-kind-home-assistant"), ActionKind::HomeAssistant),
                         ])
                         .with_on_change(clone!(state => move |value| {
-                            state.action_kind.set_neq(Some(value.clone()));
+                            // home assistant messages can't be formatted
+                            if *value == ActionKind::HomeAssistant {
+                                state.parse_mode.set_neq(ParseMode::Plain);
+                            }
+                            // the chosen destination is only kept while the kind stays the same
+                            if state.action_kind.replace(Some(*value)) != Some(*value) {
+                                state.action_destination_id.set(None);
+                            }
                         }))
                         .render()
                     )
                 )
                 .child_signal(state.action_kind.signal().map(clone!(state => move |action_kind| {
                     action_kind.map(|action_kind| {
-                        state.action_destination_id.set(None);
                         match action_kind {
                             ActionKind::TelegramDm => html!("div", {
                                 .child(Label::new()
@@ -151,6 +195,7 @@ impl AddModal {
                                                 _ => None,
                                             }
                                         }))
+                                        .with_intial_selected(state.action_destination_id.get_cloned())
                                         .with_on_change(clone!(state => move |id| {
                                             state.action_destination_id.set(Some(id.clone()));
                                         }))
@@ -172,6 +217,49 @@ impl AddModal {
                                                 _ => None,
                                             }
                                         }))
+                                        .with_intial_selected(state.action_destination_id.get_cloned())
+                                        .with_on_change(clone!(state => move |id| {
+                                            state.action_destination_id.set(Some(id.clone()));
+                                        }))
+                                        .render()
+                                    )
+                                )
+                            }),
+                            ActionKind::TelegramChannel => html!("div", {
+                                .child(Label::new()
+                                    .with_direction(LabelDirection::Column)
+                                    .with_size(LabelSize::Lg)
+                                    .with_text(&get_text!("dashboard-actions-add-destination-tg-channel-label"))
+                                    .render(Dropdown::new()
+                                        .with_bg_color(ColorBackground::ModalContent)
+                                        .with_options(available_destinations.iter().filter_map(|destination| {
+                                            match &destination.kind {
+                                                ActionDestinationKind::TelegramChannel { .. } => Some((destination.name.clone(), destination.id.clone())),
+                                                _ => None,
+                                            }
+                                        }))
+                                        .with_intial_selected(state.action_destination_id.get_cloned())
+                                        .with_on_change(clone!(state => move |id| {
+                                            state.action_destination_id.set(Some(id.clone()));
+                                        }))
+                                        .render()
+                                    )
+                                )
+                            }),
+                            ActionKind::HomeAssistant => html!("div", {
+                                .child(Label::new()
+                                    .with_direction(LabelDirection::Column)
+                                    .with_size(LabelSize::Lg)
+                                    .with_text(&get_text!("dashboard-actions-add-destination-home-assistant-label"))
+                                    .render(Dropdown::new()
+                                        .with_bg_color(ColorBackground::ModalContent)
+                                        .with_options(available_destinations.iter().filter_map(|destination| {
+                                            match &destination.kind {
+                                                ActionDestinationKind::HomeAssistant { .. } => Some((destination.name.clone(), destination.id.clone())),
+                                                _ => None,
+                                            }
+                                        }))
+                                        .with_intial_selected(state.action_destination_id.get_cloned())
                                         .with_on_change(clone!(state => move |id| {
                                             state.action_destination_id.set(Some(id.clone()));
                                         }))
@@ -190,6 +278,7 @@ impl AddModal {
                     .with_size(LabelSize::Lg)
                     .with_text(&get_text!("dashboard-actions-add-prompt"))
                     .render(TextArea::new()
+                        .with_intial_value(state.prompt.get_cloned().unwrap_or_default())
                         .with_on_input(clone!(state => move |text| {
                             state.prompt.set(text);
                         }))
@@ -200,20 +289,40 @@ impl AddModal {
                     .with_direction(LabelDirection::Column)
                     .with_size(LabelSize::Lg)
                     .with_text(&get_text!("dashboard-actions-add-message"))
-                    .render(TextArea::new()
-                        .with_on_input(clone!(state => move |text| {
-                            state.message.set(text);
-                        }))
-                        .render()
-                    )
+                    .render(html!("div", {
+                        // home assistant messages are JSON templates, so hint at the format
+                        .child_signal(state.action_kind.signal().map(clone!(state => move |action_kind| {
+                            let text_area = TextArea::new()
+                                .with_intial_value(state.message.get_cloned().unwrap_or_default())
+                                .with_on_input(clone!(state => move |text| {
+                                    state.message.set(text);
+                                }));
+
+                            Some(match action_kind {
+                                Some(ActionKind::HomeAssistant) => text_area
+                                    .with_placeholder(get_text!("dashboard-actions-add-message-home-assistant-placeholder"))
+                                    .render(),
+                                _ => text_area.render(),
+                            })
+                        })))
+                    }))
                 )
             }))
+            .child_signal(state.action_kind.signal().map(clone!(state => move |action_kind| {
+                match action_kind {
+                    None | Some(ActionKind::HomeAssistant) => None,
+                    Some(_) => Some(state.render_formatting()),
+                }
+            })))
             .child(html!("div", {
                 .style("display", "flex")
                 .style("justify-content", "center")
                 .child(Button::new()
                     .with_disabled_signal(state.submit_disabled_signal())
-                    .with_text(&get_text!("dashboard-actions-add-submit"))
+                    .with_text(&match state.editing {
+                        Some(_) => get_text!("dashboard-actions-edit-submit"),
+                        None => get_text!("dashboard-actions-add-submit"),
+                    })
                     .with_on_click(clone!(state => move || {
                         state.error.set(None);
 
@@ -223,25 +332,45 @@ impl AddModal {
                             state.message.get_cloned(),
                         ) {
                             (Some(action_destination_id), Some(prompt), Some(message)) => {
+                                let parse_mode = state.parse_mode.get();
                                 state.add_loader.load(clone!(state, action_destination_id, prompt, message => async move {
-                                    match AddAction::fetch(AddActionRequest {
-                                        destination_id: action_destination_id,
-                                        prompt,
-                                        message,
-                                    }).await {
-                                        Ok(resp) => {
-                                            state.list_actions.add_action(resp.action);
-                                            Modal::close();
+                                    match state.editing.as_ref() {
+                                        None => match AddAction::fetch(AddActionRequest {
+                                            destination_id: action_destination_id,
+                                            prompt,
+                                            message,
+                                            parse_mode,
+                                        }).await {
+                                            Ok(resp) => {
+                                                state.list_actions.add_action(resp.action);
+                                                Modal::close();
+                                            },
+                                            Err(err) => {
+                                                state.error.set(Some(err.to_string()));
+                                            }
+                                        },
+                                        // only what changed is sent
+                                        Some((action, index)) => match UpdateAction::fetch(UpdateActionRequest {
+                                            id: action.id.clone(),
+                                            destination_id: (action_destination_id != action.destination.id).then_some(action_destination_id),
+                                            prompt: (prompt != action.prompt).then_some(prompt),
+                                            message: (message != action.message).then_some(message),
+                                            parse_mode: (parse_mode != action.parse_mode).then_some(parse_mode),
+                                            enabled: None,
+                                        }).await {
+                                            Ok(resp) => {
+                                                state.list_actions.update_action(*index, resp.action);
+                                                Modal::close();
+                                            },
+                                            Err(err) => {
+                                                state.error.set(Some(err.to_string()));
+                                            }
                                         },
-                                        Err(err) => {
-                                            state.error.set(Some(err.to_string()));
-                                        }
                                     }
                                 }));
                             }
                             _ => {}
                         }
-                        Modal::close();
                     }))
                     .render()
                 )
@@ -259,6 +388,44 @@ impl AddModal {
         })
     }
 
+    // telegram only, the parse mode and a preview of how the message will look
+    fn render_formatting(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        html!("div", {
+            .style("display", "flex")
+            .style("flex-direction", "column")
+            .style("gap", "1rem")
+            .child(Label::new()
+                .with_direction(LabelDirection::Column)
+                .with_size(LabelSize::Lg)
+                .with_text(&get_text!("dashboard-actions-add-parse-mode"))
+                .render(Dropdown::new()
+                    .with_bg_color(ColorBackground::ModalContent)
+                    .with_intial_selected(Some(state.parse_mode.get()))
+                    .with_options([
+                        (get_text!("dashboard-actions-add-parse-mode-plain"), ParseMode::Plain),
+                        (get_text!("dashboard-actions-add-parse-mode-markdown"), ParseMode::MarkdownV2),
+                        (get_text!("dashboard-actions-add-parse-mode-html"), ParseMode::Html),
+                    ])
+                    .with_on_change(clone!(state => move |value| {
+                        state.parse_mode.set_neq(*value);
+                    }))
+                    .render()
+                )
+            )
+            .child(Label::new()
+                .with_direction(LabelDirection::Column)
+                .with_size(LabelSize::Lg)
+                .with_text(&get_text!("dashboard-actions-add-preview"))
+                .render(MessagePreview::render(
+                    state.message.signal_cloned(),
+                    state.parse_mode.signal(),
+                ))
+            )
+        })
+    }
+
     fn submit_disabled_signal(self: &Arc<Self>) -> impl Signal<Item = bool> {
         map_ref! {
             let action_kind = self.action_kind.signal(),
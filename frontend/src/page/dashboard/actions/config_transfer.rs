@@ -0,0 +1,322 @@
+use dominator_helpers::futures::AsyncLoader;
+use shared::api::action::{
+    ConfigFormat, ExportConfig, ExportConfigRequest, ExportConfigResponse, ImportConfig,
+    ImportConfigRequest, ImportConfigResponse, ImportMode,
+};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{js_sys::encode_uri_component, HtmlElement, HtmlInputElement};
+
+use crate::{
+    atoms::{
+        buttons::{Button, ButtonColor},
+        dropdown::Dropdown,
+    },
+    prelude::*,
+};
+
+use super::list_actions::ListActionsUi;
+
+// Download every action as a file, or upload one from another account
+// an upload is always checked with a dry run first, and only applied once confirmed
+pub struct ConfigTransferUi {
+    list_actions: Arc<ListActionsUi>,
+    format: Mutable<ConfigFormat>,
+    mode: Mutable<ImportMode>,
+    // the uploaded file, until it's imported or cancelled
+    pending: Mutable<Option<(String, ConfigFormat)>>,
+    report: Mutable<Option<ImportConfigResponse>>,
+    done: Mutable<Option<String>>,
+    error: Mutable<Option<String>>,
+    file_input: Mutable<Option<HtmlInputElement>>,
+    loader: AsyncLoader,
+}
+
+impl ConfigTransferUi {
+    pub fn new(list_actions: Arc<ListActionsUi>) -> Arc<Self> {
+        Arc::new(Self {
+            list_actions,
+            format: Mutable::new(ConfigFormat::Json),
+            mode: Mutable::new(ImportMode::Merge),
+            pending: Mutable::new(None),
+            report: Mutable::new(None),
+            done: Mutable::new(None),
+            error: Mutable::new(None),
+            file_input: Mutable::new(None),
+            loader: AsyncLoader::new(),
+        })
+    }
+
+    pub fn render(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("flex-direction", "column")
+                .style("padding-bottom", "1rem")
+                .style("margin-bottom", "1rem")
+                .style("border-bottom", &format!("1px solid {}", ColorRaw::GreyAlt1.value()))
+            }
+        });
+
+        static ROW: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("align-items", "center")
+            }
+        });
+
+        html!("div", {
+            .class(&*CONTAINER)
+            .child(html!("div", {
+                .class(&*ROW)
+                .child(Dropdown::new()
+                    .with_intial_selected(Some(state.format.get()))
+                    .with_options([
+                        ("JSON".to_string(), ConfigFormat::Json),
+                        ("YAML".to_string(), ConfigFormat::Yaml),
+                    ])
+                    .with_on_change(clone!(state => move |format| {
+                        state.format.set_neq(*format);
+                    }))
+                    .render()
+                )
+                .child(Button::new()
+                    .with_disabled_signal(state.loader.is_loading())
+                    .with_text(&get_text!("dashboard-actions-export-button"))
+                    .with_on_click(clone!(state => move || {
+                        state.export();
+                    }))
+                    .render()
+                )
+                .child(Dropdown::new()
+                    .with_intial_selected(Some(state.mode.get()))
+                    .with_options([
+                        (get_text!("dashboard-actions-import-merge"), ImportMode::Merge),
+                        (get_text!("dashboard-actions-import-replace"), ImportMode::Replace),
+                    ])
+                    .with_on_change(clone!(state => move |mode| {
+                        if state.mode.replace(*mode) != *mode {
+                            // the report depends on the mode
+                            state.dry_run();
+                        }
+                    }))
+                    .render()
+                )
+                .child(Button::new()
+                    .with_disabled_signal(state.loader.is_loading())
+                    .with_text(&get_text!("dashboard-actions-import-button"))
+                    .with_on_click(clone!(state => move || {
+                        if let Some(input) = state.file_input.get_cloned() {
+                            input.click();
+                        }
+                    }))
+                    .render()
+                )
+                .child(html!("input" => HtmlInputElement, {
+                    .attr("type", "file")
+                    .attr("accept", ".json,.yaml,.yml")
+                    .style("display", "none")
+                    .after_inserted(clone!(state => move |input| {
+                        state.file_input.set(Some(input));
+                    }))
+                    .with_node!(input => {
+                        .event(clone!(state => move |_: events::Change| {
+                            state.read_file(&input);
+                        }))
+                    })
+                }))
+            }))
+            .child_signal(state.error.signal_cloned().map(|error| {
+                error.map(|error| {
+                    html!("div", {
+                        .class(ColorText::Error.class())
+                        .text(&error)
+                    })
+                })
+            }))
+            .child_signal(state.done.signal_cloned().map(|done| {
+                done.map(|done| {
+                    html!("div", {
+                        .class(FontSize::Lg.class())
+                        .text(&done)
+                    })
+                })
+            }))
+            .child_signal(state.report.signal_cloned().map(clone!(state => move |report| {
+                report.map(|report| state.render_report(report))
+            })))
+        })
+    }
+
+    fn render_report(self: &Arc<Self>, report: ImportConfigResponse) -> Dom {
+        let state = self;
+
+        static ROW: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("align-items", "center")
+            }
+        });
+
+        let can_apply = report.problems.is_empty();
+
+        html!("div", {
+            .child(html!("div", {
+                .class(FontSize::Lg.class())
+                .text(&get_text!("dashboard-actions-import-summary", {
+                    "added" => report.added,
+                    "updated" => report.updated,
+                    "unchanged" => report.unchanged,
+                    "removed" => report.removed
+                }))
+            }))
+            .children(report.problems.iter().map(|problem| {
+                html!("div", {
+                    .class(ColorText::Error.class())
+                    .text(problem)
+                })
+            }))
+            .child(html!("div", {
+                .class(&*ROW)
+                .apply_if(can_apply, |dom| {
+                    dom.child(Button::new()
+                        .with_disabled_signal(state.loader.is_loading())
+                        .with_text(&get_text!("dashboard-actions-import-apply"))
+                        .with_on_click(clone!(state => move || {
+                            state.apply();
+                        }))
+                        .render()
+                    )
+                })
+                .child(Button::new()
+                    .with_color(ButtonColor::Red)
+                    .with_text(&get_text!("dashboard-actions-import-cancel"))
+                    .with_on_click(clone!(state => move || {
+                        state.pending.set(None);
+                        state.report.set(None);
+                    }))
+                    .render()
+                )
+            }))
+        })
+    }
+
+    fn export(self: &Arc<Self>) {
+        let state = self;
+
+        state.error.set(None);
+
+        let format = state.format.get();
+
+        state.loader.load(clone!(state => async move {
+            match ExportConfig::fetch(ExportConfigRequest { format }).await {
+                Ok(ExportConfigResponse { content, filename }) => {
+                    download(&filename, format.mime_type(), &content);
+                },
+                Err(err) => {
+                    state.error.set(Some(err.to_string()));
+                }
+            }
+        }));
+    }
+
+    fn read_file(self: &Arc<Self>, input: &HtmlInputElement) {
+        let state = self;
+
+        let file = match input.files().and_then(|files| files.get(0)) {
+            Some(file) => file,
+            None => return,
+        };
+
+        // so the same file can be picked again
+        input.set_value("");
+
+        let name = file.name().to_lowercase();
+        let format = if name.ends_with(".yaml") || name.ends_with(".yml") {
+            ConfigFormat::Yaml
+        } else {
+            ConfigFormat::Json
+        };
+
+        state.error.set(None);
+        state.done.set(None);
+
+        // the dry run has its own loader
+        spawn_local(clone!(state => async move {
+            match JsFuture::from(file.text()).await.map(|text| text.as_string()) {
+                Ok(Some(content)) => {
+                    state.pending.set(Some((content, format)));
+                    state.dry_run();
+                },
+                _ => {
+                    state.error.set(Some(get_text!("error-api-unknown")));
+                }
+            }
+        }));
+    }
+
+    fn dry_run(self: &Arc<Self>) {
+        self.import(true);
+    }
+
+    fn apply(self: &Arc<Self>) {
+        self.import(false);
+    }
+
+    fn import(self: &Arc<Self>, dry_run: bool) {
+        let state = self;
+
+        let (content, format) = match state.pending.get_cloned() {
+            Some(pending) => pending,
+            None => return,
+        };
+
+        state.error.set(None);
+
+        let mode = state.mode.get();
+
+        state.loader.load(clone!(state => async move {
+            match ImportConfig::fetch(ImportConfigRequest { content, format, mode, dry_run }).await {
+                Ok(report) if report.applied => {
+                    state.pending.set(None);
+                    state.report.set(None);
+                    state.done.set(Some(get_text!("dashboard-actions-import-done", {
+                        "added" => report.added,
+                        "updated" => report.updated
+                    })));
+                    state.list_actions.reload();
+                },
+                Ok(report) => {
+                    state.report.set(Some(report));
+                },
+                Err(err) => {
+                    state.report.set(None);
+                    state.error.set(Some(err.to_string()));
+                }
+            }
+        }));
+    }
+}
+
+// saves the content as a file, through a temporary link
+fn download(filename: &str, mime_type: &str, content: &str) {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    let link = document
+        .create_element("a")
+        .unwrap()
+        .unchecked_into::<HtmlElement>();
+
+    let href = format!(
+        "data:{mime_type};charset=utf-8,{}",
+        String::from(encode_uri_component(content))
+    );
+
+    link.set_attribute("href", &href).unwrap();
+    link.set_attribute("download", filename).unwrap();
+    link.click();
+}
@@ -1,20 +1,37 @@
+use std::time::Duration;
+
+use dominator_helpers::futures::AsyncLoader;
+use futures_signals::signal::always;
+use futures_timer::Delay;
 use shared::api::action::{
     Action, ActionDestinationKind, DeleteAction, DeleteActionRequest, ListActions,
-    ListActionsRequest, ListActionsResponse,
+    ListActionsRequest, ListActionsResponse, ParseMode, TestAction, TestActionRequest,
+    UpdateAction, UpdateActionRequest,
 };
+use wasm_bindgen_futures::spawn_local;
 
 use crate::{
     atoms::{
         buttons::{Button, ButtonColor},
         modal::Modal,
     },
+    page::dashboard::test_send::TestSend,
     prelude::*,
-    util::signal::enumerate_signal,
+    util::{
+        mixins::{is_near_page_bottom, track_near_page_bottom},
+        signal::enumerate_signal,
+    },
 };
 
+use super::{add_modal::AddModal, message_preview::MessagePreview};
+
 pub struct ListActionsUi {
     pub actions: Mutable<Option<MutableVec<Action>>>,
     pub error: Mutable<Option<String>>,
+    // more pages are loaded as the page is scrolled down
+    next_cursor: Mutable<Option<String>>,
+    near_bottom: Mutable<bool>,
+    loader: AsyncLoader,
 }
 
 impl ListActionsUi {
@@ -22,10 +39,18 @@ impl ListActionsUi {
         Arc::new(Self {
             actions: Mutable::new(None),
             error: Mutable::new(None),
+            next_cursor: Mutable::new(None),
+            near_bottom: Mutable::new(false),
+            loader: AsyncLoader::new(),
         })
     }
 
     pub fn add_action(&self, action: Action) {
+        // it's the newest, so it'll come with the last page if that isn't loaded yet
+        if self.next_cursor.lock_ref().is_some() {
+            return;
+        }
+
         self.actions
             .lock_mut()
             .as_mut()
@@ -34,6 +59,22 @@ impl ListActionsUi {
             .push_cloned(action);
     }
 
+    pub fn update_action(&self, index: usize, action: Action) {
+        self.actions
+            .lock_mut()
+            .as_mut()
+            .unwrap()
+            .lock_mut()
+            .set_cloned(index, action);
+    }
+
+    // back to the first page, after something changed many actions at once
+    pub fn reload(self: &Arc<Self>) {
+        self.actions.set(None);
+        self.next_cursor.set(None);
+        self.load_page();
+    }
+
     pub fn render(self: &Arc<Self>) -> Dom {
         let state = self;
 
@@ -48,17 +89,23 @@ impl ListActionsUi {
             }
         });
 
+        let load_more_sig = map_ref! {
+            let near_bottom = state.near_bottom.signal(),
+            let has_more = state.next_cursor.signal_ref(|cursor| cursor.is_some()),
+            let loading = state.loader.is_loading()
+            => *near_bottom && *has_more && !*loading
+        };
+
+        state.load_page();
+
         html!("div", {
-            .future(clone!(state => async move {
-                match ListActions::fetch(ListActionsRequest { cursor: None }).await {
-                    Ok(ListActionsResponse{actions}) => {
-                        state.actions.set(Some(MutableVec::new_with_values(actions)));
-                    },
-                    Err(err) => {
-                        state.error.set(Some(err.to_string()));
-                    }
+            .apply(track_near_page_bottom(&state.near_bottom))
+            .future(load_more_sig.dedupe().for_each(clone!(state => move |load_more| {
+                if load_more {
+                    state.load_page();
                 }
-            }))
+                async {}
+            })))
             .class(FontSize::Xlg.class())
             .text(&get_text!("dashboard-actions-list-title"))
             .child_signal(state.error.signal_cloned().map(|error| {
@@ -79,13 +126,78 @@ impl ListActionsUi {
                     })
                 })
             })))
+            .child_signal(state.loader.is_loading().map(|loading| {
+                loading.then(|| {
+                    html!("div", {
+                        .class(FontSize::H2.class())
+                        .text(&get_text!("dashboard-loading"))
+                    })
+                })
+            }))
         })
     }
 
+    // the first page, or the next one
+    fn load_page(self: &Arc<Self>) {
+        let state = self;
+
+        let cursor = state.next_cursor.get_cloned();
+
+        state.loader.load(clone!(state => async move {
+            match ListActions::fetch(ListActionsRequest { cursor, ..Default::default() }).await {
+                Ok(ListActionsResponse{actions, next_cursor}) => {
+                    {
+                        let mut lock = state.actions.lock_mut();
+                        match lock.as_mut() {
+                            Some(existing) => {
+                                let mut existing = existing.lock_mut();
+                                for action in actions {
+                                    existing.push_cloned(action);
+                                }
+                            },
+                            None => *lock = Some(MutableVec::new_with_values(actions)),
+                        }
+                    }
+                    state.next_cursor.set(next_cursor);
+
+                    // once the new page is on screen, it may still not reach the bottom
+                    Delay::new(Duration::from_millis(100)).await;
+                    state.near_bottom.set_neq(is_near_page_bottom());
+                },
+                Err(err) => {
+                    state.error.set(Some(err.to_string()));
+                }
+            }
+        }));
+    }
+
+    fn set_enabled(self: &Arc<Self>, action: Action, index: usize, enabled: bool) {
+        let state = self;
+
+        spawn_local(clone!(state => async move {
+            match UpdateAction::fetch(UpdateActionRequest {
+                id: action.id.clone(),
+                destination_id: None,
+                prompt: None,
+                message: None,
+                parse_mode: None,
+                enabled: Some(enabled),
+            }).await {
+                Ok(resp) => {
+                    state.update_action(index, resp.action);
+                },
+                Err(err) => {
+                    state.error.set(Some(err.to_string()));
+                }
+            }
+        }));
+    }
+
     fn render_action(self: &Arc<Self>, action: Action, index: usize) -> Dom {
         let state = self;
 
         let action_id = action.id.clone();
+        let editing = action.clone();
 
         static CONTAINER: LazyLock<String> = LazyLock::new(|| {
             class! {
@@ -117,16 +229,62 @@ impl ListActionsUi {
                         .text(&match &action.destination.kind {
                             ActionDestinationKind::TelegramDm { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-dm-label"), action.destination.name),
                             ActionDestinationKind::TelegramGroup { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-group-label"), action.destination.name),
+                            ActionDestinationKind::TelegramChannel { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-channel-label"), action.destination.name),
+                            ActionDestinationKind::HomeAssistant { .. } => format!("{}: {}", get_text!("dashboard-destinations-home-assistant-label"), action.destination.name),
                         })
                     }),
                     html!("div", {
-                        .text(&format!("{}: {}", get_text!("dashboard-actions-add-prompt"), action.prompt))
+                        .apply_if(!action.destination.active, |dom| {
+                            dom
+                                .class(ColorText::Error.class())
+                                .text(&get_text!("dashboard-destinations-inactive"))
+                        })
                     }),
                     html!("div", {
-                        .text(&format!("{}: {}", get_text!("dashboard-actions-add-message"), action.message))
-                    })
+                        .apply_if(!action.enabled, |dom| {
+                            dom
+                                .class(ColorText::Byline.class())
+                                .text(&get_text!("dashboard-actions-disabled"))
+                        })
+                    }),
+                    html!("div", {
+                        .text(&format!("{}: {}", get_text!("dashboard-actions-add-prompt"), action.prompt))
+                    }),
+                    match action.parse_mode {
+                        ParseMode::Plain => html!("div", {
+                            .text(&format!("{}: {}", get_text!("dashboard-actions-add-message"), action.message))
+                        }),
+                        // show it the way it'll look in telegram
+                        parse_mode => html!("div", {
+                            .text(&format!("{}:", get_text!("dashboard-actions-add-message")))
+                            .child(MessagePreview::render(
+                                always(Some(action.message.clone())),
+                                always(parse_mode),
+                            ))
+                        }),
+                    }
                 ])
             }))
+            .child(Button::new()
+                .with_text(&get_text!("dashboard-actions-edit-button"))
+                .with_on_click(clone!(state, editing, index => move || {
+                    AddModal::new_edit(state.clone(), editing.clone(), index).open();
+                }))
+                .render()
+            )
+            .child(Button::new()
+                .with_text(&match action.enabled {
+                    true => get_text!("dashboard-actions-disable-button"),
+                    false => get_text!("dashboard-actions-enable-button"),
+                })
+                .with_on_click(clone!(state, action, index => move || {
+                    state.set_enabled(action.clone(), index, !action.enabled);
+                }))
+                .render()
+            )
+            .child(TestSend::render(&get_text!("dashboard-actions-test-button"), clone!(action_id => move || {
+                TestAction::fetch(TestActionRequest { id: action_id.clone() })
+            })))
             .child(Button::new()
                 .with_color(ButtonColor::Red)
                 .with_text(&get_text!("dashboard-actions-delete-button"))
@@ -0,0 +1,86 @@
+use shared::{
+    api::action::ParseMode,
+    format::{parse, FormattedSpan},
+};
+
+use crate::prelude::*;
+
+// Shows how an action message will look in Telegram, or why it won't be accepted
+pub struct MessagePreview {}
+
+impl MessagePreview {
+    pub fn render(
+        message: impl Signal<Item = Option<String>> + 'static,
+        parse_mode: impl Signal<Item = ParseMode> + 'static,
+    ) -> Dom {
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("padding", "0.5rem")
+                .style("border", &format!("1px solid {}", ColorBackground::UnderlineSecondary.value()))
+                .style("border-radius", "0.25rem")
+                .style("white-space", "pre-wrap")
+                .style("overflow-wrap", "anywhere")
+            }
+        });
+
+        let preview = map_ref! {
+            let message = message,
+            let parse_mode = parse_mode
+            => message.as_ref().map(|message| parse(message, *parse_mode))
+        };
+
+        html!("div", {
+            .child_signal(preview.map(|preview| {
+                preview.map(|preview| match preview {
+                    Ok(spans) => html!("div", {
+                        .class([&*CONTAINER, FontSize::Lg.class(), ColorText::Paragraph.class()])
+                        .children(spans.into_iter().map(Self::render_span))
+                    }),
+                    Err(err) => html!("div", {
+                        .class([FontSize::Lg.class(), ColorText::Error.class()])
+                        .text(&format!("{}: {}", get_text!("dashboard-actions-preview-error"), err))
+                    }),
+                })
+            }))
+        })
+    }
+
+    fn render_span(span: FormattedSpan) -> Dom {
+        let FormattedSpan { text, style } = span;
+
+        let tag = if style.link.is_some() { "a" } else { "span" };
+
+        html!(tag, {
+            .text(&text)
+            .apply_if(style.bold, |dom| dom.style("font-weight", "bold"))
+            .apply_if(style.italic, |dom| dom.style("font-style", "italic"))
+            .apply_if(style.underline || style.strikethrough, |dom| {
+                dom.style("text-decoration", match (style.underline, style.strikethrough) {
+                    (true, true) => "underline line-through",
+                    (true, false) => "underline",
+                    _ => "line-through",
+                })
+            })
+            .apply_if(style.spoiler, |dom| {
+                dom.style("background-color", ColorBackground::UnderlineSecondary.value())
+            })
+            .apply_if(style.code || style.pre, |dom| dom.style("font-family", "monospace"))
+            .apply_if(style.pre, |dom| dom.style("display", "block"))
+            .apply_if(style.quote, |dom| {
+                dom
+                    .style("border-left", &format!("3px solid {}", ColorBackground::UnderlinePrimary.value()))
+                    .style("padding-left", "0.5rem")
+            })
+            // only real links, the message is user input after all
+            .apply(|dom| match style.link.as_ref().filter(|url| {
+                url.starts_with("https://") || url.starts_with("http://") || url.starts_with("tg://")
+            }) {
+                Some(url) => dom
+                    .attr("href", url)
+                    .attr("target", "_blank")
+                    .attr("rel", "noopener noreferrer"),
+                None => dom,
+            })
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use dominator_helpers::futures::AsyncLoader;
+use shared::api::action::{GetPause, PauseResponse, SetPause, SetPauseRequest};
+
+use crate::{
+    atoms::{buttons::Button, dropdown::Dropdown},
+    prelude::*,
+};
+
+// Pause every action for a while, same as /pause in the bot or saying the pause phrase
+pub struct PauseUi {
+    paused_until: Mutable<Option<String>>,
+    duration_secs: Mutable<u64>,
+    error: Mutable<Option<String>>,
+    loader: AsyncLoader,
+}
+
+impl PauseUi {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            paused_until: Mutable::new(None),
+            duration_secs: Mutable::new(60 * 60 * 2),
+            error: Mutable::new(None),
+            loader: AsyncLoader::new(),
+        })
+    }
+
+    pub fn render(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("align-items", "center")
+                .style("margin-left", "auto")
+            }
+        });
+
+        html!("div", {
+            .class(&*CONTAINER)
+            .future(clone!(state => async move {
+                match GetPause::fetch().await {
+                    Ok(PauseResponse { paused_until }) => {
+                        state.paused_until.set(paused_until);
+                    },
+                    Err(err) => {
+                        state.error.set(Some(err.to_string()));
+                    }
+                }
+            }))
+            .child_signal(state.error.signal_cloned().map(|error| {
+                error.map(|error| {
+                    html!("div", {
+                        .class(ColorText::Error.class())
+                        .text(&error)
+                    })
+                })
+            }))
+            .children_signal_vec(state.paused_until.signal_cloned().map(clone!(state => move |paused_until| {
+                match paused_until {
+                    Some(paused_until) => vec![
+                        html!("div", {
+                            .class([FontSize::Lg.class(), ColorText::Error.class()])
+                            .text(&get_text!("dashboard-actions-paused-until", {"until" => paused_until}))
+                        }),
+                        Button::new()
+                            .with_disabled_signal(state.loader.is_loading())
+                            .with_text(&get_text!("dashboard-actions-resume-button"))
+                            .with_on_click(clone!(state => move || {
+                                state.set_pause(None);
+                            }))
+                            .render(),
+                    ],
+                    None => vec![
+                        Dropdown::new()
+                            .with_intial_selected(Some(state.duration_secs.get()))
+                            .with_options([
+                                (get_text!("dashboard-actions-pause-1h"), 60 * 60),
+                                (get_text!("dashboard-actions-pause-2h"), 60 * 60 * 2),
+                                (get_text!("dashboard-actions-pause-8h"), 60 * 60 * 8),
+                                (get_text!("dashboard-actions-pause-1d"), 60 * 60 * 24),
+                                (get_text!("dashboard-actions-pause-1w"), 60 * 60 * 24 * 7),
+                            ])
+                            .with_on_change(clone!(state => move |duration_secs| {
+                                state.duration_secs.set_neq(*duration_secs);
+                            }))
+                            .render(),
+                        Button::new()
+                            .with_disabled_signal(state.loader.is_loading())
+                            .with_text(&get_text!("dashboard-actions-pause-button"))
+                            .with_on_click(clone!(state => move || {
+                                state.set_pause(Some(state.duration_secs.get()));
+                            }))
+                            .render(),
+                    ],
+                }
+            })).to_signal_vec())
+        })
+    }
+
+    fn set_pause(self: &Arc<Self>, duration_secs: Option<u64>) {
+        let state = self;
+
+        state.error.set(None);
+
+        state.loader.load(clone!(state => async move {
+            match SetPause::fetch(SetPauseRequest { duration_secs }).await {
+                Ok(PauseResponse { paused_until }) => {
+                    state.paused_until.set(paused_until);
+                },
+                Err(err) => {
+                    state.error.set(Some(err.to_string()));
+                }
+            }
+        }));
+    }
+}
@@ -1,13 +1,31 @@
+mod add_home_assistant_modal;
+mod deep_link_modal;
+mod invite_contact_modal;
 mod list_destinations;
+mod list_invitations;
+use add_home_assistant_modal::AddHomeAssistantModal;
+use deep_link_modal::DeepLinkModal;
+use invite_contact_modal::InviteContactModal;
 use list_destinations::ListDestinationsUi;
+use list_invitations::ListInvitationsUi;
+use shared::api::action::DeepLinkKind;
 
-use crate::prelude::*;
+use crate::{
+    atoms::buttons::{Button, ButtonSize},
+    prelude::*,
+};
 
-pub struct DashboardDestinations {}
+pub struct DashboardDestinations {
+    list_destinations: Arc<ListDestinationsUi>,
+    list_invitations: Arc<ListInvitationsUi>,
+}
 
 impl DashboardDestinations {
     pub fn new() -> Arc<Self> {
-        Arc::new(Self {})
+        Arc::new(Self {
+            list_destinations: ListDestinationsUi::new(),
+            list_invitations: ListInvitationsUi::new(),
+        })
     }
 
     pub fn render(self: &Arc<Self>) -> Dom {
@@ -18,11 +36,14 @@ impl DashboardDestinations {
                 AUTH.check().await;
             })
             .child(state.render_add_destination())
-            .child(ListDestinationsUi::new().render())
+            .child(state.list_invitations.render())
+            .child(state.list_destinations.render())
         })
     }
 
     fn render_add_destination(self: &Arc<Self>) -> Dom {
+        let state = self;
+
         static CONTAINER: LazyLock<String> = LazyLock::new(|| {
             class! {
                 .style("display", "flex")
@@ -51,7 +72,50 @@ impl DashboardDestinations {
                 }),
                 html!("div", {
                     .text(&get_text!("dashboard-destinations-instructions-tg-group-body"))
-                })
+                }),
+                html!("div", {
+                    .class(&*FontWeight::Bold.class())
+                    .text(&get_text!("dashboard-destinations-instructions-tg-channel-title"))
+                }),
+                html!("div", {
+                    .text(&get_text!("dashboard-destinations-instructions-tg-channel-body"))
+                }),
+                html!("div", {
+                    .class(&*FontWeight::Bold.class())
+                    .text(&get_text!("dashboard-destinations-instructions-home-assistant-title"))
+                }),
+                html!("div", {
+                    .text(&get_text!("dashboard-destinations-instructions-home-assistant-body"))
+                }),
+                html!("div", {
+                    .style("display", "flex")
+                    .style("flex-wrap", "wrap")
+                    .style("gap", "1rem")
+                    .child(Button::new()
+                        .with_size(ButtonSize::Xlg)
+                        .with_text(&get_text!("dashboard-destinations-invite-contact-button"))
+                        .with_on_click(clone!(state => move || {
+                            InviteContactModal::new(state.list_invitations.clone()).open();
+                        }))
+                        .render()
+                    )
+                    .child(Button::new()
+                        .with_size(ButtonSize::Xlg)
+                        .with_text(&get_text!("dashboard-destinations-link-group-button"))
+                        .with_on_click(|| {
+                            DeepLinkModal::new(DeepLinkKind::LinkGroup).open();
+                        })
+                        .render()
+                    )
+                    .child(Button::new()
+                        .with_size(ButtonSize::Xlg)
+                        .with_text(&get_text!("dashboard-destinations-add-home-assistant-button"))
+                        .with_on_click(clone!(state => move || {
+                            AddHomeAssistantModal::new(state.list_destinations.clone()).open();
+                        }))
+                        .render()
+                    )
+                }),
             ])
         })
     }
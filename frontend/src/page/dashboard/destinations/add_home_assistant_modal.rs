@@ -0,0 +1,210 @@
+use dominator_helpers::futures::AsyncLoader;
+use shared::api::action::{
+    AddHomeAssistantDestination, AddHomeAssistantDestinationRequest, HomeAssistantTarget,
+};
+
+use crate::{
+    atoms::{
+        buttons::Button,
+        dropdown::Dropdown,
+        label::{Label, LabelDirection, LabelSize},
+        modal::Modal,
+        text_input::{TextInput, TextInputKind},
+    },
+    prelude::*,
+};
+
+use super::list_destinations::ListDestinationsUi;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Event,
+    Service,
+}
+
+pub struct AddHomeAssistantModal {
+    name: Mutable<Option<String>>,
+    base_url: Mutable<Option<String>>,
+    token: Mutable<Option<String>>,
+    target_kind: Mutable<TargetKind>,
+    event_type: Mutable<Option<String>>,
+    domain: Mutable<Option<String>>,
+    service: Mutable<Option<String>>,
+    error: Mutable<Option<String>>,
+    add_loader: AsyncLoader,
+    list_destinations: Arc<ListDestinationsUi>,
+}
+
+impl AddHomeAssistantModal {
+    pub fn new(list_destinations: Arc<ListDestinationsUi>) -> Arc<Self> {
+        Arc::new(Self {
+            name: Mutable::new(None),
+            base_url: Mutable::new(None),
+            token: Mutable::new(None),
+            target_kind: Mutable::new(TargetKind::Event),
+            event_type: Mutable::new(None),
+            domain: Mutable::new(None),
+            service: Mutable::new(None),
+            error: Mutable::new(None),
+            add_loader: AsyncLoader::new(),
+            list_destinations,
+        })
+    }
+
+    pub fn open(self: &Arc<Self>) {
+        let state = self;
+
+        static INPUTS: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("flex-direction", "column")
+                .style("justify-content", "center")
+                .style("gap", "1rem")
+            }
+        });
+
+        Modal::open(clone!(state => move || {
+            html!("div", {
+                .class(&*INPUTS)
+                .child_signal(state.error.signal_cloned().map(|error| {
+                    error.map(|error| {
+                        html!("div", {
+                            .class([FontSize::H2.class(), ColorText::Error.class()])
+                            .text(&error)
+                        })
+                    })
+                }))
+                .child(state.render_text_input("dashboard-destinations-add-home-assistant-name", TextInputKind::Text, &state.name))
+                .child(state.render_text_input("dashboard-destinations-add-home-assistant-base-url", TextInputKind::Text, &state.base_url))
+                .child(state.render_text_input("dashboard-destinations-add-home-assistant-token", TextInputKind::Password, &state.token))
+                .child(Label::new()
+                    .with_direction(LabelDirection::Column)
+                    .with_size(LabelSize::Lg)
+                    .with_text(&get_text!("dashboard-destinations-add-home-assistant-target-kind"))
+                    .render(Dropdown::new()
+                        .with_bg_color(ColorBackground::ModalContent)
+                        .with_intial_selected(Some(TargetKind::Event))
+                        .with_options([
+                            (get_text!("dashboard-destinations-add-home-assistant-target-event"), TargetKind::Event),
+                            (get_text!("dashboard-destinations-add-home-assistant-target-service"), TargetKind::Service),
+                        ])
+                        .with_on_change(clone!(state => move |value| {
+                            // the inputs are re-rendered empty, so don't keep stale values around
+                            state.event_type.set(None);
+                            state.domain.set(None);
+                            state.service.set(None);
+                            state.target_kind.set_neq(*value);
+                        }))
+                        .render()
+                    )
+                )
+                .children_signal_vec(state.target_kind.signal().map(clone!(state => move |target_kind| {
+                    match target_kind {
+                        TargetKind::Event => vec![
+                            state.render_text_input("dashboard-destinations-add-home-assistant-event-type", TextInputKind::Text, &state.event_type),
+                        ],
+                        TargetKind::Service => vec![
+                            state.render_text_input("dashboard-destinations-add-home-assistant-domain", TextInputKind::Text, &state.domain),
+                            state.render_text_input("dashboard-destinations-add-home-assistant-service", TextInputKind::Text, &state.service),
+                        ],
+                    }
+                })).to_signal_vec())
+                .child(html!("div", {
+                    .style("display", "flex")
+                    .style("justify-content", "center")
+                    .child(Button::new()
+                        .with_disabled_signal(state.submit_disabled_signal())
+                        .with_text(&get_text!("dashboard-destinations-add-home-assistant-submit"))
+                        .with_on_click(clone!(state => move || {
+                            state.error.set(None);
+
+                            let target = match state.target_kind.get() {
+                                TargetKind::Event => state.event_type.get_cloned().map(|event_type| {
+                                    HomeAssistantTarget::Event { event_type }
+                                }),
+                                TargetKind::Service => match (state.domain.get_cloned(), state.service.get_cloned()) {
+                                    (Some(domain), Some(service)) => Some(HomeAssistantTarget::Service { domain, service }),
+                                    _ => None,
+                                },
+                            };
+
+                            if let (Some(name), Some(base_url), Some(token), Some(target)) = (
+                                state.name.get_cloned(),
+                                state.base_url.get_cloned(),
+                                state.token.get_cloned(),
+                                target,
+                            ) {
+                                state.add_loader.load(clone!(state => async move {
+                                    match AddHomeAssistantDestination::fetch(AddHomeAssistantDestinationRequest {
+                                        name,
+                                        base_url,
+                                        token,
+                                        target,
+                                    }).await {
+                                        Ok(resp) => {
+                                            state.list_destinations.add_destination(resp.destination);
+                                            Modal::close();
+                                        },
+                                        Err(err) => {
+                                            state.error.set(Some(err.to_string()));
+                                        }
+                                    }
+                                }));
+                            }
+                        }))
+                        .render()
+                    )
+                }))
+                .child_signal(state.add_loader.is_loading().map(|loading| {
+                    if loading {
+                        Some(html!("div", {
+                            .class(FontSize::H2.class())
+                            .text(&get_text!("dashboard-loading"))
+                        }))
+                    } else {
+                        None
+                    }
+                }))
+            })
+        }))
+    }
+
+    fn render_text_input(
+        self: &Arc<Self>,
+        text_id: &'static str,
+        kind: TextInputKind,
+        value: &Mutable<Option<String>>,
+    ) -> Dom {
+        Label::new()
+            .with_direction(LabelDirection::Column)
+            .with_size(LabelSize::Lg)
+            .with_text(&get_text!(text_id))
+            .render(
+                TextInput::new()
+                    .with_kind(kind)
+                    .with_on_input(clone!(value => move |text| {
+                        value.set(text);
+                    }))
+                    .render(),
+            )
+    }
+
+    fn submit_disabled_signal(self: &Arc<Self>) -> impl Signal<Item = bool> {
+        map_ref! {
+            let name = self.name.signal_cloned(),
+            let base_url = self.base_url.signal_cloned(),
+            let token = self.token.signal_cloned(),
+            let target_kind = self.target_kind.signal(),
+            let event_type = self.event_type.signal_cloned(),
+            let domain = self.domain.signal_cloned(),
+            let service = self.service.signal_cloned(),
+            => {
+                let target_missing = match target_kind {
+                    TargetKind::Event => event_type.is_none(),
+                    TargetKind::Service => domain.is_none() || service.is_none(),
+                };
+                name.is_none() || base_url.is_none() || token.is_none() || target_missing
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use shared::api::{
+    action::{CreateDeepLink, CreateDeepLinkRequest, DeepLinkKind},
+    auth::DeepLinkResponse,
+};
+
+use crate::{
+    atoms::{deep_link::DeepLink, modal::Modal},
+    prelude::*,
+};
+
+// A fresh one-time link each time it's opened
+pub struct DeepLinkModal {
+    kind: DeepLinkKind,
+    deep_link: Mutable<Option<DeepLinkResponse>>,
+    error: Mutable<Option<String>>,
+}
+
+impl DeepLinkModal {
+    pub fn new(kind: DeepLinkKind) -> Arc<Self> {
+        Arc::new(Self {
+            kind,
+            deep_link: Mutable::new(None),
+            error: Mutable::new(None),
+        })
+    }
+
+    pub fn open(self: &Arc<Self>) {
+        let state = self;
+
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("flex-direction", "column")
+                .style("align-items", "center")
+                .style("gap", "1rem")
+                .style("text-align", "center")
+                .style("max-width", "30rem")
+            }
+        });
+
+        Modal::open(clone!(state => move || {
+            let instructions = match state.kind {
+                DeepLinkKind::LinkGroup => get_text!("dashboard-destinations-deep-link-group-instructions"),
+            };
+
+            html!("div", {
+                .class(&*CONTAINER)
+                .future(clone!(state => async move {
+                    if state.deep_link.lock_ref().is_some() {
+                        return;
+                    }
+                    match CreateDeepLink::fetch(CreateDeepLinkRequest { kind: state.kind }).await {
+                        Ok(deep_link) => state.deep_link.set(Some(deep_link)),
+                        Err(err) => state.error.set(Some(err.to_string())),
+                    }
+                }))
+                .child(html!("div", {
+                    .class([FontSize::Lg.class(), ColorText::Paragraph.class()])
+                    .text(&instructions)
+                }))
+                .child_signal(map_ref! {
+                    let deep_link = state.deep_link.signal_cloned(),
+                    let error = state.error.signal_cloned()
+                    => match (deep_link, error) {
+                        (_, Some(error)) => html!("div", {
+                            .class([FontSize::Lg.class(), ColorText::Error.class()])
+                            .text(error)
+                        }),
+                        (Some(deep_link), None) => DeepLink::render(deep_link),
+                        (None, None) => html!("div", {
+                            .class(FontSize::Lg.class())
+                            .text(&get_text!("dashboard-loading"))
+                        }),
+                    }
+                }.map(Some))
+            })
+        }))
+    }
+}
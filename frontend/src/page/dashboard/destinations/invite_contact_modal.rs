@@ -0,0 +1,125 @@
+use dominator_helpers::futures::AsyncLoader;
+use shared::api::{
+    action::{CreateInvitation, CreateInvitationRequest},
+    auth::DeepLinkResponse,
+};
+
+use crate::{
+    atoms::{
+        buttons::Button,
+        deep_link::DeepLink,
+        label::{Label, LabelDirection, LabelSize},
+        modal::Modal,
+        text_input::TextInput,
+    },
+    prelude::*,
+};
+
+use super::list_invitations::ListInvitationsUi;
+
+// The contact sees the reason before they accept, so it's asked for first
+pub struct InviteContactModal {
+    reason: Mutable<Option<String>>,
+    deep_link: Mutable<Option<DeepLinkResponse>>,
+    error: Mutable<Option<String>>,
+    loader: AsyncLoader,
+    list_invitations: Arc<ListInvitationsUi>,
+}
+
+impl InviteContactModal {
+    pub fn new(list_invitations: Arc<ListInvitationsUi>) -> Arc<Self> {
+        Arc::new(Self {
+            reason: Mutable::new(None),
+            deep_link: Mutable::new(None),
+            error: Mutable::new(None),
+            loader: AsyncLoader::new(),
+            list_invitations,
+        })
+    }
+
+    pub fn open(self: &Arc<Self>) {
+        let state = self;
+
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("flex-direction", "column")
+                .style("align-items", "center")
+                .style("gap", "1rem")
+                .style("text-align", "center")
+                .style("max-width", "30rem")
+            }
+        });
+
+        Modal::open(clone!(state => move || {
+            html!("div", {
+                .class(&*CONTAINER)
+                .child(html!("div", {
+                    .class([FontSize::Lg.class(), ColorText::Paragraph.class()])
+                    .text(&get_text!("dashboard-destinations-deep-link-invite-contact-instructions"))
+                }))
+                .child_signal(state.error.signal_cloned().map(|error| {
+                    error.map(|error| {
+                        html!("div", {
+                            .class([FontSize::Lg.class(), ColorText::Error.class()])
+                            .text(&error)
+                        })
+                    })
+                }))
+                .child_signal(state.deep_link.signal_cloned().map(clone!(state => move |deep_link| {
+                    Some(match deep_link {
+                        Some(deep_link) => DeepLink::render(&deep_link),
+                        None => state.render_reason(),
+                    })
+                })))
+            })
+        }))
+    }
+
+    fn render_reason(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        html!("div", {
+            .style("display", "flex")
+            .style("flex-direction", "column")
+            .style("gap", "1rem")
+            .child(Label::new()
+                .with_direction(LabelDirection::Column)
+                .with_size(LabelSize::Lg)
+                .with_text(&get_text!("dashboard-destinations-invite-contact-reason"))
+                .render(TextInput::new()
+                    .with_on_input(clone!(state => move |text| {
+                        state.reason.set(text);
+                    }))
+                    .render()
+                )
+            )
+            .child(Button::new()
+                .with_disabled_signal(map_ref! {
+                    let reason = state.reason.signal_ref(|reason| reason.is_none()),
+                    let loading = state.loader.is_loading()
+                    => *reason || *loading
+                })
+                .with_text(&get_text!("dashboard-destinations-invite-contact-submit"))
+                .with_on_click(clone!(state => move || {
+                    state.error.set(None);
+
+                    if let Some(reason) = state.reason.get_cloned() {
+                        state.loader.load(clone!(state => async move {
+                            match CreateInvitation::fetch(CreateInvitationRequest { reason }).await {
+                                Ok(resp) => {
+                                    state.list_invitations.add_invitation(resp.invitation);
+                                    state.deep_link.set(Some(resp.deep_link));
+                                },
+                                Err(err) => {
+                                    state.error.set(Some(err.get_text()));
+                                }
+                            }
+                        }));
+                    }
+                }))
+                .render()
+            )
+        })
+    }
+}
@@ -1,12 +1,39 @@
-use shared::api::action::{
-    ActionDestination, ActionDestinationKind, ListActionDestinations, ListActionDestinationsRequest,
+use std::time::Duration;
+
+use dominator_helpers::futures::AsyncLoader;
+use futures_timer::Delay;
+use shared::{
+    api::action::{
+        ActionDestination, ActionDestinationId, ActionDestinationKind, DeleteDestination,
+        DeleteDestinationRequest, ListActionDestinations, ListActionDestinationsRequest,
+        ListActionDestinationsResponse, PingDestination, PingDestinationRequest, RenameDestination,
+        RenameDestinationRequest,
+    },
+    backend::result::ApiError,
 };
 
-use crate::prelude::*;
+use crate::{
+    atoms::{
+        buttons::{Button, ButtonColor},
+        label::{Label, LabelDirection, LabelSize},
+        modal::Modal,
+        text_input::TextInput,
+    },
+    page::dashboard::test_send::TestSend,
+    prelude::*,
+    util::{
+        mixins::{is_near_page_bottom, track_near_page_bottom},
+        signal::enumerate_signal,
+    },
+};
 
 pub struct ListDestinationsUi {
-    pub destinations: Mutable<Option<Vec<ActionDestination>>>,
+    pub destinations: Mutable<Option<MutableVec<ActionDestination>>>,
     pub error: Mutable<Option<String>>,
+    // more pages are loaded as the page is scrolled down
+    next_cursor: Mutable<Option<String>>,
+    near_bottom: Mutable<bool>,
+    loader: AsyncLoader,
 }
 
 impl ListDestinationsUi {
@@ -14,16 +41,32 @@ impl ListDestinationsUi {
         Arc::new(Self {
             destinations: Mutable::new(None),
             error: Mutable::new(None),
+            next_cursor: Mutable::new(None),
+            near_bottom: Mutable::new(false),
+            loader: AsyncLoader::new(),
         })
     }
 
+    pub fn add_destination(&self, destination: ActionDestination) {
+        // it's the newest, so it'll come with the last page if that isn't loaded yet
+        if self.next_cursor.lock_ref().is_some() {
+            return;
+        }
+
+        let mut lock = self.destinations.lock_mut();
+        match lock.as_mut() {
+            Some(destinations) => destinations.lock_mut().push_cloned(destination),
+            None => *lock = Some(MutableVec::new_with_values(vec![destination])),
+        }
+    }
+
     pub fn render(self: &Arc<Self>) -> Dom {
         let state = self;
 
         static LIST: LazyLock<String> = LazyLock::new(|| {
             class! {
                 .style("display", "flex")
-                .style("gap", "1rem")
+                .style("gap", "2rem")
                 .style("margin-top", "1rem")
                 .style("width", "100%")
                 .style("justify-content", "flex-start")
@@ -31,17 +74,23 @@ impl ListDestinationsUi {
             }
         });
 
+        let load_more_sig = map_ref! {
+            let near_bottom = state.near_bottom.signal(),
+            let has_more = state.next_cursor.signal_ref(|cursor| cursor.is_some()),
+            let loading = state.loader.is_loading()
+            => *near_bottom && *has_more && !*loading
+        };
+
+        state.load_page();
+
         html!("div", {
-            .future(clone!(state => async move {
-                match ListActionDestinations::fetch(ListActionDestinationsRequest{ cursor: None }).await {
-                    Ok(resp) => {
-                        state.destinations.set(Some(resp.destinations));
-                    },
-                    Err(err) => {
-                        state.error.set(Some(err.to_string()));
-                    }
+            .apply(track_near_page_bottom(&state.near_bottom))
+            .future(load_more_sig.dedupe().for_each(clone!(state => move |load_more| {
+                if load_more {
+                    state.load_page();
                 }
-            }))
+                async {}
+            })))
             .class(FontSize::Xlg.class())
             .text(&get_text!("dashboard-destinations-list-title"))
             .child_signal(state.error.signal_cloned().map(|error| {
@@ -52,29 +101,270 @@ impl ListDestinationsUi {
                     })
                 })
             }))
-            .child_signal(state.destinations.signal_cloned().map(|destinations| {
+            .child_signal(state.destinations.signal_cloned().map(clone!(state => move |destinations| {
                 destinations.map(|destinations| {
                     html!("div", {
                         .class(&*LIST)
-                        .children(destinations.iter().map(|destination| {
-                            html!("div", {
-                                .class([&*FontSize::Xlg.class()])
-                                .children(&mut [
-                                    html!("div", {
-                                        .text(&format!("{}: {}", get_text!("dashboard-destinations-list-id"), destination.id.to_string()))
-                                    }),
-                                    html!("div", {
-                                        .text(&match &destination.kind {
-                                            ActionDestinationKind::TelegramDm { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-dm-label"), destination.name),
-                                            ActionDestinationKind::TelegramGroup { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-group-label"), destination.name),
-                                        })
-                                    }),
-                                ])
-                            })
-                        }))
+                        .children_signal_vec(enumerate_signal(destinations.signal_vec_cloned()).map(clone!(state => move |(destination, index)| {
+                            state.render_destination(destination, index)
+                        })))
+                    })
+                })
+            })))
+            .child_signal(state.loader.is_loading().map(|loading| {
+                loading.then(|| {
+                    html!("div", {
+                        .class(FontSize::H2.class())
+                        .text(&get_text!("dashboard-loading"))
                     })
                 })
             }))
         })
     }
+
+    // the first page, or the next one
+    fn load_page(self: &Arc<Self>) {
+        let state = self;
+
+        let cursor = state.next_cursor.get_cloned();
+
+        state.loader.load(clone!(state => async move {
+            match ListActionDestinations::fetch(ListActionDestinationsRequest { cursor, ..Default::default() }).await {
+                Ok(ListActionDestinationsResponse { destinations, next_cursor }) => {
+                    {
+                        let mut lock = state.destinations.lock_mut();
+                        match lock.as_mut() {
+                            Some(existing) => {
+                                let mut existing = existing.lock_mut();
+                                for destination in destinations {
+                                    existing.push_cloned(destination);
+                                }
+                            },
+                            None => *lock = Some(MutableVec::new_with_values(destinations)),
+                        }
+                    }
+                    state.next_cursor.set(next_cursor);
+
+                    // once the new page is on screen, it may still not reach the bottom
+                    Delay::new(Duration::from_millis(100)).await;
+                    state.near_bottom.set_neq(is_near_page_bottom());
+                },
+                Err(err) => {
+                    state.error.set(Some(err.to_string()));
+                }
+            }
+        }));
+    }
+
+    fn render_destination(self: &Arc<Self>, destination: ActionDestination, index: usize) -> Dom {
+        let state = self;
+
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("width", "100%")
+                .style("flex-direction", "column")
+            }
+        });
+
+        static BUTTONS: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+            }
+        });
+
+        html!("div", {
+            .class([&*CONTAINER, &*FontSize::Xlg.class()])
+            .children(&mut [
+                html!("div", {
+                    .text(&format!("{}: {}", get_text!("dashboard-destinations-list-id"), destination.id.to_string()))
+                }),
+                html!("div", {
+                    .text(&match &destination.kind {
+                        ActionDestinationKind::TelegramDm { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-dm-label"), destination.name),
+                        ActionDestinationKind::TelegramGroup { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-group-label"), destination.name),
+                        ActionDestinationKind::TelegramChannel { .. } => format!("{}: {}", get_text!("dashboard-destinations-tg-channel-label"), destination.name),
+                        ActionDestinationKind::HomeAssistant { .. } => format!("{}: {}", get_text!("dashboard-destinations-home-assistant-label"), destination.name),
+                    })
+                }),
+                html!("div", {
+                    .apply_if(!destination.active, |dom| {
+                        dom
+                            .class(ColorText::Error.class())
+                            .text(&get_text!("dashboard-destinations-inactive"))
+                    })
+                }),
+                html!("div", {
+                    .apply_if(!destination.consented, |dom| {
+                        dom
+                            .class(ColorText::Error.class())
+                            .text(&get_text!("dashboard-destinations-not-consented"))
+                    })
+                }),
+                html!("div", {
+                    .class(&*BUTTONS)
+                    .child(Button::new()
+                        .with_text(&get_text!("dashboard-destinations-rename-button"))
+                        .with_on_click(clone!(state, destination, index => move || {
+                            state.open_rename(destination.clone(), index);
+                        }))
+                        .render()
+                    )
+                    .child(Button::new()
+                        .with_color(ButtonColor::Red)
+                        .with_text(&get_text!("dashboard-destinations-delete-button"))
+                        .with_on_click(clone!(state, destination, index => move || {
+                            state.open_delete(destination.id.clone(), index);
+                        }))
+                        .render()
+                    )
+                }),
+                TestSend::render(&get_text!("dashboard-destinations-ping-button"), clone!(destination => move || {
+                    PingDestination::fetch(PingDestinationRequest { id: destination.id.clone() })
+                })),
+            ])
+        })
+    }
+
+    fn open_rename(self: &Arc<Self>, destination: ActionDestination, index: usize) {
+        let state = self;
+
+        Modal::open(clone!(state, destination => move || {
+            let name = Mutable::new(Some(destination.name.clone()));
+            let error: Mutable<Option<String>> = Mutable::new(None);
+            let loader = AsyncLoader::new();
+
+            html!("div", {
+                .style("display", "flex")
+                .style("flex-direction", "column")
+                .style("gap", "1rem")
+                .child_signal(error.signal_cloned().map(|error| {
+                    error.map(|error| {
+                        html!("div", {
+                            .class([FontSize::H2.class(), ColorText::Error.class()])
+                            .text(&error)
+                        })
+                    })
+                }))
+                .child(Label::new()
+                    .with_direction(LabelDirection::Column)
+                    .with_size(LabelSize::Lg)
+                    .with_text(&get_text!("dashboard-destinations-rename-label"))
+                    .render(TextInput::new()
+                        .with_intial_value(&destination.name)
+                        .with_on_input(clone!(name => move |text| {
+                            name.set(text);
+                        }))
+                        .render()
+                    )
+                )
+                .child(Button::new()
+                    .with_disabled_signal(name.signal_ref(|name| name.is_none()))
+                    .with_text(&get_text!("dashboard-destinations-rename-submit"))
+                    .with_on_click(clone!(state, destination, name, error, loader => move || {
+                        if let Some(name) = name.get_cloned() {
+                            loader.load(clone!(state, destination, error => async move {
+                                match RenameDestination::fetch(RenameDestinationRequest {
+                                    id: destination.id.clone(),
+                                    name: name.clone(),
+                                }).await {
+                                    Ok(_) => {
+                                        if let Some(destinations) = state.destinations.lock_ref().as_ref() {
+                                            destinations.lock_mut().set_cloned(index, ActionDestination {
+                                                name: name.trim().to_string(),
+                                                ..destination
+                                            });
+                                        }
+                                        Modal::close();
+                                    },
+                                    Err(err) => {
+                                        error.set(Some(err.get_text()));
+                                    }
+                                }
+                            }));
+                        }
+                    }))
+                    .render()
+                )
+            })
+        }));
+    }
+
+    fn open_delete(self: &Arc<Self>, id: ActionDestinationId, index: usize) {
+        let state = self;
+
+        Modal::open(clone!(state, id => move || {
+            // set when the destination still has actions, to confirm deleting them too
+            let in_use: Mutable<Option<u32>> = Mutable::new(None);
+            let error: Mutable<Option<String>> = Mutable::new(None);
+            let loader = AsyncLoader::new();
+
+            let delete = clone!(state, id, in_use, error, loader => move |cascade: bool| {
+                error.set(None);
+                loader.load(clone!(state, id, in_use, error => async move {
+                    match DeleteDestination::fetch(DeleteDestinationRequest { id, cascade }).await {
+                        Ok(_) => {
+                            if let Some(destinations) = state.destinations.lock_ref().as_ref() {
+                                destinations.lock_mut().remove(index);
+                            }
+                            Modal::close();
+                        },
+                        Err(ApiError::DestinationInUse(count)) => {
+                            in_use.set(Some(count));
+                        },
+                        Err(err) => {
+                            error.set(Some(err.get_text()));
+                        }
+                    }
+                }));
+            });
+
+            delete(false);
+
+            html!("div", {
+                .style("display", "flex")
+                .style("flex-direction", "column")
+                .style("gap", "1rem")
+                .child_signal(error.signal_cloned().map(|error| {
+                    error.map(|error| {
+                        html!("div", {
+                            .class([FontSize::H2.class(), ColorText::Error.class()])
+                            .text(&error)
+                        })
+                    })
+                }))
+                .child_signal(loader.is_loading().map(|loading| {
+                    loading.then(|| {
+                        html!("div", {
+                            .class([FontSize::H2.class()])
+                            .text(&get_text!("dashboard-please-wait"))
+                        })
+                    })
+                }))
+                .child_signal(in_use.signal().map(clone!(delete => move |in_use| {
+                    in_use.map(|count| {
+                        html!("div", {
+                            .style("display", "flex")
+                            .style("flex-direction", "column")
+                            .style("gap", "1rem")
+                            .child(html!("div", {
+                                .class([FontSize::H2.class()])
+                                .text(&get_text!("dashboard-destinations-delete-in-use", {"count" => count}))
+                            }))
+                            .child(Button::new()
+                                .with_color(ButtonColor::Red)
+                                .with_text(&get_text!("dashboard-destinations-delete-cascade"))
+                                .with_on_click(clone!(delete => move || {
+                                    delete(true);
+                                }))
+                                .render()
+                            )
+                        })
+                    })
+                })))
+            })
+        }));
+    }
 }
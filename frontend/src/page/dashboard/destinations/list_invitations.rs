@@ -0,0 +1,91 @@
+use shared::api::action::{Invitation, InvitationStatus, ListInvitations};
+
+use crate::prelude::*;
+
+pub struct ListInvitationsUi {
+    pub invitations: MutableVec<Invitation>,
+    pub error: Mutable<Option<String>>,
+}
+
+impl ListInvitationsUi {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            invitations: MutableVec::new(),
+            error: Mutable::new(None),
+        })
+    }
+
+    // newest first, same as the backend
+    pub fn add_invitation(&self, invitation: Invitation) {
+        self.invitations.lock_mut().insert_cloned(0, invitation);
+    }
+
+    pub fn render(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        static LIST: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("margin-top", "1rem")
+                .style("width", "100%")
+                .style("flex-direction", "column")
+            }
+        });
+
+        html!("div", {
+            .future(clone!(state => async move {
+                match ListInvitations::fetch().await {
+                    Ok(resp) => {
+                        state.invitations.lock_mut().replace_cloned(resp.invitations);
+                    },
+                    Err(err) => {
+                        state.error.set(Some(err.to_string()));
+                    }
+                }
+            }))
+            .style("padding-bottom", "1rem")
+            .style("margin-bottom", "1rem")
+            .style("border-bottom", &format!("1px solid {}", ColorRaw::GreyAlt1.value()))
+            .class(FontSize::Xlg.class())
+            .text(&get_text!("dashboard-destinations-invitations-title"))
+            .child_signal(state.error.signal_cloned().map(|error| {
+                error.map(|error| {
+                    html!("div", {
+                        .class([FontSize::H2.class(), ColorText::Error.class()])
+                        .text(&error)
+                    })
+                })
+            }))
+            .child(html!("div", {
+                .class(&*LIST)
+                .children_signal_vec(state.invitations.signal_vec_cloned().map(Self::render_invitation))
+            }))
+        })
+    }
+
+    fn render_invitation(invitation: Invitation) -> Dom {
+        let status = match invitation.status {
+            InvitationStatus::Pending => get_text!("dashboard-destinations-invitation-pending"),
+            InvitationStatus::Accepted => get_text!("dashboard-destinations-invitation-accepted"),
+            InvitationStatus::Declined => get_text!("dashboard-destinations-invitation-declined"),
+            InvitationStatus::Revoked => get_text!("dashboard-destinations-invitation-revoked"),
+        };
+
+        html!("div", {
+            .style("display", "flex")
+            .style("flex-direction", "column")
+            .style("gap", "0.25rem")
+            .child(html!("div", {
+                .text(&match &invitation.contact_name {
+                    Some(contact_name) => format!("{contact_name} ({status})"),
+                    None => status,
+                })
+            }))
+            .child(html!("div", {
+                .class([FontSize::Lg.class(), ColorText::Byline.class()])
+                .text(&invitation.reason)
+            }))
+        })
+    }
+}
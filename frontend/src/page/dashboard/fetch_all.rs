@@ -0,0 +1,50 @@
+// Every page at once, for dropdowns that need the whole list
+use shared::{
+    api::action::{
+        Action, ActionDestination, ListActionDestinations, ListActionDestinationsRequest,
+        ListActions, ListActionsRequest,
+    },
+    backend::result::ApiResult,
+};
+
+use crate::prelude::*;
+
+pub async fn all_actions() -> ApiResult<Vec<Action>> {
+    let mut actions = Vec::new();
+    let mut cursor = None;
+
+    loop {
+        let resp = ListActions::fetch(ListActionsRequest {
+            cursor,
+            ..Default::default()
+        })
+        .await?;
+
+        actions.extend(resp.actions);
+
+        match resp.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => return Ok(actions),
+        }
+    }
+}
+
+pub async fn all_destinations() -> ApiResult<Vec<ActionDestination>> {
+    let mut destinations = Vec::new();
+    let mut cursor = None;
+
+    loop {
+        let resp = ListActionDestinations::fetch(ListActionDestinationsRequest {
+            cursor,
+            ..Default::default()
+        })
+        .await?;
+
+        destinations.extend(resp.destinations);
+
+        match resp.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => return Ok(destinations),
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use dominator_helpers::futures::AsyncLoader;
+use shared::api::action::{
+    Action, ActionDelivery, ActionDestination, ActionDestinationId, ActionId, DeliveryStatus,
+    ListDeliveries, ListDeliveriesRequest,
+};
+
+use crate::{
+    atoms::{
+        buttons::Button,
+        dropdown::Dropdown,
+        label::{Label, LabelDirection, LabelSize},
+    },
+    page::dashboard::fetch_all::{all_actions, all_destinations},
+    prelude::*,
+};
+
+// What was sent, newest first, a page at a time
+pub struct DashboardHistory {
+    action_id: Mutable<Option<ActionId>>,
+    destination_id: Mutable<Option<ActionDestinationId>>,
+    status: Mutable<Option<DeliveryStatus>>,
+    // for the filter dropdowns
+    actions: Mutable<Vec<Action>>,
+    destinations: Mutable<Vec<ActionDestination>>,
+    deliveries: MutableVec<ActionDelivery>,
+    next_cursor: Mutable<Option<String>>,
+    error: Mutable<Option<String>>,
+    loader: AsyncLoader,
+}
+
+impl DashboardHistory {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            action_id: Mutable::new(None),
+            destination_id: Mutable::new(None),
+            status: Mutable::new(None),
+            actions: Mutable::new(Vec::new()),
+            destinations: Mutable::new(Vec::new()),
+            deliveries: MutableVec::new(),
+            next_cursor: Mutable::new(None),
+            error: Mutable::new(None),
+            loader: AsyncLoader::new(),
+        })
+    }
+
+    pub fn render(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        static LIST: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "2rem")
+                .style("margin-top", "1rem")
+                .style("width", "100%")
+                .style("flex-direction", "column")
+            }
+        });
+
+        let filters_sig = map_ref! {
+            let action_id = state.action_id.signal_cloned(),
+            let destination_id = state.destination_id.signal_cloned(),
+            let status = state.status.signal()
+            => (action_id.clone(), destination_id.clone(), *status)
+        };
+
+        html!("div", {
+            .future(async {
+                AUTH.check().await;
+            })
+            .future(clone!(state => async move {
+                if let Ok(actions) = all_actions().await {
+                    state.actions.set(actions);
+                }
+                if let Ok(destinations) = all_destinations().await {
+                    state.destinations.set(destinations);
+                }
+            }))
+            // starts over from the first page whenever a filter changes
+            .future(filters_sig.for_each(clone!(state => move |_| {
+                state.deliveries.lock_mut().clear();
+                state.next_cursor.set(None);
+                state.load_page();
+                async {}
+            })))
+            .child(state.render_filters())
+            .child_signal(state.error.signal_cloned().map(|error| {
+                error.map(|error| {
+                    html!("div", {
+                        .class([FontSize::H2.class(), ColorText::Error.class()])
+                        .text(&error)
+                    })
+                })
+            }))
+            .child(html!("div", {
+                .class(&*LIST)
+                .children_signal_vec(state.deliveries.signal_vec_cloned().map(Self::render_delivery))
+            }))
+            .child_signal(map_ref! {
+                let empty = state.deliveries.signal_vec_cloned().is_empty(),
+                let loading = state.loader.is_loading()
+                => *empty && !*loading
+            }.map(|empty| {
+                empty.then(|| {
+                    html!("div", {
+                        .class([FontSize::Xlg.class(), ColorText::Byline.class()])
+                        .text(&get_text!("dashboard-history-empty"))
+                    })
+                })
+            }))
+            .child_signal(map_ref! {
+                let has_more = state.next_cursor.signal_ref(|cursor| cursor.is_some()),
+                let loading = state.loader.is_loading()
+                => (*has_more, *loading)
+            }.map(clone!(state => move |(has_more, loading)| {
+                if loading {
+                    Some(html!("div", {
+                        .class(FontSize::H2.class())
+                        .text(&get_text!("dashboard-loading"))
+                    }))
+                } else if has_more {
+                    Some(html!("div", {
+                        .style("margin-top", "1rem")
+                        .child(Button::new()
+                            .with_text(&get_text!("dashboard-history-load-more"))
+                            .with_on_click(clone!(state => move || {
+                                state.load_page();
+                            }))
+                            .render()
+                        )
+                    }))
+                } else {
+                    None
+                }
+            })))
+        })
+    }
+
+    fn load_page(self: &Arc<Self>) {
+        let state = self;
+
+        let req = ListDeliveriesRequest {
+            action_id: state.action_id.get_cloned(),
+            destination_id: state.destination_id.get_cloned(),
+            status: state.status.get(),
+            cursor: state.next_cursor.get_cloned(),
+        };
+
+        state.error.set(None);
+
+        state.loader.load(clone!(state => async move {
+            match ListDeliveries::fetch(req).await {
+                Ok(resp) => {
+                    let mut deliveries = state.deliveries.lock_mut();
+                    for delivery in resp.deliveries {
+                        deliveries.push_cloned(delivery);
+                    }
+                    state.next_cursor.set(resp.next_cursor);
+                },
+                Err(err) => {
+                    state.error.set(Some(err.get_text()));
+                }
+            }
+        }));
+    }
+
+    fn render_filters(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("flex-wrap", "wrap")
+                .style("gap", "1rem")
+                .style("padding-bottom", "1rem")
+                .style("border-bottom", &format!("1px solid {}", ColorRaw::GreyAlt1.value()))
+            }
+        });
+
+        let all = get_text!("dashboard-history-filter-all");
+
+        html!("div", {
+            .class(&*CONTAINER)
+            .child_signal(state.actions.signal_cloned().map(clone!(state, all => move |actions| {
+                Some(Label::new()
+                    .with_direction(LabelDirection::Column)
+                    .with_size(LabelSize::Lg)
+                    .with_text(&get_text!("dashboard-history-filter-action"))
+                    .render(Dropdown::new()
+                        .with_intial_selected(Some(state.action_id.get_cloned()))
+                        .with_options(std::iter::once((all.clone(), None)).chain(actions.into_iter().map(|action| {
+                            (action.prompt, Some(action.id))
+                        })))
+                        .with_on_change(clone!(state => move |action_id| {
+                            state.action_id.set_neq(action_id.clone());
+                        }))
+                        .render()
+                    ))
+            })))
+            .child_signal(state.destinations.signal_cloned().map(clone!(state, all => move |destinations| {
+                Some(Label::new()
+                    .with_direction(LabelDirection::Column)
+                    .with_size(LabelSize::Lg)
+                    .with_text(&get_text!("dashboard-history-filter-destination"))
+                    .render(Dropdown::new()
+                        .with_intial_selected(Some(state.destination_id.get_cloned()))
+                        .with_options(std::iter::once((all.clone(), None)).chain(destinations.into_iter().map(|destination| {
+                            (destination.name, Some(destination.id))
+                        })))
+                        .with_on_change(clone!(state => move |destination_id| {
+                            state.destination_id.set_neq(destination_id.clone());
+                        }))
+                        .render()
+                    ))
+            })))
+            .child(Label::new()
+                .with_direction(LabelDirection::Column)
+                .with_size(LabelSize::Lg)
+                .with_text(&get_text!("dashboard-history-filter-status"))
+                .render(Dropdown::new()
+                    .with_intial_selected(Some(None))
+                    .with_options([
+                        (all, None),
+                        (get_text!("dashboard-history-status-sent"), Some(DeliveryStatus::Sent)),
+                        (get_text!("dashboard-history-status-failed"), Some(DeliveryStatus::Failed)),
+                        (get_text!("dashboard-history-status-skipped"), Some(DeliveryStatus::Skipped)),
+                    ])
+                    .with_on_change(clone!(state => move |status| {
+                        state.status.set_neq(*status);
+                    }))
+                    .render()
+                ))
+        })
+    }
+
+    fn render_delivery(delivery: ActionDelivery) -> Dom {
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "0.5rem")
+                .style("width", "100%")
+                .style("flex-direction", "column")
+            }
+        });
+
+        static TEXT: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("white-space", "pre-wrap")
+                .style("word-break", "break-word")
+            }
+        });
+
+        let status = match delivery.status {
+            DeliveryStatus::Sent => get_text!("dashboard-history-status-sent"),
+            DeliveryStatus::Failed => get_text!("dashboard-history-status-failed"),
+            DeliveryStatus::Skipped => get_text!("dashboard-history-status-skipped"),
+        };
+
+        let deleted = get_text!("dashboard-history-deleted");
+
+        html!("div", {
+            .class([&*CONTAINER, &*FontSize::Xlg.class()])
+            .child(html!("div", {
+                .class(ColorText::Byline.class())
+                .text(&format!("{} · {}", delivery.created_at, status))
+            }))
+            .child(html!("div", {
+                .text(&format!("{}: {}", get_text!("dashboard-history-action"), delivery.prompt.unwrap_or_else(|| deleted.clone())))
+            }))
+            .child(html!("div", {
+                .text(&format!("{}: {}", get_text!("dashboard-history-destination"), delivery.destination_name.unwrap_or(deleted)))
+            }))
+            .child(html!("div", {
+                .class([&*TEXT, FontSize::Lg.class()])
+                .text(&delivery.text)
+            }))
+            .apply_if(delivery.error.is_some(), |dom| {
+                dom.child(html!("div", {
+                    .class(ColorText::Error.class())
+                    .text(delivery.error.as_deref().unwrap_or_default())
+                }))
+            })
+        })
+    }
+}
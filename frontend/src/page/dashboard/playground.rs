@@ -0,0 +1,208 @@
+use dominator_helpers::futures::AsyncLoader;
+use shared::api::{
+    action::{DeliverySkipReason, SimulateTranscript, SimulateTranscriptRequest, SimulatedAction},
+    omi::OmiSegment,
+};
+
+use crate::{
+    atoms::{
+        label::{Label, LabelDirection, LabelSize},
+        text_area::TextArea,
+    },
+    prelude::*,
+};
+
+// Type or paste a transcript and see which actions would fire, nothing is sent
+pub struct DashboardPlayground {
+    transcript: Mutable<Option<String>>,
+    results: Mutable<Option<Vec<SimulatedAction>>>,
+    error: Mutable<Option<String>>,
+    loader: AsyncLoader,
+}
+
+impl DashboardPlayground {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            transcript: Mutable::new(None),
+            results: Mutable::new(None),
+            error: Mutable::new(None),
+            loader: AsyncLoader::new(),
+        })
+    }
+
+    pub fn render(self: &Arc<Self>) -> Dom {
+        let state = self;
+
+        static LIST: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "2rem")
+                .style("margin-top", "1rem")
+                .style("width", "100%")
+                .style("flex-direction", "column")
+            }
+        });
+
+        html!("div", {
+            .future(async {
+                AUTH.check().await;
+            })
+            // re-run on every change, the loader drops the previous request
+            .future(state.transcript.signal_cloned().for_each(clone!(state => move |transcript| {
+                state.simulate(transcript);
+                async {}
+            })))
+            .child(html!("div", {
+                .class([FontSize::Xlg.class(), ColorText::Byline.class()])
+                .style("margin-bottom", "1rem")
+                .text(&get_text!("dashboard-playground-instructions"))
+            }))
+            .child(Label::new()
+                .with_direction(LabelDirection::Column)
+                .with_size(LabelSize::Lg)
+                .with_text(&get_text!("dashboard-playground-transcript"))
+                .render(TextArea::new()
+                    .with_on_input(clone!(state => move |text| {
+                        state.transcript.set(text);
+                    }))
+                    .render()
+                )
+            )
+            .child_signal(state.error.signal_cloned().map(|error| {
+                error.map(|error| {
+                    html!("div", {
+                        .class([FontSize::H2.class(), ColorText::Error.class()])
+                        .text(&error)
+                    })
+                })
+            }))
+            .child_signal(state.results.signal_cloned().map(|results| {
+                results.map(|results| {
+                    html!("div", {
+                        .class(&*LIST)
+                        .apply_if(results.is_empty(), |dom| {
+                            dom.class(FontSize::Xlg.class())
+                                .text(&get_text!("dashboard-playground-no-actions"))
+                        })
+                        .children(results.into_iter().map(Self::render_result))
+                    })
+                })
+            }))
+        })
+    }
+
+    // each line is a segment, like Omi sends them
+    fn simulate(self: &Arc<Self>, transcript: Option<String>) {
+        let state = self;
+
+        let segments = transcript
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| OmiSegment {
+                text: line.to_string(),
+                speaker: None,
+                speaker_id: None,
+                is_user: None,
+                person_id: None,
+                start: None,
+                end: None,
+            })
+            .collect::<Vec<_>>();
+
+        state.loader.load(clone!(state => async move {
+            match SimulateTranscript::fetch(SimulateTranscriptRequest { segments }).await {
+                Ok(resp) => {
+                    state.error.set(None);
+                    state.results.set(Some(resp.actions));
+                },
+                Err(err) => {
+                    state.error.set(Some(err.get_text()));
+                }
+            }
+        }));
+    }
+
+    fn render_result(result: SimulatedAction) -> Dom {
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "0.5rem")
+                .style("width", "100%")
+                .style("flex-direction", "column")
+            }
+        });
+
+        static RENDERED: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("white-space", "pre-wrap")
+                .style("word-break", "break-word")
+            }
+        });
+
+        let (outcome, outcome_color) = match (
+            &result.matched_segment,
+            result.skipped,
+            &result.render_error,
+        ) {
+            (None, _, _) => (
+                get_text!("dashboard-playground-no-match"),
+                ColorText::Byline,
+            ),
+            (Some(_), Some(DeliverySkipReason::Paused), _) => (
+                get_text!("dashboard-playground-skipped-paused"),
+                ColorText::Error,
+            ),
+            (Some(_), Some(DeliverySkipReason::ActionDisabled), _) => (
+                get_text!("dashboard-playground-skipped-disabled"),
+                ColorText::Error,
+            ),
+            (Some(_), Some(DeliverySkipReason::DestinationInactive), _) => (
+                get_text!("dashboard-playground-skipped-inactive"),
+                ColorText::Error,
+            ),
+            (Some(_), Some(DeliverySkipReason::NotConsented), _) => (
+                get_text!("dashboard-playground-skipped-not-consented"),
+                ColorText::Error,
+            ),
+            (Some(_), None, Some(_)) => (
+                get_text!("dashboard-playground-render-error"),
+                ColorText::Error,
+            ),
+            (Some(_), None, None) => (
+                get_text!("dashboard-playground-would-send"),
+                ColorText::Success,
+            ),
+        };
+
+        html!("div", {
+            .class([&*CONTAINER, &*FontSize::Xlg.class()])
+            .child(html!("div", {
+                .class(&*FontWeight::Bold.class())
+                .text(&format!("\"{}\" → {}", result.prompt, result.destination_name))
+            }))
+            .child(html!("div", {
+                .class(outcome_color.class())
+                .text(&outcome)
+            }))
+            .apply_if(result.matched_segment.is_some(), |dom| {
+                dom.child(html!("div", {
+                    .class([FontSize::Lg.class(), ColorText::Byline.class()])
+                    .text(&format!("{}: {}", get_text!("dashboard-playground-matched"), result.matched_segment.as_deref().unwrap_or_default()))
+                }))
+            })
+            .apply_if(result.rendered.is_some(), |dom| {
+                dom.child(html!("div", {
+                    .class([&*RENDERED, FontSize::Lg.class()])
+                    .text(result.rendered.as_deref().unwrap_or_default())
+                }))
+            })
+            .apply_if(result.render_error.is_some(), |dom| {
+                dom.child(html!("div", {
+                    .class([FontSize::Lg.class(), ColorText::Error.class()])
+                    .text(result.render_error.as_deref().unwrap_or_default())
+                }))
+            })
+        })
+    }
+}
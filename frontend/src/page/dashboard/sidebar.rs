@@ -62,6 +62,8 @@ impl Sidebar {
             .children([
                 self.render_button(Dashboard::Actions),
                 self.render_button(Dashboard::Destinations),
+                self.render_button(Dashboard::History),
+                self.render_button(Dashboard::Playground),
             ])
             .child(html!("div", {
                 .class([&*BUTTON_BG_CLASS, FontSize::Xlg.class()])
@@ -92,6 +94,8 @@ impl Sidebar {
             .text(&get_text!(match dashboard {
                 Dashboard::Actions => "dashboard-label-actions",
                 Dashboard::Destinations => "dashboard-label-destinations",
+                Dashboard::History => "dashboard-label-history",
+                Dashboard::Playground => "dashboard-label-playground",
             }))
             .apply(handle_on_click(move || {
                 Route::Dashboard(dashboard).go_to_url();
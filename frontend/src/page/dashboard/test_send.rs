@@ -0,0 +1,67 @@
+use std::future::Future;
+
+use dominator_helpers::futures::AsyncLoader;
+use shared::{
+    api::action::{DeliveryStatus, TestSendResponse},
+    backend::result::ApiResult,
+};
+
+use crate::{atoms::buttons::Button, prelude::*};
+
+// A button that sends something for real, marked as a test, and says how it went
+// used for both actions and destination pings
+pub struct TestSend {}
+
+impl TestSend {
+    pub fn render<F, Fut>(text: &str, send: F) -> Dom
+    where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = ApiResult<TestSendResponse>> + 'static,
+    {
+        static CONTAINER: LazyLock<String> = LazyLock::new(|| {
+            class! {
+                .style("display", "flex")
+                .style("gap", "1rem")
+                .style("align-items", "center")
+            }
+        });
+
+        let loader = AsyncLoader::new();
+        let result: Mutable<Option<(bool, String)>> = Mutable::new(None);
+
+        html!("div", {
+            .class(&*CONTAINER)
+            .child(Button::new()
+                .with_disabled_signal(loader.is_loading())
+                .with_text(text)
+                .with_on_click(clone!(loader, result => move || {
+                    let fut = send();
+                    result.set(None);
+                    loader.load(clone!(result => async move {
+                        result.set(Some(match fut.await {
+                            Ok(TestSendResponse { status: DeliveryStatus::Sent, .. }) => {
+                                (true, get_text!("dashboard-test-send-sent"))
+                            },
+                            Ok(TestSendResponse { status: DeliveryStatus::Skipped, error }) => {
+                                (false, get_text!("dashboard-test-send-skipped", {"reason" => error.unwrap_or_default()}))
+                            },
+                            Ok(TestSendResponse { status: DeliveryStatus::Failed, error }) => {
+                                (false, get_text!("dashboard-test-send-failed", {"reason" => error.unwrap_or_default()}))
+                            },
+                            Err(err) => (false, err.get_text()),
+                        }));
+                    }));
+                }))
+                .render()
+            )
+            .child_signal(result.signal_cloned().map(|result| {
+                result.map(|(ok, text)| {
+                    html!("div", {
+                        .apply_if(!ok, |dom| dom.class(ColorText::Error.class()))
+                        .text(&text)
+                    })
+                })
+            }))
+        })
+    }
+}
@@ -1,12 +1,18 @@
-use crate::{page::landing::Landing, prelude::*};
+use shared::api::auth::{AuthRegisterDeepLink, AuthRegisterDeepLinkRequest, DeepLinkResponse};
+
+use crate::{atoms::deep_link::DeepLink, page::landing::Landing, prelude::*};
 
 pub struct RegisterStart {
     pub uid: Option<String>,
+    pub deep_link: Mutable<Option<DeepLinkResponse>>,
 }
 
 impl RegisterStart {
     pub fn new(uid: Option<String>) -> Arc<Self> {
-        Arc::new(Self { uid })
+        Arc::new(Self {
+            uid,
+            deep_link: Mutable::new(None),
+        })
     }
     pub fn render(self: &Arc<Self>) -> Dom {
         match &self.uid {
@@ -16,8 +22,17 @@ impl RegisterStart {
     }
 
     fn render_uid(self: &Arc<Self>, uid: String) -> Dom {
+        let state = self;
+
         html!("div", {
             .class(&*CLASS)
+            // the widget is enough on desktop, this is for finishing up in the Telegram app
+            .future(clone!(state, uid => async move {
+                match AuthRegisterDeepLink::fetch(AuthRegisterDeepLinkRequest { omi_uid: uid }).await {
+                    Ok(deep_link) => state.deep_link.set(Some(deep_link)),
+                    Err(err) => tracing::error!("couldn't create a registration link: {err:?}"),
+                }
+            }))
             .child(
                 html!("div", {
                     .child(html!("div", {
@@ -55,6 +70,18 @@ impl RegisterStart {
                     .attr("data-auth-url", &Route::Landing(Landing::Auth(AuthRoute::RegisterComplete{uid: uid.clone()})).link_ext())
                 })
             )
+            .child_signal(state.deep_link.signal_cloned().map(|deep_link| {
+                deep_link.map(|deep_link| {
+                    html!("div", {
+                        .class(&*BYLINE)
+                        .child(html!("div", {
+                            .class([FontSize::H3.class(), ColorText::Byline.class()])
+                            .text(&get_text!("landing-register-instructions-deep-link"))
+                        }))
+                        .child(DeepLink::render(&deep_link))
+                    })
+                })
+            }))
         })
     }
 
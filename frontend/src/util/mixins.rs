@@ -34,3 +34,46 @@ where
         })
     }
 }
+
+// keeps near_bottom up to date as the page is scrolled or resized, for infinite scroll
+// it's also set right away, a short page is already at the bottom
+pub fn track_near_page_bottom<A>(
+    near_bottom: &Mutable<bool>,
+) -> impl FnOnce(DomBuilder<A>) -> DomBuilder<A>
+where
+    A: AsRef<EventTarget>,
+{
+    let near_bottom = near_bottom.clone();
+
+    move |dom| {
+        near_bottom.set_neq(is_near_page_bottom());
+
+        apply_methods!(dom, {
+            .global_event(clone!(near_bottom => move |_: events::Scroll| {
+                near_bottom.set_neq(is_near_page_bottom());
+            }))
+            .global_event(move |_: events::Resize| {
+                near_bottom.set_neq(is_near_page_bottom());
+            })
+        })
+    }
+}
+
+// within a screen's height of the bottom, so the next page is there before it's needed
+pub fn is_near_page_bottom() -> bool {
+    let window = web_sys::window().unwrap();
+
+    let viewport_height = window
+        .inner_height()
+        .ok()
+        .and_then(|height| height.as_f64())
+        .unwrap_or_default();
+    let scroll_y = window.scroll_y().unwrap_or_default();
+    let page_height = window
+        .document()
+        .and_then(|document| document.document_element())
+        .map(|element| element.scroll_height() as f64)
+        .unwrap_or_default();
+
+    scroll_y + viewport_height * 2.0 >= page_height
+}
@@ -1,12 +1,8 @@
-use std::fmt::Display;
-
 use crate::backend::route::{ActionRoute, Route};
 use http::Method;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
-use wasm_bindgen::prelude::*;
 
-use super::{ApiBoth, ApiReq};
+use super::{auth::DeepLinkResponse, omi::OmiSegment, ApiBoth, ApiReq, ApiRes};
 
 // List Action Destinations
 pub struct ListActionDestinations {}
@@ -19,15 +15,23 @@ impl ApiBoth for ListActionDestinations {
     type Res = ListActionDestinationsResponse;
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct ListActionDestinationsRequest {
-    // TODO
+    // the previous response's next_cursor, None for the first page
+    #[serde(default)]
     pub cursor: Option<String>,
+    // capped by the server, which also has a default
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub sort: ListSort,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ListActionDestinationsResponse {
     pub destinations: Vec<ActionDestination>,
+    // None on the last page
+    pub next_cursor: Option<String>,
 }
 
 // Add Action
@@ -46,6 +50,8 @@ pub struct AddActionRequest {
     pub destination_id: ActionDestinationId,
     pub prompt: String,
     pub message: String,
+    #[serde(default)]
+    pub parse_mode: ParseMode,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -53,6 +59,38 @@ pub struct AddActionResponse {
     pub action: Action,
 }
 
+// Update Action
+// only the fields that are set are changed
+pub struct UpdateAction {}
+
+impl ApiBoth for UpdateAction {
+    const ROUTE: Route = Route::Action(ActionRoute::UpdateAction);
+    const METHOD: Method = Method::POST;
+
+    type Req = UpdateActionRequest;
+    type Res = UpdateActionResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateActionRequest {
+    pub id: ActionId,
+    #[serde(default)]
+    pub destination_id: Option<ActionDestinationId>,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub parse_mode: Option<ParseMode>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateActionResponse {
+    pub action: Action,
+}
+
 // Delete Action
 pub struct DeleteAction {}
 
@@ -68,6 +106,405 @@ pub struct DeleteActionRequest {
     pub id: ActionId,
 }
 
+// Add Home Assistant Destination
+pub struct AddHomeAssistantDestination {}
+
+impl ApiBoth for AddHomeAssistantDestination {
+    const ROUTE: Route = Route::Action(ActionRoute::AddHomeAssistantDestination);
+    const METHOD: Method = Method::POST;
+
+    type Req = AddHomeAssistantDestinationRequest;
+    type Res = AddHomeAssistantDestinationResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AddHomeAssistantDestinationRequest {
+    pub name: String,
+    pub base_url: String,
+    // long-lived access token, never sent back to the client
+    pub token: String,
+    pub target: HomeAssistantTarget,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AddHomeAssistantDestinationResponse {
+    pub destination: ActionDestination,
+}
+
+// Delete Destination
+pub struct DeleteDestination {}
+
+impl ApiReq for DeleteDestination {
+    const ROUTE: Route = Route::Action(ActionRoute::DeleteDestination);
+    const METHOD: Method = Method::POST;
+
+    type Req = DeleteDestinationRequest;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DeleteDestinationRequest {
+    pub id: ActionDestinationId,
+    // if false, fails with ApiError::DestinationInUse when actions still use it
+    pub cascade: bool,
+}
+
+// Rename Destination
+pub struct RenameDestination {}
+
+impl ApiReq for RenameDestination {
+    const ROUTE: Route = Route::Action(ActionRoute::RenameDestination);
+    const METHOD: Method = Method::POST;
+
+    type Req = RenameDestinationRequest;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RenameDestinationRequest {
+    pub id: ActionDestinationId,
+    pub name: String,
+}
+
+// Create Deep Link
+// for destinations that are linked from Telegram somewhere else
+// (inviting someone else goes through CreateInvitation, since they need to consent)
+pub struct CreateDeepLink {}
+
+impl ApiBoth for CreateDeepLink {
+    const ROUTE: Route = Route::Action(ActionRoute::CreateDeepLink);
+    const METHOD: Method = Method::POST;
+
+    type Req = CreateDeepLinkRequest;
+    type Res = DeepLinkResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateDeepLinkRequest {
+    pub kind: DeepLinkKind,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeepLinkKind {
+    // Telegram asks which group to add the bot to, and that group becomes one of our destinations
+    LinkGroup,
+}
+
+// Create Invitation
+// someone else's DM as a destination, once they accept
+pub struct CreateInvitation {}
+
+impl ApiBoth for CreateInvitation {
+    const ROUTE: Route = Route::Action(ActionRoute::CreateInvitation);
+    const METHOD: Method = Method::POST;
+
+    type Req = CreateInvitationRequest;
+    type Res = CreateInvitationResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateInvitationRequest {
+    // shown to the contact before they accept
+    pub reason: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateInvitationResponse {
+    pub invitation: Invitation,
+    pub deep_link: DeepLinkResponse,
+}
+
+// List Invitations
+pub struct ListInvitations {}
+
+impl ApiRes for ListInvitations {
+    const ROUTE: Route = Route::Action(ActionRoute::ListInvitations);
+    const METHOD: Method = Method::POST;
+
+    type Res = ListInvitationsResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ListInvitationsResponse {
+    pub invitations: Vec<Invitation>,
+}
+
+// List Deliveries
+// newest first, a page at a time
+pub struct ListDeliveries {}
+
+impl ApiBoth for ListDeliveries {
+    const ROUTE: Route = Route::Action(ActionRoute::ListDeliveries);
+    const METHOD: Method = Method::POST;
+
+    type Req = ListDeliveriesRequest;
+    type Res = ListDeliveriesResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ListDeliveriesRequest {
+    #[serde(default)]
+    pub action_id: Option<ActionId>,
+    #[serde(default)]
+    pub destination_id: Option<ActionDestinationId>,
+    #[serde(default)]
+    pub status: Option<DeliveryStatus>,
+    // next_cursor from the previous page, None for the first
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ListDeliveriesResponse {
+    pub deliveries: Vec<ActionDelivery>,
+    // None when there are no more pages
+    pub next_cursor: Option<String>,
+}
+
+// Simulate Transcript
+// runs the same matching as the Omi webhook, but nothing is sent or recorded
+pub struct SimulateTranscript {}
+
+impl ApiBoth for SimulateTranscript {
+    const ROUTE: Route = Route::Action(ActionRoute::SimulateTranscript);
+    const METHOD: Method = Method::POST;
+
+    type Req = SimulateTranscriptRequest;
+    type Res = SimulateTranscriptResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SimulateTranscriptRequest {
+    pub segments: Vec<OmiSegment>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SimulateTranscriptResponse {
+    // every action, not just the ones that would fire
+    pub actions: Vec<SimulatedAction>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SimulatedAction {
+    pub action_id: ActionId,
+    pub prompt: String,
+    pub destination_name: String,
+    // the first segment containing the prompt
+    pub matched_segment: Option<String>,
+    // matched, but wouldn't be sent
+    pub skipped: Option<DeliverySkipReason>,
+    pub would_send: bool,
+    // only for matched actions
+    pub rendered: Option<String>,
+    pub render_error: Option<String>,
+}
+
+// Test Action
+// sends the action's message right away, marked as a test, and records it like any other delivery
+pub struct TestAction {}
+
+impl ApiBoth for TestAction {
+    const ROUTE: Route = Route::Action(ActionRoute::TestAction);
+    const METHOD: Method = Method::POST;
+
+    type Req = TestActionRequest;
+    type Res = TestSendResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TestActionRequest {
+    pub id: ActionId,
+}
+
+// Ping Destination
+// a fixed test message, to check the destination is reachable before adding actions to it
+pub struct PingDestination {}
+
+impl ApiBoth for PingDestination {
+    const ROUTE: Route = Route::Action(ActionRoute::PingDestination);
+    const METHOD: Method = Method::POST;
+
+    type Req = PingDestinationRequest;
+    type Res = TestSendResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PingDestinationRequest {
+    pub id: ActionDestinationId,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TestSendResponse {
+    pub status: DeliveryStatus,
+    // why it wasn't sent, if it wasn't
+    pub error: Option<String>,
+}
+
+// Get Pause
+pub struct GetPause {}
+
+impl ApiRes for GetPause {
+    const ROUTE: Route = Route::Action(ActionRoute::GetPause);
+    const METHOD: Method = Method::POST;
+
+    type Res = PauseResponse;
+}
+
+// Set Pause
+// pauses every action for a while, or resumes them
+pub struct SetPause {}
+
+impl ApiBoth for SetPause {
+    const ROUTE: Route = Route::Action(ActionRoute::SetPause);
+    const METHOD: Method = Method::POST;
+
+    type Req = SetPauseRequest;
+    type Res = PauseResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SetPauseRequest {
+    // None resumes right away
+    pub duration_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PauseResponse {
+    // UTC, only set while paused
+    pub paused_until: Option<String>,
+}
+
+// Export Config
+// every action, referring to destinations by name so the file can be imported into another account
+pub struct ExportConfig {}
+
+impl ApiBoth for ExportConfig {
+    const ROUTE: Route = Route::Action(ActionRoute::ExportConfig);
+    const METHOD: Method = Method::POST;
+
+    type Req = ExportConfigRequest;
+    type Res = ExportConfigResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ExportConfigRequest {
+    #[serde(default)]
+    pub format: ConfigFormat,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ExportConfigResponse {
+    // a ConfigFile, encoded in the requested format
+    pub content: String,
+    pub filename: String,
+}
+
+// Import Config
+// destinations aren't created, they must already exist with the same name
+pub struct ImportConfig {}
+
+impl ApiBoth for ImportConfig {
+    const ROUTE: Route = Route::Action(ActionRoute::ImportConfig);
+    const METHOD: Method = Method::POST;
+
+    type Req = ImportConfigRequest;
+    type Res = ImportConfigResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ImportConfigRequest {
+    pub content: String,
+    #[serde(default)]
+    pub format: ConfigFormat,
+    #[serde(default)]
+    pub mode: ImportMode,
+    // only validates and reports what would change
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ImportConfigResponse {
+    // false on a dry run, or when there were any problems
+    pub applied: bool,
+    pub added: u32,
+    pub updated: u32,
+    pub unchanged: u32,
+    // only in replace mode
+    pub removed: u32,
+    // nothing is applied unless this is empty
+    pub problems: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Yaml => "application/yaml",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    // actions are matched by prompt and destination, and added or updated
+    // anything not in the file is kept
+    #[default]
+    Merge,
+    // matched the same way, but anything not in the file is deleted
+    Replace,
+}
+
+// bumped whenever the file layout changes, older versions must keep importing
+pub const CONFIG_FILE_VERSION: u32 = 1;
+
+// the exported file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigFile {
+    pub version: u32,
+    // informational, so it's clear which destinations need to exist before importing
+    #[serde(default)]
+    pub destinations: Vec<ConfigDestination>,
+    #[serde(default)]
+    pub actions: Vec<ConfigAction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigDestination {
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigAction {
+    pub prompt: String,
+    // matched by name
+    pub destination: String,
+    pub message: String,
+    #[serde(default)]
+    pub parse_mode: ParseMode,
+    #[serde(default = "config_action_enabled_default")]
+    pub enabled: bool,
+}
+
+fn config_action_enabled_default() -> bool {
+    true
+}
+
 // List Actions
 
 pub struct ListActions {}
@@ -80,15 +517,41 @@ impl ApiBoth for ListActions {
     type Res = ListActionsResponse;
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct ListActionsRequest {
-    // TODO
+    // the previous response's next_cursor, None for the first page
+    #[serde(default)]
     pub cursor: Option<String>,
+    // capped by the server, which also has a default
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub sort: ListSort,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ListActionsResponse {
     pub actions: Vec<Action>,
+    // None on the last page
+    pub next_cursor: Option<String>,
+}
+
+// by creation time, a cursor only works with the sort it came from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSort {
+    #[default]
+    Oldest,
+    Newest,
+}
+
+impl ListSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Oldest => "oldest",
+            Self::Newest => "newest",
+        }
+    }
 }
 
 // Data types
@@ -99,129 +562,187 @@ pub struct Action {
     pub destination: ActionDestination,
     pub prompt: String,
     pub message: String,
+    #[serde(default)]
+    pub parse_mode: ParseMode,
+    // disabled actions are never triggered, but are kept
+    pub enabled: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ActionDestination {
-    pub id: ActionDestinationId,
-    pub name: String,
-    pub kind: ActionDestinationKind,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// How the action message is formatted when it's sent to Telegram
+// https://core.telegram.org/bots/api#formatting-options
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub enum ActionDestinationKind {
-    TelegramDm { chat_id: i64 },
-    TelegramGroup { chat_id: i64 },
+pub enum ParseMode {
+    #[default]
+    Plain,
+    MarkdownV2,
+    Html,
 }
 
-impl ActionDestinationKind {
-    pub fn chat_id(&self) -> i64 {
+impl ParseMode {
+    // stored in the `parse_mode` column
+    pub fn as_str(&self) -> &'static str {
         match self {
-            ActionDestinationKind::TelegramDm { chat_id } => *chat_id,
-            ActionDestinationKind::TelegramGroup { chat_id } => *chat_id,
+            ParseMode::Plain => "plain",
+            ParseMode::MarkdownV2 => "markdown_v2",
+            ParseMode::Html => "html",
         }
     }
-}
-
-// TODO - make a macro for UUID newtype wrappers
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
-pub struct ActionId(Uuid);
-
-impl ActionId {
-    pub fn new(u: Uuid) -> Self {
-        Self(u)
-    }
 
-    pub fn to_string(&self) -> String {
-        self.0.simple().to_string()
+    // the value Telegram expects for parse_mode, plain text doesn't send one
+    pub fn telegram_value(&self) -> Option<&'static str> {
+        match self {
+            ParseMode::Plain => None,
+            ParseMode::MarkdownV2 => Some("MarkdownV2"),
+            ParseMode::Html => Some("HTML"),
+        }
     }
 }
 
-impl From<&ActionId> for JsValue {
-    fn from(u: &ActionId) -> Self {
-        u.to_string().into()
-    }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActionDestination {
+    pub id: ActionDestinationId,
+    pub name: String,
+    pub kind: ActionDestinationKind,
+    // false when the bot was removed or blocked, actions are skipped until it's back
+    pub active: bool,
+    // false when an invited contact sent /stop, actions are skipped until they accept a new invitation
+    pub consented: bool,
 }
 
-impl From<ActionId> for JsValue {
-    fn from(u: ActionId) -> Self {
-        u.to_string().into()
-    }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Invitation {
+    pub id: InvitationId,
+    pub reason: String,
+    pub status: InvitationStatus,
+    // whoever opened the link, if anyone has yet
+    pub contact_name: Option<String>,
+    // set once accepted
+    pub destination_id: Option<ActionDestinationId>,
+    pub created_at: String,
 }
 
-impl TryFrom<&str> for ActionId {
-    type Error = String;
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+    // accepted, and later stopped with /stop
+    Revoked,
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match Uuid::parse_str(value) {
-            Ok(u) => Ok(Self(u)),
-            Err(e) => Err(e.to_string()),
+impl InvitationStatus {
+    // stored in the `status` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvitationStatus::Pending => "pending",
+            InvitationStatus::Accepted => "accepted",
+            InvitationStatus::Declined => "declined",
+            InvitationStatus::Revoked => "revoked",
         }
     }
 }
 
-impl TryFrom<String> for ActionId {
-    type Error = String;
-
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        value.as_str().try_into()
-    }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionDestinationKind {
+    TelegramDm {
+        chat_id: i64,
+    },
+    TelegramGroup {
+        chat_id: i64,
+        // set when linked from a forum topic
+        #[serde(default)]
+        message_thread_id: Option<i64>,
+    },
+    TelegramChannel {
+        chat_id: i64,
+    },
+    HomeAssistant {
+        base_url: String,
+        target: HomeAssistantTarget,
+    },
 }
 
-impl Display for ActionId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.to_string())
+impl ActionDestinationKind {
+    pub fn telegram_chat_id(&self) -> Option<i64> {
+        match self {
+            ActionDestinationKind::TelegramDm { chat_id } => Some(*chat_id),
+            ActionDestinationKind::TelegramGroup { chat_id, .. } => Some(*chat_id),
+            ActionDestinationKind::TelegramChannel { chat_id } => Some(*chat_id),
+            ActionDestinationKind::HomeAssistant { .. } => None,
+        }
     }
 }
 
-// TODO - make a macro for UUID newtype wrappers
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
-pub struct ActionDestinationId(Uuid);
+// What gets triggered on the Home Assistant side
+// the action message is used as the JSON event data / service data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HomeAssistantTarget {
+    // POST /api/events/<event_type>
+    Event { event_type: String },
+    // POST /api/services/<domain>/<service>
+    Service { domain: String, service: String },
+}
 
-impl ActionDestinationId {
-    pub fn new(u: Uuid) -> Self {
-        Self(u)
-    }
+crate::id::uuid_newtype!(ActionId, ActionDestinationId, InvitationId, DeliveryId);
 
-    pub fn to_string(&self) -> String {
-        self.0.simple().to_string()
-    }
+// one attempt at sending a triggered action
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActionDelivery {
+    pub id: DeliveryId,
+    pub action_id: ActionId,
+    pub destination_id: ActionDestinationId,
+    // None if the action or destination has since been deleted
+    pub prompt: Option<String>,
+    pub destination_name: Option<String>,
+    // what was (or would have been) sent, after formatting
+    pub text: String,
+    // only for telegram destinations
+    pub message_id: Option<i64>,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    pub created_at: String,
 }
 
-impl From<&ActionDestinationId> for JsValue {
-    fn from(u: &ActionDestinationId) -> Self {
-        u.to_string().into()
-    }
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Sent,
+    Failed,
+    // the destination was inactive, or the contact had stopped it
+    Skipped,
 }
 
-impl From<ActionDestinationId> for JsValue {
-    fn from(u: ActionDestinationId) -> Self {
-        u.to_string().into()
-    }
+// why a triggered action isn't sent, checked in this order
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliverySkipReason {
+    Paused,
+    ActionDisabled,
+    DestinationInactive,
+    NotConsented,
 }
 
-impl TryFrom<&str> for ActionDestinationId {
-    type Error = String;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match Uuid::parse_str(value) {
-            Ok(u) => Ok(Self(u)),
-            Err(e) => Err(e.to_string()),
+impl DeliverySkipReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Paused => "all actions are paused",
+            Self::ActionDisabled => "action is disabled",
+            Self::DestinationInactive => "destination is inactive",
+            Self::NotConsented => "the invited contact has stopped it",
         }
     }
 }
 
-impl TryFrom<String> for ActionDestinationId {
-    type Error = String;
-
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        value.as_str().try_into()
-    }
-}
-
-impl Display for ActionDestinationId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.to_string())
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
     }
 }
@@ -4,7 +4,8 @@ use crate::backend::route::{AdminRoute, Route};
 
 use super::{
     auth::{AuthRegisterResponse, AuthTokenCreateResponse},
-    ApiBoth, ApiEmpty, Method,
+    telegram::TelegramScopedCommands,
+    ApiBoth, ApiEmpty, ApiRes, Method,
 };
 
 pub struct AdminTelegramSetWebHook {}
@@ -14,6 +15,57 @@ impl ApiEmpty for AdminTelegramSetWebHook {
     const METHOD: Method = Method::POST;
 }
 
+// so getUpdates polling can be used instead, polling never deletes it by itself
+pub struct AdminTelegramDeleteWebHook {}
+
+impl ApiEmpty for AdminTelegramDeleteWebHook {
+    const ROUTE: Route = Route::Admin(AdminRoute::TelegramDeleteWebHook);
+    const METHOD: Method = Method::POST;
+}
+
+// registers the command menu and bot descriptions, for every scope and language
+pub struct AdminTelegramSetCommands {}
+
+impl ApiEmpty for AdminTelegramSetCommands {
+    const ROUTE: Route = Route::Admin(AdminRoute::TelegramSetCommands);
+    const METHOD: Method = Method::POST;
+}
+
+// what's actually registered right now, for every scope and language
+// one getMyCommands call each, so it's only here and not in ServerInfo
+pub struct AdminTelegramGetCommands {}
+
+impl ApiRes for AdminTelegramGetCommands {
+    const ROUTE: Route = Route::Admin(AdminRoute::TelegramGetCommands);
+    const METHOD: Method = Method::POST;
+
+    type Res = AdminTelegramGetCommandsResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AdminTelegramGetCommandsResponse {
+    pub commands: Vec<TelegramScopedCommands>,
+}
+
+// dev-only, fetches pending updates with getUpdates instead of waiting for the webhook
+pub struct AdminTelegramPollUpdates {}
+
+impl ApiRes for AdminTelegramPollUpdates {
+    const ROUTE: Route = Route::Admin(AdminRoute::TelegramPollUpdates);
+    const METHOD: Method = Method::POST;
+
+    type Res = AdminTelegramPollUpdatesResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AdminTelegramPollUpdatesResponse {
+    pub handled: u32,
+    // updates are not retried, they're logged and skipped
+    pub failed: u32,
+    // the next update_id that will be asked for
+    pub offset: Option<i64>,
+}
+
 pub struct AdminPopulateFakeUser {}
 
 impl ApiBoth for AdminPopulateFakeUser {
@@ -57,6 +57,48 @@ pub struct AuthSigninResponse {
     pub auth_key: String,
 }
 
+//// Signin - via Telegram Mini App
+// the dashboard opened from within Telegram, which hands us signed initData instead of the login widget's params
+pub struct AuthMiniAppSignin {}
+
+impl ApiBoth for AuthMiniAppSignin {
+    const ROUTE: Route = Route::Auth(AuthRoute::MiniAppSignin);
+    const METHOD: Method = Method::POST;
+
+    type Req = AuthMiniAppSigninRequest;
+    type Res = AuthSigninResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AuthMiniAppSigninRequest {
+    // Telegram.WebApp.initData, exactly as given (still url-encoded)
+    pub init_data: String,
+}
+
+//// Register - via a Telegram deep link, for finishing the registration on mobile
+// whoever presses Start on the link becomes the Telegram side of the account
+pub struct AuthRegisterDeepLink {}
+
+impl ApiBoth for AuthRegisterDeepLink {
+    const ROUTE: Route = Route::Auth(AuthRoute::RegisterDeepLink);
+    const METHOD: Method = Method::POST;
+
+    type Req = AuthRegisterDeepLinkRequest;
+    type Res = DeepLinkResponse;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AuthRegisterDeepLinkRequest {
+    pub omi_uid: String,
+}
+
+// a one-time t.me link into the bot
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DeepLinkResponse {
+    pub url: String,
+    pub expires_in_secs: u64,
+}
+
 //// Signout - via User action
 pub struct AuthSignout {}
 
@@ -93,6 +135,8 @@ pub struct AuthCheckResponse {
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum AuthTokenKind {
     Signin,
+    // one-time /start payloads, see DeepLinkResponse
+    DeepLink,
 }
 
 impl TryFrom<String> for AuthTokenKind {
@@ -101,6 +145,7 @@ impl TryFrom<String> for AuthTokenKind {
     fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
         match value.to_lowercase().as_str() {
             "signin" => Ok(Self::Signin),
+            "deeplink" => Ok(Self::DeepLink),
             _ => Err("invalid kind"),
         }
     }
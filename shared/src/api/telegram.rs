@@ -8,7 +8,8 @@ use super::ApiReq;
 
 #[derive(Debug)]
 pub enum TelegramBotCommand {
-    Start,
+    // with the payload of a t.me/bot?start=... (or ?startgroup=...) deep link, if any
+    Start(Option<String>),
     Omi(TelegramOmiCommand),
 }
 
@@ -16,6 +17,25 @@ pub enum TelegramBotCommand {
 pub enum TelegramOmiCommand {
     LinkDm,
     LinkGroup,
+    // with the code from LinkChannelCode, channel posts don't say who sent them
+    LinkChannel(Option<String>),
+    // dm only, a one-time code for linking (or unlinking) a channel
+    LinkChannelCode,
+    // dm, group, topic or channel - whichever it's sent from
+    // channels need a code, same as LinkChannel
+    Unlink(Option<String>),
+    ListActions,
+    AddAction,
+    // 1-based index into the ListActions output
+    DeleteAction(Option<usize>),
+    ListDestinations,
+    Cancel,
+    // dm only, revokes consent given to every inviter
+    Stop,
+    // hours as it was written, or the default if not given
+    // checked by the handler, so a bad value gets the usage text instead of the default
+    Pause(Option<String>),
+    Resume,
 }
 
 #[derive(Serialize, Deserialize, Error, Debug, Clone, PartialEq)]
@@ -26,6 +46,32 @@ pub enum TelegramBotError {
     #[error("Internal telegram error: {0}")]
     Internal(String),
 
+    // the bot can't post there anymore (blocked, kicked, etc.)
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Too many requests, retry after {retry_after}s")]
+    TooManyRequests { retry_after: u64 },
+
+    // the group was upgraded to a supergroup, which has a new id
+    #[error("Chat migrated to {migrate_to_chat_id}")]
+    ChatMigrated { migrate_to_chat_id: i64 },
+
+    #[error("Telegram server error {error_code}: {description}")]
+    Server {
+        error_code: u16,
+        description: String,
+    },
+
+    #[error("Telegram error {error_code}: {description}")]
+    Api {
+        error_code: u16,
+        description: String,
+    },
+
     #[error("Unknown non-Omi command")]
     UnknownCommand(String),
 
@@ -46,18 +92,87 @@ impl TryFrom<&TelegramMessage> for TelegramBotCommand {
             if parts.len() > 0 {
                 match message.chat.chat_type {
                     TelegramChatType::Private => match parts[0] {
-                        "/start" => return Ok(TelegramBotCommand::Start),
+                        "/start" => {
+                            return Ok(TelegramBotCommand::Start(
+                                parts.get(1).map(|payload| payload.to_string()),
+                            ))
+                        }
                         "/link" => return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::LinkDm)),
+                        "/linkchannel" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::LinkChannelCode))
+                        }
+                        "/unlink" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Unlink(None)))
+                        }
+                        "/actions" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::ListActions))
+                        }
+                        "/addaction" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::AddAction))
+                        }
+                        "/delete" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::DeleteAction(
+                                parts.get(1).and_then(|n| n.parse().ok()),
+                            )))
+                        }
+                        "/destinations" => {
+                            return Ok(TelegramBotCommand::Omi(
+                                TelegramOmiCommand::ListDestinations,
+                            ))
+                        }
+                        "/cancel" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Cancel))
+                        }
+                        "/stop" => return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Stop)),
+                        "/pause" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Pause(
+                                parts.get(1).map(|hours| hours.to_string()),
+                            )))
+                        }
+                        "/resume" => {
+                            return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Resume))
+                        }
+                        // might be a reply in an ongoing conversation
                         _ => return Err(TelegramBotError::OmiCommand(text)),
                     },
                     _ => {
+                        // a ?startgroup= link arrives as "/start@bot_username payload"
+                        // a plain /start in a group is just ignored, like any other command
+                        if (parts[0] == "/start" || parts[0].starts_with("/start@"))
+                            && parts.len() > 1
+                        {
+                            return Ok(TelegramBotCommand::Start(Some(parts[1].to_string())));
+                        }
+
                         if parts[0] == "/omi" && parts.len() > 1 {
-                            match &parts[1..] {
-                                ["link"] => {
+                            let is_channel = message.chat.chat_type == TelegramChatType::Channel;
+
+                            match (&parts[1..], is_channel) {
+                                (["link"], false) => {
                                     return Ok(TelegramBotCommand::Omi(
                                         TelegramOmiCommand::LinkGroup,
                                     ))
                                 }
+                                (["link"], true) => {
+                                    return Ok(TelegramBotCommand::Omi(
+                                        TelegramOmiCommand::LinkChannel(None),
+                                    ))
+                                }
+                                (["link", code], true) => {
+                                    return Ok(TelegramBotCommand::Omi(
+                                        TelegramOmiCommand::LinkChannel(Some(code.to_string())),
+                                    ))
+                                }
+                                (["unlink"], _) => {
+                                    return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Unlink(
+                                        None,
+                                    )))
+                                }
+                                (["unlink", code], true) => {
+                                    return Ok(TelegramBotCommand::Omi(TelegramOmiCommand::Unlink(
+                                        Some(code.to_string()),
+                                    )))
+                                }
                                 _ => return Err(TelegramBotError::OmiCommand(text)),
                             }
                         }
@@ -87,16 +202,54 @@ pub struct TelegramWebHookRequest {
     pub edited_message: Option<TelegramMessage>,
     pub channel_post: Option<TelegramMessage>,
     pub edited_channel_post: Option<TelegramMessage>,
+    // the bot itself was added, removed, blocked, etc.
+    pub my_chat_member: Option<TelegramChatMemberUpdated>,
+    // an inline keyboard button was pressed
+    pub callback_query: Option<TelegramCallbackQuery>,
+}
+
+// https://core.telegram.org/bots/api#callbackquery
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TelegramCallbackQuery {
+    pub id: String,
+    pub from: TelegramUser,
+    // the message with the button, if it's not too old
+    pub message: Option<TelegramMessage>,
+    pub data: Option<String>,
+}
+
+// https://core.telegram.org/bots/api#inlinekeyboardbutton
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TelegramInlineKeyboardButton {
+    pub text: String,
+    pub callback_data: String,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TelegramMessage {
     pub message_id: i64,
+    // only meaningful for forum topics if is_topic_message is set
+    // (otherwise it may be a reply thread)
     pub message_thread_id: Option<i64>,
-    pub from: TelegramUser,
+    pub is_topic_message: Option<bool>,
+    // empty for channel posts
+    pub from: Option<TelegramUser>,
+    pub sender_chat: Option<TelegramChat>,
     pub chat: TelegramChat,
     pub date: u64,
     pub text: Option<String>,
+    // service message sent to the old group when it's upgraded to a supergroup
+    pub migrate_to_chat_id: Option<i64>,
+}
+
+impl TelegramMessage {
+    // the forum topic this message was sent in, if any
+    pub fn topic_thread_id(&self) -> Option<i64> {
+        match self.is_topic_message {
+            Some(true) => self.message_thread_id,
+            _ => None,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -130,6 +283,74 @@ pub enum TelegramChatType {
     Channel,
 }
 
+// https://core.telegram.org/bots/api#chatmember
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TelegramChatMember {
+    pub status: TelegramChatMemberStatus,
+    pub user: TelegramUser,
+    // only sent for restricted members
+    pub can_send_messages: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TelegramChatMemberStatus {
+    Creator,
+    Administrator,
+    Member,
+    Restricted,
+    Left,
+    Kicked,
+}
+
+impl TelegramChatMemberStatus {
+    pub fn is_present(&self) -> bool {
+        !matches!(self, Self::Left | Self::Kicked)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        matches!(self, Self::Creator | Self::Administrator)
+    }
+}
+
+// https://core.telegram.org/bots/api#chatmemberupdated
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TelegramChatMemberUpdated {
+    pub chat: TelegramChat,
+    pub from: TelegramUser,
+    pub date: u64,
+    pub old_chat_member: TelegramChatMember,
+    pub new_chat_member: TelegramChatMember,
+}
+
+// what shows up in the bot's command menu
+// https://core.telegram.org/bots/api#botcommand
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TelegramCommandInfo {
+    // without the leading slash
+    pub command: String,
+    pub description: String,
+}
+
+// https://core.telegram.org/bots/api#botcommandscope
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TelegramCommandScope {
+    Default,
+    AllPrivateChats,
+    AllGroupChats,
+    AllChatAdministrators,
+}
+
+// the commands registered for one scope and language
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TelegramScopedCommands {
+    pub scope: TelegramCommandScope,
+    // None is the fallback for every language without its own commands
+    pub language_code: Option<String>,
+    pub commands: Vec<TelegramCommandInfo>,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TelegramWebHookInfo {
     pub url: String,
@@ -20,6 +20,28 @@ pub enum ApiError {
     #[error("omi error: {0}")]
     Omi(OmiHookError),
 
+    #[error("home assistant error: {0}")]
+    HomeAssistant(String),
+
+    #[error("validation error: {0}")]
+    Validation(String),
+
+    // deleting without cascade would leave this many actions behind
+    #[error("destination is used by {0} actions")]
+    DestinationInUse(u32),
+
+    // retry after this many seconds
+    #[error("too many requests, retry in {0}s")]
+    RateLimited(u64),
+
+    // a UNIQUE or PRIMARY KEY constraint, with the table.column it failed on
+    #[error("already exists: {0}")]
+    AlreadyExists(String),
+
+    // a FOREIGN KEY constraint, whatever it refers to doesn't exist (anymore)
+    #[error("refers to something that doesn't exist")]
+    MissingReference,
+
     #[error("kv error: {0}")]
     Kv(String),
 
@@ -32,6 +54,21 @@ pub enum ApiError {
 
 pub type ApiResult<T> = Result<T, ApiError>;
 
+impl ApiError {
+    // D1 only reports which constraint failed in the message, e.g.
+    // "D1_ERROR: UNIQUE constraint failed: omi_account.id: SQLITE_CONSTRAINT"
+    pub fn from_db_message(message: &str) -> Self {
+        if let Some((_, rest)) = message.split_once("UNIQUE constraint failed: ") {
+            let column = rest.split([':', ',', ' ']).next().unwrap_or_default();
+            Self::AlreadyExists(column.to_string())
+        } else if message.contains("FOREIGN KEY constraint failed") {
+            Self::MissingReference
+        } else {
+            Self::Db(message.to_string())
+        }
+    }
+}
+
 impl From<JsValue> for ApiError {
     fn from(err: JsValue) -> Self {
         Self::Unknown(format!("{:?}", err))
@@ -65,3 +102,74 @@ pub enum AuthError {
     #[error("terms have not been agreed")]
     TermsNotAgreed,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn already_exists(message: &str) -> Option<String> {
+        match ApiError::from_db_message(message) {
+            ApiError::AlreadyExists(column) => Some(column),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn unique_constraint() {
+        // registration relies on these to tell which account already exists
+        assert_eq!(
+            already_exists("UNIQUE constraint failed: omi_account.id: SQLITE_CONSTRAINT")
+                .as_deref(),
+            Some("omi_account.id")
+        );
+        assert_eq!(
+            already_exists(
+                "D1_ERROR: UNIQUE constraint failed: telegram_account.id: SQLITE_CONSTRAINT"
+            )
+            .as_deref(),
+            Some("telegram_account.id")
+        );
+        assert_eq!(
+            already_exists("UNIQUE constraint failed: telegram_account.user_id").as_deref(),
+            Some("telegram_account.user_id")
+        );
+    }
+
+    #[test]
+    fn unique_constraint_multiple_columns() {
+        // the first column is enough to know which table it was
+        assert_eq!(
+            already_exists(
+                "D1_ERROR: UNIQUE constraint failed: destination.user_id, destination.name: SQLITE_CONSTRAINT"
+            )
+            .as_deref(),
+            Some("destination.user_id")
+        );
+    }
+
+    #[test]
+    fn foreign_key_constraint() {
+        assert!(matches!(
+            ApiError::from_db_message("FOREIGN KEY constraint failed"),
+            ApiError::MissingReference
+        ));
+        assert!(matches!(
+            ApiError::from_db_message("D1_ERROR: FOREIGN KEY constraint failed: SQLITE_CONSTRAINT"),
+            ApiError::MissingReference
+        ));
+    }
+
+    #[test]
+    fn other_errors_stay_db() {
+        for message in [
+            "D1_ERROR: no such table: user_acount: SQLITE_ERROR",
+            "NOT NULL constraint failed: destination.name",
+            "",
+        ] {
+            match ApiError::from_db_message(message) {
+                ApiError::Db(db_message) => assert_eq!(db_message, message),
+                err => panic!("{message:?} became {err:?}"),
+            }
+        }
+    }
+}
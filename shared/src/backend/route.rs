@@ -12,6 +12,8 @@ pub enum Route {
 pub enum AuthRoute {
     Register,
     Signin,
+    MiniAppSignin,
+    RegisterDeepLink,
     Check,
     Signout,
 }
@@ -19,15 +21,34 @@ pub enum AuthRoute {
 #[derive(Debug, Clone)]
 pub enum AdminRoute {
     TelegramSetWebHook,
+    TelegramDeleteWebHook,
+    TelegramSetCommands,
+    TelegramGetCommands,
+    TelegramPollUpdates,
     PopulateFakeUser,
 }
 
 #[derive(Debug, Clone)]
 pub enum ActionRoute {
     ListDestinations,
+    AddHomeAssistantDestination,
     AddAction,
     DeleteAction,
     ListActions,
+    DeleteDestination,
+    RenameDestination,
+    CreateDeepLink,
+    CreateInvitation,
+    ListInvitations,
+    ListDeliveries,
+    SimulateTranscript,
+    TestAction,
+    PingDestination,
+    UpdateAction,
+    GetPause,
+    SetPause,
+    ExportConfig,
+    ImportConfig,
 }
 
 impl Route {
@@ -74,6 +95,9 @@ impl Route {
                 // these just need to set the cookie, no auth checks
                 AuthRoute::Register => RouteAuthKind::NoAuthCookieSetter,
                 AuthRoute::Signin => RouteAuthKind::NoAuthCookieSetter,
+                AuthRoute::MiniAppSignin => RouteAuthKind::NoAuthCookieSetter,
+                // same trust as the register page itself, the Telegram side is proven by the bot
+                AuthRoute::RegisterDeepLink => RouteAuthKind::None,
                 // signout is allowed even if we've already "signed out everywhere"
                 AuthRoute::Signout => RouteAuthKind::PartialAuthTokenOnly,
             },
@@ -93,6 +117,8 @@ impl AuthRoute {
             ["check"] => Some(Self::Check),
             ["signout"] => Some(Self::Signout),
             ["signin"] => Some(Self::Signin),
+            ["mini-app-signin"] => Some(Self::MiniAppSignin),
+            ["register-deep-link"] => Some(Self::RegisterDeepLink),
             _ => None,
         }
     }
@@ -102,6 +128,10 @@ impl AdminRoute {
     pub fn try_from_paths(paths: &[&str]) -> Option<Self> {
         match *paths {
             ["tg", "set-web-hook"] => Some(Self::TelegramSetWebHook),
+            ["tg", "delete-web-hook"] => Some(Self::TelegramDeleteWebHook),
+            ["tg", "set-commands"] => Some(Self::TelegramSetCommands),
+            ["tg", "get-commands"] => Some(Self::TelegramGetCommands),
+            ["tg", "poll-updates"] => Some(Self::TelegramPollUpdates),
             ["populate-fake-user"] => Some(Self::PopulateFakeUser),
             _ => None,
         }
@@ -112,9 +142,24 @@ impl ActionRoute {
     pub fn try_from_paths(paths: &[&str]) -> Option<Self> {
         match *paths {
             ["list-destinations"] => Some(Self::ListDestinations),
+            ["add-home-assistant-destination"] => Some(Self::AddHomeAssistantDestination),
             ["add-action"] => Some(Self::AddAction),
             ["delete-action"] => Some(Self::DeleteAction),
             ["list-actions"] => Some(Self::ListActions),
+            ["delete-destination"] => Some(Self::DeleteDestination),
+            ["rename-destination"] => Some(Self::RenameDestination),
+            ["create-deep-link"] => Some(Self::CreateDeepLink),
+            ["create-invitation"] => Some(Self::CreateInvitation),
+            ["list-invitations"] => Some(Self::ListInvitations),
+            ["list-deliveries"] => Some(Self::ListDeliveries),
+            ["simulate-transcript"] => Some(Self::SimulateTranscript),
+            ["test-action"] => Some(Self::TestAction),
+            ["ping-destination"] => Some(Self::PingDestination),
+            ["update-action"] => Some(Self::UpdateAction),
+            ["get-pause"] => Some(Self::GetPause),
+            ["set-pause"] => Some(Self::SetPause),
+            ["export-config"] => Some(Self::ExportConfig),
+            ["import-config"] => Some(Self::ImportConfig),
             _ => None,
         }
     }
@@ -147,6 +192,8 @@ impl std::fmt::Display for AuthRoute {
             Self::Signout => "signout".to_string(),
             Self::Check => "check".to_string(),
             Self::Signin => "signin".to_string(),
+            Self::MiniAppSignin => "mini-app-signin".to_string(),
+            Self::RegisterDeepLink => "register-deep-link".to_string(),
         };
 
         write!(f, "{}", s)
@@ -157,6 +204,10 @@ impl std::fmt::Display for AdminRoute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s: String = match self {
             Self::TelegramSetWebHook => "tg/set-web-hook".to_string(),
+            Self::TelegramDeleteWebHook => "tg/delete-web-hook".to_string(),
+            Self::TelegramSetCommands => "tg/set-commands".to_string(),
+            Self::TelegramGetCommands => "tg/get-commands".to_string(),
+            Self::TelegramPollUpdates => "tg/poll-updates".to_string(),
             Self::PopulateFakeUser => "populate-fake-user".to_string(),
         };
 
@@ -168,9 +219,24 @@ impl std::fmt::Display for ActionRoute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s: String = match self {
             Self::ListDestinations => "list-destinations".to_string(),
+            Self::AddHomeAssistantDestination => "add-home-assistant-destination".to_string(),
             Self::AddAction => "add-action".to_string(),
             Self::DeleteAction => "delete-action".to_string(),
             Self::ListActions => "list-actions".to_string(),
+            Self::DeleteDestination => "delete-destination".to_string(),
+            Self::RenameDestination => "rename-destination".to_string(),
+            Self::CreateDeepLink => "create-deep-link".to_string(),
+            Self::CreateInvitation => "create-invitation".to_string(),
+            Self::ListInvitations => "list-invitations".to_string(),
+            Self::ListDeliveries => "list-deliveries".to_string(),
+            Self::SimulateTranscript => "simulate-transcript".to_string(),
+            Self::TestAction => "test-action".to_string(),
+            Self::PingDestination => "ping-destination".to_string(),
+            Self::UpdateAction => "update-action".to_string(),
+            Self::GetPause => "get-pause".to_string(),
+            Self::SetPause => "set-pause".to_string(),
+            Self::ExportConfig => "export-config".to_string(),
+            Self::ImportConfig => "import-config".to_string(),
         };
 
         write!(f, "{}", s)
@@ -2,6 +2,13 @@ use crate::backend::result::ApiError;
 
 impl From<worker::Error> for ApiError {
     fn from(err: worker::Error) -> Self {
-        Self::Unknown(err.to_string())
+        match err {
+            worker::Error::D1(err) => Self::from_db_message(&format!(
+                "{}: {}",
+                String::from(AsRef::<worker::js_sys::Error>::as_ref(&err).message()),
+                err.cause()
+            )),
+            err => Self::Unknown(err.to_string()),
+        }
     }
 }
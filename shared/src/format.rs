@@ -0,0 +1,631 @@
+// Parses action messages written for Telegram's parse_mode into styled spans
+//
+// The backend uses this to reject messages Telegram would fail to parse,
+// and the dashboard uses the same spans to preview how a message will render
+// https://core.telegram.org/bots/api#formatting-options
+use crate::api::action::ParseMode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedSpan {
+    pub text: String,
+    pub style: SpanStyle,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpanStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub spoiler: bool,
+    pub code: bool,
+    pub pre: bool,
+    pub quote: bool,
+    pub link: Option<String>,
+}
+
+// the text with all the markup removed
+pub fn plain_text(spans: &[FormattedSpan]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+pub fn parse(text: &str, parse_mode: ParseMode) -> Result<Vec<FormattedSpan>, String> {
+    match parse_mode {
+        ParseMode::Plain => Ok(vec![FormattedSpan {
+            text: text.to_string(),
+            style: SpanStyle::default(),
+        }]),
+        ParseMode::MarkdownV2 => MarkdownParser::new(text).parse(),
+        ParseMode::Html => HtmlParser::new(text).parse(),
+    }
+}
+
+// characters that must be escaped with a backslash outside of entities in MarkdownV2
+pub const MARKDOWN_V2_RESERVED: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entity {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    // the index of the span the link text starts at, set when it's opened
+    // since the url only comes at the end
+    Link(usize),
+    Code,
+    Pre,
+    Quote,
+}
+
+impl Entity {
+    fn describe(&self) -> &'static str {
+        match self {
+            Entity::Bold => "bold",
+            Entity::Italic => "italic",
+            Entity::Underline => "underline",
+            Entity::Strikethrough => "strikethrough",
+            Entity::Spoiler => "spoiler",
+            Entity::Link(_) => "link",
+            Entity::Code => "code",
+            Entity::Pre => "pre",
+            Entity::Quote => "quote",
+        }
+    }
+}
+
+// shared by both parsers, tracks which entities are open and builds up the spans
+struct SpanBuilder {
+    spans: Vec<FormattedSpan>,
+    open: Vec<Entity>,
+    buf: String,
+}
+
+impl SpanBuilder {
+    fn new() -> Self {
+        Self {
+            spans: Vec::new(),
+            open: Vec::new(),
+            buf: String::new(),
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        self.buf.push(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    fn is_open(&self, entity: Entity) -> bool {
+        self.open
+            .iter()
+            .any(|open| std::mem::discriminant(open) == std::mem::discriminant(&entity))
+    }
+
+    fn flush(&mut self) {
+        if self.buf.is_empty() {
+            return;
+        }
+
+        let mut style = SpanStyle::default();
+        for entity in &self.open {
+            match entity {
+                Entity::Bold => style.bold = true,
+                Entity::Italic => style.italic = true,
+                Entity::Underline => style.underline = true,
+                Entity::Strikethrough => style.strikethrough = true,
+                Entity::Spoiler => style.spoiler = true,
+                // filled in when the link is closed
+                Entity::Link(_) => {}
+                Entity::Code => style.code = true,
+                Entity::Pre => style.pre = true,
+                Entity::Quote => style.quote = true,
+            }
+        }
+
+        self.spans.push(FormattedSpan {
+            text: std::mem::take(&mut self.buf),
+            style,
+        });
+    }
+
+    fn open(&mut self, entity: Entity) -> Result<(), String> {
+        if self.is_open(entity) {
+            return Err(format!("{} can't be nested in itself", entity.describe()));
+        }
+        self.flush();
+        self.open.push(match entity {
+            Entity::Link(_) => Entity::Link(self.spans.len()),
+            entity => entity,
+        });
+        Ok(())
+    }
+
+    fn close(&mut self, entity: Entity) -> Result<Entity, String> {
+        match self.open.last() {
+            Some(last) if std::mem::discriminant(last) == std::mem::discriminant(&entity) => {
+                self.flush();
+                Ok(self.open.pop().unwrap())
+            }
+            Some(last) => Err(format!(
+                "{} was closed before {}",
+                entity.describe(),
+                last.describe()
+            )),
+            None => Err(format!("{} was closed but never opened", entity.describe())),
+        }
+    }
+
+    fn toggle(&mut self, entity: Entity) -> Result<(), String> {
+        if self.is_open(entity) {
+            self.close(entity).map(|_| ())
+        } else {
+            self.open(entity)
+        }
+    }
+
+    fn close_link(&mut self, url: String) -> Result<(), String> {
+        match self.close(Entity::Link(0))? {
+            Entity::Link(start) => {
+                if url.trim().is_empty() {
+                    return Err("link is missing a url".to_string());
+                }
+                for span in &mut self.spans[start..] {
+                    span.style.link = Some(url.clone());
+                }
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn finish(mut self) -> Result<Vec<FormattedSpan>, String> {
+        // quotes end with the line, which may also be the end of the message
+        if let Some(Entity::Quote) = self.open.last() {
+            self.close(Entity::Quote)?;
+        }
+        match self.open.last() {
+            Some(entity) => Err(format!("{} was never closed", entity.describe())),
+            None => {
+                self.flush();
+                Ok(self.spans)
+            }
+        }
+    }
+}
+
+struct MarkdownParser {
+    chars: Vec<char>,
+    pos: usize,
+    builder: SpanBuilder,
+}
+
+impl MarkdownParser {
+    fn new(text: &str) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            pos: 0,
+            builder: SpanBuilder::new(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.pos == 0 || self.chars[self.pos - 1] == '\n'
+    }
+
+    fn parse(mut self) -> Result<Vec<FormattedSpan>, String> {
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' => {
+                    let escaped = self.escaped()?;
+                    self.builder.push(escaped);
+                }
+                '`' if self.starts_with("```") => {
+                    self.pos += 3;
+                    let mut content = self.read_code("```")?;
+                    // the first line is the language, if it's on its own line
+                    if let Some((language, rest)) = content.split_once('\n') {
+                        if !language.is_empty() && !language.contains(char::is_whitespace) {
+                            content = rest.to_string();
+                        }
+                    }
+                    self.builder.open(Entity::Pre)?;
+                    self.builder.push_str(&content);
+                    self.builder.close(Entity::Pre)?;
+                }
+                '`' => {
+                    self.pos += 1;
+                    let content = self.read_code("`")?;
+                    self.builder.open(Entity::Code)?;
+                    self.builder.push_str(&content);
+                    self.builder.close(Entity::Code)?;
+                }
+                '*' => {
+                    self.pos += 1;
+                    self.builder.toggle(Entity::Bold)?;
+                }
+                '_' if self.starts_with("__") => {
+                    self.pos += 2;
+                    self.builder.toggle(Entity::Underline)?;
+                }
+                '_' => {
+                    self.pos += 1;
+                    self.builder.toggle(Entity::Italic)?;
+                }
+                '~' => {
+                    self.pos += 1;
+                    self.builder.toggle(Entity::Strikethrough)?;
+                }
+                '|' if self.starts_with("||") => {
+                    self.pos += 2;
+                    self.builder.toggle(Entity::Spoiler)?;
+                }
+                '[' => {
+                    self.pos += 1;
+                    self.builder.open(Entity::Link(0))?;
+                }
+                ']' if self.builder.is_open(Entity::Link(0)) => {
+                    self.pos += 1;
+                    if self.peek(0) != Some('(') {
+                        return Err("link text must be followed by (url)".to_string());
+                    }
+                    self.pos += 1;
+                    let url = self.read_url()?;
+                    self.builder.close_link(url)?;
+                }
+                '>' if self.at_line_start() => {
+                    self.pos += 1;
+                    self.builder.open(Entity::Quote)?;
+                }
+                '\n' if self.builder.open.last() == Some(&Entity::Quote) => {
+                    self.builder.close(Entity::Quote)?;
+                    self.pos += 1;
+                    self.builder.push('\n');
+                }
+                c if MARKDOWN_V2_RESERVED.contains(&c) => {
+                    return Err(format!("'{c}' must be escaped as '\\{c}'"));
+                }
+                c => {
+                    self.pos += 1;
+                    self.builder.push(c);
+                }
+            }
+        }
+
+        self.builder.finish()
+    }
+
+    fn escaped(&mut self) -> Result<char, String> {
+        match self.peek(1) {
+            Some(c) if (1..=126).contains(&(c as u32)) => {
+                self.pos += 2;
+                Ok(c)
+            }
+            Some(c) => Err(format!("'{c}' can't be escaped")),
+            None => Err("the message can't end with a backslash".to_string()),
+        }
+    }
+
+    // inside code, only ` and \ need escaping
+    fn read_code(&mut self, end: &str) -> Result<String, String> {
+        let mut content = String::new();
+        loop {
+            if self.starts_with(end) {
+                self.pos += end.chars().count();
+                return Ok(content);
+            }
+            match self.peek(0) {
+                Some('\\') => content.push(self.escaped()?),
+                Some(c) => {
+                    self.pos += 1;
+                    content.push(c);
+                }
+                None => return Err("code was never closed".to_string()),
+            }
+        }
+    }
+
+    // inside a link url, only ) and \ need escaping
+    fn read_url(&mut self) -> Result<String, String> {
+        let mut url = String::new();
+        loop {
+            match self.peek(0) {
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(url);
+                }
+                Some('\\') => url.push(self.escaped()?),
+                Some(c) => {
+                    self.pos += 1;
+                    url.push(c);
+                }
+                None => return Err("link url was never closed".to_string()),
+            }
+        }
+    }
+}
+
+struct HtmlParser<'a> {
+    text: &'a str,
+    pos: usize,
+    builder: SpanBuilder,
+    // the href of the currently open <a>
+    link_url: Option<String>,
+}
+
+impl<'a> HtmlParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            builder: SpanBuilder::new(),
+            link_url: None,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<FormattedSpan>, String> {
+        while let Some(c) = self.text[self.pos..].chars().next() {
+            match c {
+                '<' => {
+                    let end = self.text[self.pos..]
+                        .find('>')
+                        .ok_or_else(|| "'<' must be written as &lt;".to_string())?;
+                    let tag = &self.text[self.pos + 1..self.pos + end];
+                    self.pos += end + 1;
+                    self.tag(tag)?;
+                }
+                '&' => {
+                    let end = self.text[self.pos..]
+                        .find(';')
+                        .ok_or_else(|| "'&' must be written as &amp;".to_string())?;
+                    let entity = &self.text[self.pos + 1..self.pos + end];
+                    self.pos += end + 1;
+                    let c = Self::entity(entity)?;
+                    self.builder.push(c);
+                }
+                '>' => return Err("'>' must be written as &gt;".to_string()),
+                c => {
+                    self.pos += c.len_utf8();
+                    self.builder.push(c);
+                }
+            }
+        }
+
+        self.builder.finish()
+    }
+
+    fn entity(entity: &str) -> Result<char, String> {
+        match entity {
+            "lt" => Ok('<'),
+            "gt" => Ok('>'),
+            "amp" => Ok('&'),
+            "quot" => Ok('"'),
+            _ => {
+                let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| format!("unsupported entity &{entity};"))
+            }
+        }
+    }
+
+    fn tag(&mut self, tag: &str) -> Result<(), String> {
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag.trim()),
+            None => (false, tag.trim()),
+        };
+
+        let (name, attributes) = match tag.split_once(char::is_whitespace) {
+            Some((name, attributes)) => (name, attributes.trim()),
+            None => (tag, ""),
+        };
+        let name = name.to_lowercase();
+
+        let entity = match name.as_str() {
+            "b" | "strong" => Entity::Bold,
+            "i" | "em" => Entity::Italic,
+            "u" | "ins" => Entity::Underline,
+            "s" | "strike" | "del" => Entity::Strikethrough,
+            "tg-spoiler" => Entity::Spoiler,
+            "span" => {
+                if !closing && Self::attribute(attributes, "class").as_deref() != Some("tg-spoiler")
+                {
+                    return Err("<span> is only supported with class=\"tg-spoiler\"".to_string());
+                }
+                Entity::Spoiler
+            }
+            "a" => Entity::Link(0),
+            "code" => Entity::Code,
+            "pre" => Entity::Pre,
+            "blockquote" => Entity::Quote,
+            _ => return Err(format!("<{name}> is not supported")),
+        };
+
+        match (closing, entity) {
+            (true, Entity::Link(_)) => {
+                let url = self.link_url.take().unwrap_or_default();
+                self.builder.close_link(url)
+            }
+            (true, entity) => self.builder.close(entity).map(|_| ()),
+            (false, Entity::Link(_)) => {
+                let url = Self::attribute(attributes, "href")
+                    .ok_or_else(|| "<a> must have an href".to_string())?;
+                self.builder.open(entity)?;
+                self.link_url = Some(url);
+                Ok(())
+            }
+            (false, entity) => self.builder.open(entity),
+        }
+    }
+
+    // name="value" or name='value'
+    fn attribute(attributes: &str, name: &str) -> Option<String> {
+        let rest = attributes.split_once(&format!("{name}="))?.1;
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = rest[1..].split(quote).next()?;
+        Some(value.replace("&amp;", "&").replace("&quot;", "\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markdown(text: &str) -> Result<Vec<FormattedSpan>, String> {
+        parse(text, ParseMode::MarkdownV2)
+    }
+
+    fn html(text: &str) -> Result<Vec<FormattedSpan>, String> {
+        parse(text, ParseMode::Html)
+    }
+
+    #[test]
+    fn markdown_reserved_must_be_escaped() {
+        for c in MARKDOWN_V2_RESERVED {
+            let text = format!("a {c} b");
+            assert!(markdown(&text).is_err(), "unescaped {c} was accepted");
+
+            let spans = markdown(&format!("a \\{c} b")).unwrap();
+            assert_eq!(plain_text(&spans), text);
+        }
+    }
+
+    #[test]
+    fn markdown_trailing_backslash() {
+        assert!(markdown("a \\").is_err());
+        assert_eq!(plain_text(&markdown("a \\\\").unwrap()), "a \\");
+    }
+
+    #[test]
+    fn markdown_nested() {
+        let spans = markdown("*bold _both_*").unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].text, "bold ");
+        assert!(spans[0].style.bold && !spans[0].style.italic);
+        assert_eq!(spans[1].text, "both");
+        assert!(spans[1].style.bold && spans[1].style.italic);
+    }
+
+    #[test]
+    fn markdown_misnested() {
+        assert_eq!(
+            markdown("*bold _both* italic_").unwrap_err(),
+            "bold was closed before italic"
+        );
+        assert_eq!(markdown("*bold").unwrap_err(), "bold was never closed");
+        assert_eq!(
+            markdown("[text *bold](https://example.com)*").unwrap_err(),
+            "link was closed before bold"
+        );
+    }
+
+    #[test]
+    fn markdown_link() {
+        let spans = markdown("[a *b*](https://example.com/\\)x)").unwrap();
+        assert_eq!(plain_text(&spans), "a b");
+        for span in &spans {
+            assert_eq!(span.style.link.as_deref(), Some("https://example.com/)x"));
+        }
+        assert!(spans[1].style.bold);
+    }
+
+    #[test]
+    fn markdown_link_empty_url() {
+        assert_eq!(markdown("[text]()").unwrap_err(), "link is missing a url");
+        assert_eq!(markdown("[text]( )").unwrap_err(), "link is missing a url");
+        assert!(markdown("[text]").is_err());
+        assert!(markdown("[text](https://example.com").is_err());
+    }
+
+    #[test]
+    fn markdown_code() {
+        let spans = markdown("`a * b` and ```rust\nlet x = 1;```").unwrap();
+        assert_eq!(spans[0].text, "a * b");
+        assert!(spans[0].style.code);
+        assert_eq!(spans[1].text, " and ");
+        assert_eq!(spans[2].text, "let x = 1;");
+        assert!(spans[2].style.pre);
+        assert!(markdown("`never closed").is_err());
+    }
+
+    #[test]
+    fn markdown_quote() {
+        // closed by the end of the message
+        let spans = markdown(">quoted").unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "quoted");
+        assert!(spans[0].style.quote);
+
+        // closed by the end of the line
+        let spans = markdown(">quoted\nnot").unwrap();
+        assert!(spans[0].style.quote);
+        assert_eq!(spans[1].text, "\nnot");
+        assert!(!spans[1].style.quote);
+
+        // only at the start of a line
+        assert!(markdown("a >b").is_err());
+
+        // other entities still have to close within the quote
+        assert!(markdown(">*bold").is_err());
+    }
+
+    #[test]
+    fn html_entities() {
+        let spans = html("&lt;a&gt; &amp; &quot;b&quot; &#65;&#x42;").unwrap();
+        assert_eq!(plain_text(&spans), "<a> & \"b\" AB");
+
+        assert!(html("a & b").is_err());
+        assert!(html("a > b").is_err());
+        assert!(html("a < b").is_err());
+        assert!(html("&nbsp;").is_err());
+    }
+
+    #[test]
+    fn html_tags() {
+        let spans =
+            html("<b>bold <i>both</i></b> <a href=\"https://example.com?a=1&amp;b=2\">link</a>")
+                .unwrap();
+        assert!(spans[0].style.bold);
+        assert!(spans[1].style.bold && spans[1].style.italic);
+        assert_eq!(
+            spans[3].style.link.as_deref(),
+            Some("https://example.com?a=1&b=2")
+        );
+
+        assert_eq!(
+            html("<b><i>x</b></i>").unwrap_err(),
+            "bold was closed before italic"
+        );
+        assert!(html("<b>x").is_err());
+        assert!(html("x</b>").is_err());
+        assert!(html("<a href=\"\">x</a>").is_err());
+        assert!(html("<a>x</a>").is_err());
+        assert!(html("<div>x</div>").is_err());
+        assert!(html("<span>x</span>").is_err());
+        assert!(
+            html("<span class=\"tg-spoiler\">x</span>").unwrap()[0]
+                .style
+                .spoiler
+        );
+    }
+
+    #[test]
+    fn plain_is_untouched() {
+        let text = "*not bold* <b>& ok";
+        assert_eq!(plain_text(&parse(text, ParseMode::Plain).unwrap()), text);
+    }
+}
@@ -28,6 +28,8 @@ impl Route {
             ["dashboard", dashboard_kind] => match *dashboard_kind {
                 "actions" => Self::Dashboard(Dashboard::Actions),
                 "destinations" => Self::Dashboard(Dashboard::Destinations),
+                "history" => Self::Dashboard(Dashboard::History),
+                "playground" => Self::Dashboard(Dashboard::Playground),
                 _ => Self::NotFound(NotFoundReason::BadUrl),
             },
             ["register", "start"] => Self::Landing(Landing::Auth(AuthRoute::RegisterStart { uid })),
@@ -84,6 +86,8 @@ impl std::fmt::Display for Route {
             Route::Dashboard(dashboard) => match dashboard {
                 Dashboard::Actions => format!("dashboard/actions"),
                 Dashboard::Destinations => format!("dashboard/destinations"),
+                Dashboard::History => format!("dashboard/history"),
+                Dashboard::Playground => format!("dashboard/playground"),
             },
             Route::NotFound(reason) => match reason {
                 NotFoundReason::BadUrl => "404".to_string(),
@@ -100,6 +104,8 @@ impl std::fmt::Display for Route {
 pub enum Dashboard {
     Actions,
     Destinations,
+    History,
+    Playground,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,81 @@
+// UUID newtype wrappers, so one kind of id can't be passed as another
+//
+// Displayed (and so stored) in the simple form, without hyphens
+// but parsed from either
+macro_rules! uuid_newtype {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(::serde::Deserialize, ::serde::Serialize, Debug, Clone, PartialEq, Eq)]
+            pub struct $name(::uuid::Uuid);
+
+            impl $name {
+                pub fn new(u: ::uuid::Uuid) -> Self {
+                    Self(u)
+                }
+            }
+
+            impl From<&$name> for ::wasm_bindgen::JsValue {
+                fn from(u: &$name) -> Self {
+                    u.to_string().into()
+                }
+            }
+
+            impl From<$name> for ::wasm_bindgen::JsValue {
+                fn from(u: $name) -> Self {
+                    u.to_string().into()
+                }
+            }
+
+            impl TryFrom<&str> for $name {
+                type Error = String;
+
+                fn try_from(value: &str) -> Result<Self, Self::Error> {
+                    match ::uuid::Uuid::parse_str(value) {
+                        Ok(u) => Ok(Self(u)),
+                        Err(e) => Err(e.to_string()),
+                    }
+                }
+            }
+
+            impl TryFrom<String> for $name {
+                type Error = String;
+
+                fn try_from(value: String) -> Result<Self, Self::Error> {
+                    value.as_str().try_into()
+                }
+            }
+
+            impl ::std::fmt::Display for $name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    write!(f, "{}", self.0.simple())
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use uuid_newtype;
+
+#[cfg(test)]
+mod tests {
+    uuid_newtype!(TestId);
+
+    const HYPHENATED: &str = "0192f4a1-7c3e-7b2a-9f10-3d5e6a7b8c9d";
+    const SIMPLE: &str = "0192f4a17c3e7b2a9f103d5e6a7b8c9d";
+
+    #[test]
+    fn displays_without_hyphens() {
+        let id = TestId::try_from(HYPHENATED).unwrap();
+        assert_eq!(id.to_string(), SIMPLE);
+        assert_eq!(format!("{id}"), SIMPLE);
+    }
+
+    #[test]
+    fn parses_either_form() {
+        assert_eq!(
+            TestId::try_from(SIMPLE).unwrap(),
+            TestId::try_from(HYPHENATED.to_string()).unwrap()
+        );
+        assert!(TestId::try_from("not a uuid").is_err());
+    }
+}
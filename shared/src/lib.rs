@@ -1,6 +1,8 @@
 pub mod api;
 pub mod auth;
 pub mod backend;
+pub mod format;
 pub mod frontend;
+mod id;
 pub mod logger;
 pub mod user;